@@ -119,7 +119,7 @@ while true; do
             cargo contract call \
               --contract "$CONTRACT_ADDRESS" \
               --message register_passport \
-              --args "\"$DATASET_URI\"" "$PAYLOAD_HASH" "\"$DATASET_TYPE\"" "$GRANULARITY" "$SUBJECT_ARG" \
+              --args "\"$DATASET_URI\"" "$PAYLOAD_HASH" "\"$DATASET_TYPE\"" "$GRANULARITY" "$SUBJECT_ARG" None \
               --suri "$SEED_PHRASE" \
               --url "$RPC_URL" \
               --gas 10000000000 \
@@ -162,10 +162,20 @@ while true; do
                 SUBJECT_ARG="None"
             fi
 
+            read -p "Diff URI (ipfs://...) (or Enter for None): " DIFF_URI
+            if [ -n "$DIFF_URI" ]; then
+                read -p "Diff hash (0x + 64 hex): " DIFF_HASH
+                DIFF_URI_ARG="Some(\"$DIFF_URI\")"
+                DIFF_HASH_ARG="Some($DIFF_HASH)"
+            else
+                DIFF_URI_ARG="None"
+                DIFF_HASH_ARG="None"
+            fi
+
             cargo contract call \
               --contract "$CONTRACT_ADDRESS" \
               --message update_dataset \
-              --args "$TOKEN_ID" "\"$DATASET_URI\"" "$PAYLOAD_HASH" "\"$DATASET_TYPE\"" "$SUBJECT_ARG" \
+              --args "$TOKEN_ID" "\"$DATASET_URI\"" "$PAYLOAD_HASH" "\"$DATASET_TYPE\"" "$SUBJECT_ARG" "$DIFF_URI_ARG" "$DIFF_HASH_ARG" \
               --suri "$SEED_PHRASE" \
               --url "$RPC_URL" \
               --skip-confirm \
@@ -7,15 +7,19 @@
 
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+pub mod psp34;
+
 #[ink::contract]
 mod dpp_contract_v2 {
+    use crate::psp34::{Id, PSP34Error, PSP34};
     use ink::prelude::string::String;
     use ink::storage::Mapping;
+    use ink::U256;
     use scale::{Decode, Encode};
 
     #[allow(dead_code)]
     pub type TokenId = u128;
-    
+
     /// Granularity level of the passport.
     #[derive(Encode, Decode, Clone, Debug, PartialEq)]
     #[cfg_attr(
@@ -39,7 +43,8 @@ mod dpp_contract_v2 {
     pub struct PassportRecord {
         pub token_id: u128,
 
-        /// Issuer authority (immutable). Only the issuer can update or revoke.
+        /// Issuer authority. Only the issuer can update or revoke. Changes only
+        /// through the two-step issuer transfer.
         pub issuer: Address,
 
         pub dataset_uri: String,
@@ -60,6 +65,15 @@ mod dpp_contract_v2 {
         pub granularity: Granularity,
 
         pub subject_id_hash: Option<[u8; 32]>,
+
+        /// Block number when the passport was archived (end of life).
+        pub archived_at: Option<u32>,
+
+        /// Block number from which the passport is expired (validity period end).
+        pub expires_at: Option<u32>,
+
+        /// Whether the token can change owner; fixed at registration.
+        pub transferable: bool,
     }
 
     /// Technical status (not a product lifecycle stage).
@@ -80,6 +94,37 @@ mod dpp_contract_v2 {
         Archived,
     }
 
+    /// Read visibility of a passport's full data.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Visibility {
+        /// Full record and history readable by anyone
+        #[default]
+        Public,
+
+        /// Full data only for issuer, owner, and approved accounts; others get a stub
+        Restricted,
+    }
+
+    /// Minimal public view of a passport (always readable, even when Restricted).
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PassportStub {
+        pub token_id: u128,
+
+        pub issuer: Address,
+
+        pub status: PassportStatus,
+
+        pub visibility: Visibility,
+    }
+
     /// Version history entry (immutable, append-only)
     ///
     /// Each update creates a new history entry, preserving the complete audit trail.
@@ -113,1167 +158,15499 @@ mod dpp_contract_v2 {
         /// For version 1, this is the issuer.
         /// For subsequent versions, this is who performed the update.
         pub updated_by: Address,
+
+        /// Optional URI of a machine-readable change summary against the previous version
+        ///
+        /// Always `None` for version 1.
+        pub diff_uri: Option<String>,
+
+        /// SHA-256 hash of the change summary referenced by `diff_uri`
+        pub diff_hash: Option<[u8; 32]>,
+
+        /// Issuer authority in force when this version was created
+        ///
+        /// Differs between versions only if issuer authority was transferred.
+        pub issuer: Address,
     }
 
-    /// Error types
-    #[derive(Debug, PartialEq, Eq, Clone)]
-    #[ink::scale_derive(Encode, Decode, TypeInfo)]
-    pub enum Error {
-        /// Token ID not found
-        TokenNotFound,
-        /// Invalid input (empty strings, etc.)
-        InvalidInput,
-        /// Unauthorized (caller is not the issuer)
-        Unauthorized,
-        /// Caller is not the current owner
-        NotOwner,
-        /// Caller is not owner nor approved operator
-        NotApproved,
-        /// Operation not allowed
-        NotAllowed,
-        /// Passport is revoked (cannot be updated)
-        PassportRevoked,
-        /// Passport is already revoked (cannot revoke again)
-        AlreadyRevoked,
+    /// Session key registered by an issuer for a constrained device (kiosk, scanner).
+    ///
+    /// A session key may update datasets of the issuer's tokens within
+    /// `first_token_id..=last_token_id` until `expires_at` (block number, exclusive).
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SessionKey {
+        pub issuer: Address,
+
+        pub first_token_id: u128,
+
+        pub last_token_id: u128,
+
+        pub expires_at: u32,
     }
 
-    pub type Result<T> = core::result::Result<T, Error>;
+    /// Activity counters for one reporting epoch (fixed-length block window).
+    #[derive(Encode, Decode, Clone, Debug, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EpochCounters {
+        pub registered: u128,
 
-    // Events
+        pub updated: u128,
 
-    /// Emitted when a passport is registered
-    #[ink(event)]
-    pub struct PassportRegistered {
-        #[ink(topic)]
-        pub token_id: u128,
-        #[ink(topic)]
-        pub issuer: Address,
-        pub dataset_uri: String,
-        pub payload_hash: [u8; 32],
-        pub dataset_type: String,
-        pub version: u32,
-        pub created_at: u32,
+        pub revoked: u128,
     }
 
-    /// Emitted when a passport dataset is updated
-    #[ink(event)]
-    pub struct PassportUpdated {
-        #[ink(topic)]
-        pub token_id: u128,
-        pub dataset_uri: String,
-        pub payload_hash: [u8; 32],
-        pub dataset_type: String,
-        pub version: u32,
-        pub updated_at: u32,
+    /// One custody handover (mint or transfer) in a token's chain of custody.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct CustodyEntry {
+        /// `None` for the initial mint
+        pub from: Option<Address>,
+
+        pub to: Address,
+
+        pub block_number: u32,
     }
 
-    /// Emitted when a passport is revoked
-    #[ink(event)]
-    pub struct PassportRevoked {
-        #[ink(topic)]
-        pub token_id: u128,
-        #[ink(topic)]
-        pub issuer: Address,
-        pub reason: Option<String>,
-        pub revoked_at: u32,
+    /// Compacted prefix of a token's custody history.
+    ///
+    /// `digest` is a hash chain over the pruned entries:
+    /// `digest_n = sha256(scale(digest_{n-1}, entry_n))`, starting from `[0u8; 32]`,
+    /// so archived entries can be checked against the on-chain checkpoint.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct CustodyCheckpoint {
+        /// Number of entries folded into `digest`
+        pub pruned_count: u32,
+
+        pub digest: [u8; 32],
     }
 
-    // Ownership events (ERC-721 compatible). Transfers do not change issuer authority.
+    /// Maximum custody entries folded into the checkpoint per call.
+    pub const MAX_CUSTODY_PRUNE_PER_CALL: u32 = 100;
 
-    #[ink(event)]
-    pub struct Transfer {
-        #[ink(topic)]
-        pub from: Option<Address>,
-        #[ink(topic)]
-        pub to: Option<Address>,
-        #[ink(topic)]
-        pub token_id: u128,
+    /// Contract-wide roles.
+    #[derive(Encode, Decode, Clone, Copy, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Role {
+        /// Contract configuration, fees, and role management
+        Admin,
+
+        /// Emergency functions (pausing)
+        Pauser,
+
+        /// Issuer onboarding decisions
+        Registrar,
+
+        /// Market-surveillance flags on passports
+        Regulator,
     }
 
-    #[ink(event)]
-    pub struct Approval {
-        #[ink(topic)]
-        pub owner: Address,
-        #[ink(topic)]
-        pub approved: Address,
-        #[ink(topic)]
-        pub token_id: u128,
+    /// Decision state of an issuer onboarding request.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum OnboardingStatus {
+        Pending,
+
+        Approved,
+
+        Rejected,
     }
 
-    #[ink(event)]
-    pub struct ApprovalForAll {
-        #[ink(topic)]
-        pub owner: Address,
-        #[ink(topic)]
-        pub operator: Address,
-        pub approved: bool,
+    /// Issuer onboarding record (self-service request + admin decision).
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct OnboardingRecord {
+        /// Hash of the issuer DID (e.g. SHA-256 of `did:web:...`)
+        pub did_hash: [u8; 32],
+
+        /// URI of the supporting evidence (registration documents, VC, ...)
+        pub evidence_uri: String,
+
+        /// SHA-256 hash of the evidence document
+        pub evidence_hash: [u8; 32],
+
+        pub status: OnboardingStatus,
+
+        pub requested_at: u32,
+
+        pub decided_at: Option<u32>,
     }
 
-    #[ink(storage)]
-    pub struct DppContractV2 {
-        passports: Mapping<u128, PassportRecord>,
+    /// Issuer branding document (logo, display name, verification page URL).
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct IssuerProfile {
+        pub uri: String,
 
-        next_token_id: u128,
+        /// SHA-256 hash of the profile document
+        pub hash: [u8; 32],
 
-        version_history: Mapping<(u128, u32), VersionHistory>,
+        pub updated_at: u32,
+    }
 
-        // subject_id_hash -> token_id (best-effort reverse lookup)
-        subject_id_to_token: Mapping<[u8; 32], u128>,
+    /// Registration fees per granularity level.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct RegistrationFees {
+        pub product_class: U256,
 
-        token_owner: Mapping<u128, Address>,
-        token_approvals: Mapping<u128, Address>,
-        owned_tokens_count: Mapping<Address, u128>,
-        operator_approvals: Mapping<(Address, Address), ()>,
+        pub batch: U256,
+
+        pub item: U256,
     }
 
-    impl DppContractV2 {
-        /// Constructor.
-        #[ink(constructor)]
-        pub fn new() -> Self {
+    /// What a Suspended passport is prevented from doing (set by the admin).
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct StatusPolicy {
+        /// Suspended passports reject `update_dataset`.
+        pub suspended_blocks_updates: bool,
+
+        /// Suspended passports reject transfers.
+        pub suspended_blocks_transfers: bool,
+    }
+
+    impl Default for StatusPolicy {
+        fn default() -> Self {
             Self {
-                passports: Mapping::new(),
-                next_token_id: 0,
-                version_history: Mapping::new(),
-                subject_id_to_token: Mapping::new(),
-                token_owner: Mapping::new(),
-                token_approvals: Mapping::new(),
-                owned_tokens_count: Mapping::new(),
-                operator_approvals: Mapping::new(),
+                suspended_blocks_updates: false,
+                suspended_blocks_transfers: true,
             }
         }
+    }
 
-        /// Register a new passport anchor.
-        /// * `payload_hash` - SHA-256 hash of the JWT string
-        /// * `dataset_type` - MIME type (e.g., "application/vc+jwt")
-        /// * `granularity` - Granularity level (ProductClass, Batch, or Item)
-        /// * `subject_id_hash` - Optional hashed canonical subject identifier
-        ///
-        /// # Returns
-        ///
-        /// Token ID of the newly registered passport
-        ///
-        /// # Errors
-        ///
-        /// * `InvalidInput` - Empty dataset_uri or dataset_type
-        #[ink(message)]
-        pub fn register_passport(
-            &mut self,
-            dataset_uri: String,
-            payload_hash: [u8; 32],
-            dataset_type: String,
-            granularity: Granularity,
-            subject_id_hash: Option<[u8; 32]>,
-        ) -> Result<u128> {
-            let caller = self.env().caller();
-            let token_id = self.next_token_id;
-            let block_number = self.env().block_number();
+    /// Who may `lock`/`unlock` a token's transfers (set by the admin).
+    #[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum LockAuthority {
+        #[default]
+        Owner,
 
-            if dataset_uri.is_empty() || dataset_type.is_empty() {
-                return Err(Error::InvalidInput);
-            }
+        Issuer,
+    }
 
-            let record = PassportRecord {
-                token_id,
-                issuer: caller,
-                dataset_uri: dataset_uri.clone(),
-                payload_hash,
-                dataset_type: dataset_type.clone(),
-                version: 1,
-                status: PassportStatus::Active,
-                created_at: block_number,
-                updated_at: block_number,
-                granularity: granularity.clone(),
-                subject_id_hash,
-            };
+    /// Policy in force when a passport was registered, kept for later disputes.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct IssuanceContext {
+        /// Registration fee required for the token's granularity
+        pub required_fee: U256,
 
-            self.passports.insert(token_id, &record);
+        /// Value actually transferred with the registration (the required fee
+        /// for tokens of a `register_batch` call, which pays the sum once)
+        pub fee_paid: U256,
 
-            self.add_token_to(&caller, token_id)?;
+        pub max_versions: u32,
 
-            self.next_token_id += 1;
+        pub strict_uri_mode: bool,
 
-            if let Some(subject_hash) = subject_id_hash {
-                self.subject_id_to_token.insert(subject_hash, &token_id);
-            }
+        pub status_policy: StatusPolicy,
 
-            let history_entry = VersionHistory {
-                version: 1,
-                dataset_uri: dataset_uri.clone(),
-                payload_hash,
-                dataset_type: dataset_type.clone(),
-                updated_at: block_number,
-                updated_by: caller,
-            };
-            self.version_history.insert((token_id, 1), &history_entry);
+        /// Whether the issuer had an approved onboarding request
+        pub issuer_approved: bool,
 
-            self.env().emit_event(PassportRegistered {
-                token_id,
-                issuer: caller,
-                dataset_uri,
-                payload_hash,
-                dataset_type,
-                version: 1,
-                created_at: block_number,
-            });
+        /// Whether registration was restricted to trusted issuers (allowlist
+        /// or external issuer registry)
+        pub permissioned: bool,
+    }
 
-            self.env().emit_event(Transfer {
-                from: None,
-                to: Some(caller),
-                token_id,
-            });
+    /// Recovery started by an owner's recovery account, executable after the challenge period.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingRecovery {
+        pub recovery: Address,
 
-            Ok(token_id)
-        }
+        pub new_owner: Address,
 
-        /// Get the current anchor record.
-        #[ink(message)]
-        pub fn get_passport(&self, token_id: u128) -> Option<PassportRecord> {
-            self.passports.get(token_id)
-        }
+        pub initiated_at: u32,
 
-        /// Update the anchor (issuer-only). Increments `version`.
-        ///
-        /// NOTE: Granularity is immutable after registration.
-        #[ink(message)]
-        pub fn update_dataset(
-            &mut self,
-            token_id: u128,
-            dataset_uri: String,
-            payload_hash: [u8; 32],
-            dataset_type: String,
-            subject_id_hash: Option<[u8; 32]>,
-        ) -> Result<()> {
-            let caller = self.env().caller();
-            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+        pub executable_at: u32,
+    }
 
-            if record.issuer != caller {
-                return Err(Error::Unauthorized);
-            }
+    /// Default recovery challenge period in blocks (~7 days at 6s blocks).
+    pub const DEFAULT_RECOVERY_CHALLENGE_PERIOD: u32 = 100_800;
 
-            // Cannot update revoked passports
-            if record.status == PassportStatus::Revoked {
-                return Err(Error::PassportRevoked);
-            }
+    /// N-of-M co-signer policy an issuer requires before its passports become Active.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct IssuancePolicy {
+        pub signers: ink::prelude::vec::Vec<Address>,
 
-            // Validation: check for empty strings
-            if dataset_uri.is_empty() || dataset_type.is_empty() {
-                return Err(Error::InvalidInput);
-            }
+        /// Number of distinct signer approvals required to activate
+        pub threshold: u32,
+    }
 
-            // Prepare new version
-            let block_number = self.env().block_number();
-            let new_version = record.version + 1;
+    /// Dataset update staged until the issuer's reviewer approves it.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingUpdate {
+        /// Version entry as it will be published (`updated_at` is set on approval)
+        pub entry: VersionHistory,
 
-            // Update fields in current record
-            let old_subject_hash = record.subject_id_hash;
-            record.dataset_uri = dataset_uri.clone();
-            record.payload_hash = payload_hash;
-            record.dataset_type = dataset_type.clone();
-            record.subject_id_hash = subject_id_hash;
-            record.version = new_version;
-            record.updated_at = block_number;
+        pub subject_id_hash: Option<[u8; 32]>,
+    }
 
-            // Update reverse lookup.
-            if let Some(old_hash) = old_subject_hash {
-                // Only remove if it points to this token_id (safety check)
-                if let Some(existing_token) = self.subject_id_to_token.get(old_hash) {
-                    if existing_token == token_id {
-                        // Note: ink! Mapping doesn't support deletion directly
-                        // We'll overwrite with new hash if it changed
-                    }
-                }
-            }
-            
-            // Add/update new mapping
-            if let Some(new_hash) = subject_id_hash {
-                self.subject_id_to_token.insert(new_hash, &token_id);
-            }
+    /// Maximum co-issuers per token (in addition to the primary issuer).
+    pub const MAX_CO_ISSUERS: usize = 8;
 
-            // Store updated record
-            self.passports.insert(token_id, &record);
+    /// Maximum co-signers in an issuance policy.
+    pub const MAX_CO_SIGNERS: usize = 16;
 
-            // Append-only version history.
-            let history_entry = VersionHistory {
-                version: new_version,
-                dataset_uri: dataset_uri.clone(),
-                payload_hash,
-                dataset_type: dataset_type.clone(),
-                updated_at: block_number,
-                updated_by: caller,
-            };
-            self.version_history.insert((token_id, new_version), &history_entry);
+    /// Maximum Drafts (including drafts held for review) per issuer.
+    pub const MAX_DRAFTS_PER_ISSUER: usize = 256;
 
-            // Emit event
-            self.env().emit_event(PassportUpdated {
-                token_id,
-                dataset_uri,
-                payload_hash,
-                dataset_type,
-                version: new_version,
-                updated_at: block_number,
-            });
+    /// Bonded reservation of a subject hash ahead of registration.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PreClaim {
+        pub claimant: Address,
 
-            Ok(())
-        }
+        /// Value locked by the claimant; refunded on registration, forfeited to
+        /// the fund if the window lapses.
+        pub bond: U256,
 
-        /// Revoke a passport (issuer-only).
-        #[ink(message)]
-        pub fn revoke_passport(
-            &mut self,
-            token_id: u128,
-            reason: Option<String>,
-        ) -> Result<()> {
-            let caller = self.env().caller();
-            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+        pub expires_at: u32,
+    }
 
-            // Authorization: only original issuer
-            if record.issuer != caller {
-                return Err(Error::Unauthorized);
-            }
+    /// Default pre-claim reservation window in blocks (~1 day at 6s blocks).
+    pub const DEFAULT_PRE_CLAIM_WINDOW: u32 = 14_400;
 
-            // Cannot revoke already revoked passports
-            if record.status == PassportStatus::Revoked {
-                return Err(Error::AlreadyRevoked);
-            }
+    /// Resolver / verification endpoint published by an issuer.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ResolverEndpoint {
+        /// SHA-256 hash of the endpoint URL
+        pub url_hash: [u8; 32],
 
-            // Update status
-            let block_number = self.env().block_number();
-            record.status = PassportStatus::Revoked;
-            record.updated_at = block_number;
+        /// Public key the endpoint signs responses with (max `MAX_PUBLIC_KEY_LEN` bytes)
+        pub public_key: ink::prelude::vec::Vec<u8>,
 
-            // Store
-            self.passports.insert(token_id, &record);
+        pub updated_at: u32,
+    }
 
-            // Emit event (reason stored in event, not in storage)
-            self.env().emit_event(PassportRevoked {
-                token_id,
-                issuer: caller,
-                reason,
-                revoked_at: block_number,
-            });
+    /// Reference to the same product anchored in another registry.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct FederatedRef {
+        /// Short code of the remote registry (e.g. a consortium chain or national system)
+        pub registry_code: String,
 
-            Ok(())
-        }
+        /// Hash of the remote registry's identifier for the product
+        pub remote_ref_hash: [u8; 32],
 
-        // Ownership (NFT-like).
+        pub added_at: u32,
+    }
 
-        #[ink(message)]
-        pub fn balance_of(&self, owner: Address) -> u128 {
-            self.owned_tokens_count.get(owner).unwrap_or(0)
-        }
+    /// Maximum federated references per token.
+    pub const MAX_FEDERATED_REFS: usize = 16;
 
-        #[ink(message)]
-        pub fn owner_of(&self, token_id: u128) -> Option<Address> {
-            self.token_owner.get(token_id)
-        }
+    /// Maximum length of a federated registry code.
+    pub const MAX_REGISTRY_CODE_LEN: usize = 32;
 
-        #[ink(message)]
-        pub fn get_approved(&self, token_id: u128) -> Option<Address> {
-            self.token_approvals.get(token_id)
-        }
+    /// Maximum length of a resolver endpoint public key (uncompressed secp256k1).
+    pub const MAX_PUBLIC_KEY_LEN: usize = 65;
 
-        #[ink(message)]
-        pub fn is_approved_for_all(&self, owner: Address, operator: Address) -> bool {
-            self.operator_approvals.contains((owner, operator))
-        }
+    /// Default reporting epoch length in blocks (~1 day at 6s blocks).
+    pub const DEFAULT_EPOCH_LENGTH: u32 = 14_400;
 
-        #[ink(message)]
-        pub fn approve(&mut self, to: Address, token_id: u128) -> Result<()> {
-            let caller = self.env().caller();
-            let owner = self.owner_of(token_id).ok_or(Error::TokenNotFound)?;
+    /// Status history entry (immutable, append-only).
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct StatusChange {
+        /// `None` for the initial status set at registration
+        pub old_status: Option<PassportStatus>,
 
-            if to == owner {
-                return Err(Error::NotAllowed);
-            }
+        pub new_status: PassportStatus,
 
-            if caller != owner && !self.is_approved_for_all(owner, caller) {
-                return Err(Error::NotApproved);
-            }
+        pub changed_by: Address,
 
-            self.token_approvals.insert(token_id, &to);
-            self.env().emit_event(Approval {
-                owner,
-                approved: to,
-                token_id,
-            });
+        pub reason: Option<String>,
 
-            Ok(())
-        }
+        pub block_number: u32,
+    }
 
-        #[ink(message)]
-        pub fn set_approval_for_all(&mut self, operator: Address, approved: bool) -> Result<()> {
-            let caller = self.env().caller();
+    /// Why, when, and by whom a passport was revoked.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct RevocationInfo {
+        pub reason: Option<String>,
 
-            if operator == caller {
-                return Err(Error::NotAllowed);
-            }
+        pub revoked_at: u32,
 
-            if approved {
-                self.operator_approvals.insert((caller, operator), &());
-            } else {
-                self.operator_approvals.remove((caller, operator));
-            }
+        pub revoked_by: Address,
+    }
 
-            self.env().emit_event(ApprovalForAll {
-                owner: caller,
-                operator,
-                approved,
-            });
+    /// How a physical product reached end of life.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum EndOfLifeReason {
+        Destroyed,
 
-            Ok(())
-        }
+        Recycled,
+    }
 
-        #[ink(message)]
-        pub fn transfer(&mut self, to: Address, token_id: u128) -> Result<()> {
-            let caller = self.env().caller();
-            self.transfer_token_from(&caller, &to, token_id)
-        }
+    /// Final end-of-life anchor recorded when the owner decommissions a passport.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EndOfLifeRecord {
+        pub reason: EndOfLifeReason,
 
-        #[ink(message)]
-        pub fn transfer_from(&mut self, from: Address, to: Address, token_id: u128) -> Result<()> {
-            self.transfer_token_from(&from, &to, token_id)
-        }
+        /// Hash of the end-of-life evidence (e.g. recycling certificate).
+        pub evidence_hash: [u8; 32],
 
-        // Query messages
+        pub decommissioned_by: Address,
 
-        /// Get next token ID (for informational purposes)
-        #[ink(message)]
-        pub fn next_token_id(&self) -> u128 {
-            self.next_token_id
-        }
+        pub decommissioned_at: u32,
+    }
 
-        /// Get specific version from history
-        ///
-        /// # Arguments
-        ///
-        /// * `token_id` - Token ID
-        /// * `version` - Version number (1-indexed)
+    /// Audit record of a burned token; the passport record itself is kept.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct BurnRecord {
+        /// Owner at the time of the burn
+        pub burned_by: Address,
+
+        pub burned_at: u32,
+
+        /// Whether the issuer had consented to the burn
+        pub issuer_consented: bool,
+    }
+
+    /// Maximum share holders of a co-owned passport.
+    pub const MAX_CO_OWNERS: usize = 16;
+
+    /// Shares of a jointly owned (co-owned) batch passport.
+    ///
+    /// The token stays with its owner of record; transferring it as a whole
+    /// needs approval from holders of at least `threshold` shares.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct CoOwnership {
+        /// Holders and their (non-zero) share counts
+        pub shares: ink::prelude::vec::Vec<(Address, u32)>,
+
+        /// Shares whose holders must approve a transfer of the whole token
+        pub threshold: u32,
+    }
+
+    impl CoOwnership {
+        pub fn shares_of(&self, account: Address) -> u32 {
+            self.shares
+                .iter()
+                .find(|(holder, _)| *holder == account)
+                .map_or(0, |(_, shares)| *shares)
+        }
+    }
+
+    /// Kind of consumer-facing notice (lighter-weight than a recall or revocation).
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum NoticeType {
+        SafetyInformation,
+
+        FirmwareUpdate,
+
+        UsageWarning,
+    }
+
+    /// Market-surveillance concern recorded on a passport by a regulator.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PassportFlag {
+        /// Hash of the off-chain statement of the concern
+        pub reason_hash: [u8; 32],
+
+        pub flagged_by: Address,
+
+        pub flagged_at: u32,
+    }
+
+    /// Time-limited notice an issuer binds to a passport.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Notice {
+        pub notice_type: NoticeType,
+
+        /// Location of the notice content
+        pub uri: String,
+
+        /// SHA-256 hash of the notice content
+        pub hash: [u8; 32],
+
+        pub posted_at: u32,
+
+        /// Block from which the notice is no longer shown
+        pub expires_at: u32,
+    }
+
+    /// Selector of `IssuerRegistry::is_registered(issuer) -> bool` (fixed in the
+    /// registry contract, see `issuer_registry/`).
+    pub const IS_REGISTERED_SELECTOR: [u8; 4] = [0x49, 0x53, 0x52, 0x45];
+
+    /// Selector of the `on_dpp_received(operator, from, token_id, data) -> [u8; 4]`
+    /// hook a contract must implement to receive tokens via `safe_transfer_from`.
+    /// The hook accepts by returning this selector.
+    pub const ON_DPP_RECEIVED_SELECTOR: [u8; 4] = ink::selector_bytes!("on_dpp_received");
+
+    // Extension IDs for `supports_interface`: the first four bytes of the
+    // BLAKE2b-256 hash of the extension name (as ink! message selectors).
+
+    /// PSP34 trait (`psp34.rs`)
+    pub const INTERFACE_PSP34: [u8; 4] = ink::selector_bytes!("PSP34");
+
+    /// `total_supply`, `token_by_index`, `token_of_owner_by_index`
+    pub const INTERFACE_ENUMERABLE: [u8; 4] = ink::selector_bytes!("DPP::Enumerable");
+
+    /// `collection_name`, `collection_symbol`, `token_uri`
+    pub const INTERFACE_METADATA: [u8; 4] = ink::selector_bytes!("DPP::Metadata");
+
+    /// Soulbound registration and `lock`/`unlock`/`is_locked` (ERC-5192 style)
+    pub const INTERFACE_LOCKABLE: [u8; 4] = ink::selector_bytes!("DPP::Lockable");
+
+    /// `set_royalty`, `royalty_info` (ERC-2981 style)
+    pub const INTERFACE_ROYALTY: [u8; 4] = ink::selector_bytes!("DPP::Royalty");
+
+    /// `safe_transfer_from` with the `on_dpp_received` hook
+    pub const INTERFACE_SAFE_TRANSFER: [u8; 4] = ink::selector_bytes!("DPP::SafeTransfer");
+
+    /// `supports_interface` itself
+    pub const INTERFACE_DETECTION: [u8; 4] = ink::selector_bytes!("DPP::SupportsInterface");
+
+    /// Extensions this contract implements.
+    pub const SUPPORTED_INTERFACES: [[u8; 4]; 7] = [
+        INTERFACE_PSP34,
+        INTERFACE_ENUMERABLE,
+        INTERFACE_METADATA,
+        INTERFACE_LOCKABLE,
+        INTERFACE_ROYALTY,
+        INTERFACE_SAFE_TRANSFER,
+        INTERFACE_DETECTION,
+    ];
+
+    /// Basis points of a full sale price (royalties are at most 100%).
+    pub const MAX_ROYALTY_BASIS_POINTS: u16 = 10_000;
+
+    /// Maximum unexpired notices per token.
+    pub const MAX_NOTICES: usize = 8;
+
+    /// Conformity endorsement a registered certifier attaches to a passport.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Endorsement {
+        pub certifier: Address,
+
+        /// Hash identifying what is endorsed (e.g. standard and scope)
+        pub claim_hash: [u8; 32],
+
+        /// Location of the certificate or audit report
+        pub evidence_uri: String,
+
+        /// SHA-256 hash of the evidence document
+        pub evidence_hash: [u8; 32],
+
+        /// Hash of the external certificate number, indexed for
+        /// `find_attestation_by_certificate`
+        pub certificate_hash: Option<[u8; 32]>,
+
+        pub endorsed_at: u32,
+    }
+
+    /// Maximum endorsements per token (one per certifier).
+    pub const MAX_ENDORSEMENTS: usize = 16;
+
+    /// Certifier endorsement signed off-chain, attached at registration by
+    /// `register_with_attestations`.
+    #[derive(Debug, PartialEq, Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct SignedAttestation {
+        pub certifier: Address,
+
+        pub claim_hash: [u8; 32],
+
+        pub evidence_uri: String,
+
+        pub evidence_hash: [u8; 32],
+
+        pub certificate_hash: Option<[u8; 32]>,
+
+        /// secp256k1 signature by `certifier` over `attestation_message`
+        pub signature: [u8; 65],
+    }
+
+    /// Software bill of materials anchored for a connected product.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SbomEntry {
+        /// Firmware/software version the SBOM describes
+        pub software_version: String,
+
+        pub sbom_uri: String,
+
+        /// SHA-256 hash of the SBOM document
+        pub sbom_hash: [u8; 32],
+
+        pub set_by: Address,
+
+        pub set_at: u32,
+    }
+
+    /// Circular-economy actor allowed to append service records.
+    #[derive(Encode, Decode, Clone, Copy, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum ServiceProvider {
+        Repairer,
+
+        Recycler,
+    }
+
+    /// Kind of a service record.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum ServiceRecordKind {
+        /// Appended by a registered repairer
+        Repair,
+
+        /// Appended by a registered recycler
+        EndOfLife,
+    }
+
+    /// Repair or end-of-life record appended by a registered service provider.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ServiceRecord {
+        pub kind: ServiceRecordKind,
+
+        /// Location of the record document
+        pub uri: String,
+
+        /// SHA-256 hash of the record document
+        pub hash: [u8; 32],
+
+        pub recorded_by: Address,
+
+        pub recorded_at: u32,
+    }
+
+    /// Compact verifier-facing view of a passport's current anchor and custody.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct VerificationSummary {
+        pub token_id: u128,
+
+        pub issuer: Address,
+
+        pub owner: Option<Address>,
+
+        pub status: PassportStatus,
+
+        pub version: u32,
+
+        pub payload_hash: [u8; 32],
+
+        /// True while the issuer is still the token owner (not yet "in the wild")
+        pub issuer_holds_custody: bool,
+
+        /// Unexpired issuer notices (safety information, firmware updates, warnings)
+        pub notices: ink::prelude::vec::Vec<Notice>,
+    }
+
+    /// Result of `self_check`: one flag per storage invariant of a token.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SelfCheckReport {
+        pub token_id: u128,
+
+        /// Version history has an entry for each of the latest
+        /// `MAX_SELF_CHECK_VERSIONS` versions
+        pub history_contiguous: bool,
+
+        /// Token has an owner with a non-zero balance (none once burned)
+        pub owner_set: bool,
+
+        /// Status log is non-empty and ends in the record's current status
+        pub status_log_consistent: bool,
+
+        /// Custody checkpoint is within the log and the latest entry names the owner
+        pub custody_log_consistent: bool,
+
+        /// Subject hash (if any) resolves back to this token
+        pub subject_mapping_consistent: bool,
+
+        /// Owner balance matches the owner's index length, and the token and
+        /// owner indexes list the token exactly while it is live
+        pub counters_consistent: bool,
+
+        /// All of the above hold
+        pub healthy: bool,
+    }
+
+    /// Admin-only operation; goes through the action queue when a timelock or
+    /// an admin threshold above one is set.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum AdminAction {
+        SetUpdateFee(U256),
+
+        SetRegistrationFee(Granularity, U256),
+
+        SetAllowlistMode(bool),
+
+        AddTrustedIssuer(Address),
+
+        RemoveTrustedIssuer(Address),
+
+        SetTimelockDelay(u32),
+
+        SetAdminThreshold(u32),
+
+        GrantRole(Role, Address),
+
+        RevokeRole(Role, Address),
+
+        SetIssuerRegistry(Option<Address>),
+
+        SetRecipientAllowlist(bool),
+
+        SetAllowedRecipient(Address, bool),
+
+        SetFundAddress(Address),
+
+        FreezeToken(u128),
+
+        UnfreezeToken(u128),
+
+        SetMaxVersions(u32),
+
+        SetUpdateTransferCooldown(u32),
+
+        StartMigration(Migration),
+
+        AddCertifier(Address),
+
+        RemoveCertifier(Address),
+
+        AddServiceProvider(ServiceProvider, Address),
+
+        RemoveServiceProvider(ServiceProvider, Address),
+
+        SetStrictUriMode(bool),
+
+        SetTokenBaseUri(Option<String>),
+
+        BlacklistAccount(Address),
+
+        UnblacklistAccount(Address),
+
+        SetStatusPolicy(StatusPolicy),
+
+        SetLockAuthority(LockAuthority),
+
+        SetVerboseEvents(bool),
+
+        SetDepositPerByte(U256),
+
+        WithdrawFund(U256),
+
+        SetPreClaimTerms(U256, u32),
+
+        SetEpochLength(u32),
+
+        SetRecoveryChallengePeriod(u32),
+
+        SetMinUpdateInterval(u128, u32),
+
+        PruneCustodyHistory(u128, u32),
+    }
+
+    /// Admin action waiting for its timelock to pass.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct QueuedAction {
+        pub action: AdminAction,
+
+        pub queued_by: Address,
+
+        /// First block at which the action can be executed
+        pub eta: u32,
+
+        /// Admins that confirmed the action (the queuing admin included)
+        pub confirmations: ink::prelude::vec::Vec<Address>,
+    }
+
+    /// Result of `health`: contract-wide counters and the invariants between them.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct HealthReport {
+        /// Token IDs issued so far
+        pub minted: u128,
+
+        /// Tokens that currently exist
+        pub live_tokens: u128,
+
+        /// Sum of all owners' balances
+        pub owned_total: u128,
+
+        pub admin_count: u32,
+
+        pub paused: bool,
+
+        /// Fees collected and not yet withdrawn
+        pub fund_balance: U256,
+
+        /// Native balance held by the contract
+        pub contract_balance: U256,
+
+        /// Every live token is counted in exactly one owner balance
+        pub balances_consistent: bool,
+
+        /// The contract holds at least the fund balance
+        pub fund_covered: bool,
+
+        /// All invariants hold
+        pub healthy: bool,
+    }
+
+    /// Collection name reported to NFT tooling.
+    pub const COLLECTION_NAME: &str = "FIDES Digital Product Passports";
+
+    /// Collection symbol reported to NFT tooling.
+    pub const COLLECTION_SYMBOL: &str = "DPP";
+
+    /// Maximum items a single `continue_migration` call processes.
+    pub const MAX_MIGRATION_ITEMS_PER_CALL: u32 = 500;
+
+    /// Latest versions `self_check` inspects for history gaps.
+    pub const MAX_SELF_CHECK_VERSIONS: u32 = 64;
+
+    /// Named long-running state transformation, run in batches.
+    #[derive(Encode, Decode, Clone, Copy, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Migration {
+        /// Recompute the `health` counters from token storage (backfill for
+        /// registries deployed before the counters were maintained)
+        RecountTokens,
+
+        /// Add tokens missing from the enumeration indexes (registries
+        /// deployed before they were maintained)
+        BackfillTokenIndex,
+    }
+
+    /// Progress of the running migration.
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct MigrationState {
+        pub migration: Migration,
+
+        /// Next token ID to process
+        pub cursor: u128,
+
+        /// Items processed so far
+        pub processed: u128,
+
+        pub started_at: u32,
+
+        /// Scratch counters of `RecountTokens`, written back on completion.
+        /// Mints, burns, and transfers of already scanned IDs adjust them in place.
+        pub live: u128,
+        pub owned: u128,
+    }
+
+    /// Error types
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        /// Token ID not found
+        TokenNotFound,
+        /// Invalid input (empty strings, etc.)
+        InvalidInput,
+        /// Unauthorized (caller is not the issuer)
+        Unauthorized,
+        /// Caller is not the current owner
+        NotOwner,
+        /// Caller is not owner nor approved operator
+        NotApproved,
+        /// Operation not allowed
+        NotAllowed,
+        /// Passport is revoked (cannot be updated)
+        PassportRevoked,
+        /// Passport is already revoked (cannot revoke again)
+        AlreadyRevoked,
+        /// Token reached the configured version limit (must be superseded)
+        MaxVersionsReached,
+        /// Token has been superseded by a newer token
+        AlreadySuperseded,
+        /// Transferred value is below the configured fee
+        InsufficientFee,
+        /// Native token transfer failed
+        TransferFailed,
+        /// Malformed `ipfs://` URI (strict mode)
+        InvalidUri,
+        /// Passport is suspended (cannot be transferred)
+        PassportSuspended,
+        /// Passport is archived (read-only)
+        PassportArchived,
+        /// Passport is still a Draft (must be activated first)
+        PassportDraft,
+        /// Passport has no expiry or its deadline has not passed yet
+        NotExpired,
+        /// Recovery challenge period has not elapsed yet
+        ChallengePeriodActive,
+        /// Minimum interval between dataset updates has not elapsed yet
+        TooFrequent,
+        /// Signature does not recover to the expected account
+        InvalidSignature,
+        /// Token transfer cool-down has not elapsed yet
+        TransferThrottled,
+        /// Registration is restricted to trusted issuers and the caller is not one
+        NotTrustedIssuer,
+        /// Call to the configured issuer registry contract failed
+        IssuerRegistryUnavailable,
+        /// Registration, updates, and transfers are paused
+        ContractPaused,
+        /// Token is frozen by an administrator
+        TokenFrozen,
+        /// Recipient has not accepted the token's current transfer terms
+        TermsNotAccepted,
+        /// Queued admin action's delay has not passed yet
+        TimelockActive,
+        /// Recipient contract did not accept the token in `safe_transfer_from`
+        ReceiverRejected,
+        /// Token transfers are locked
+        TokenLocked,
+        /// Recipient is not on an enabled recipient allowlist
+        RecipientNotAllowed,
+        /// Account is blacklisted by an administrator
+        AccountBlacklisted,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Maximum number of tokens processed by one batch message.
+    pub const MAX_BATCH_SIZE: usize = 100;
+
+    /// Per-token outcome of a batch operation (`error = None` on success).
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct BatchItemResult {
+        pub token_id: u128,
+        pub error: Option<Error>,
+    }
+
+    /// Maximum token IDs in one `reserve_token_ids` range.
+    pub const MAX_RESERVED_IDS: u32 = 100_000;
+
+    /// Maximum token ID ranges reserved per issuer.
+    pub const MAX_ID_RESERVATIONS: usize = 16;
+
+    /// Dataset fields of one passport (as in `register_passport`), e.g. an
+    /// item of a `register_batch` call.
+    #[derive(Debug, PartialEq, Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct RegistrationInput {
+        pub dataset_uri: String,
+        pub payload_hash: [u8; 32],
+        pub dataset_type: String,
+        pub granularity: Granularity,
+        pub subject_id_hash: Option<[u8; 32]>,
+        /// Expire this many blocks after registration (see `set_expiry`)
+        pub validity_blocks: Option<u32>,
+    }
+
+    /// How `mint_passport` issues a passport, beyond its dataset fields.
+    struct MintParams {
+        issuer: Address,
+        owner: Address,
+        status: PassportStatus,
+        /// `false` for soulbound passports
+        transferable: bool,
+        /// Fee already collected by the caller (batch registration)
+        prepaid: bool,
+        /// Register under this reserved ID instead of the next sequential one
+        reserved_id: Option<u128>,
+    }
+
+    impl MintParams {
+        /// Active, transferable, fee collected per passport, next sequential ID.
+        fn new(issuer: Address, owner: Address) -> Self {
+            Self {
+                issuer,
+                owner,
+                status: PassportStatus::Active,
+                transferable: true,
+                prepaid: false,
+                reserved_id: None,
+            }
+        }
+    }
+
+    // URI validation (strict mode)
+
+    const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+    /// Syntactic CID check (no decoding): CIDv0 base58btc, CIDv1 base32 (`b`) or base58btc (`z`).
+    fn is_valid_cid(cid: &str) -> bool {
+        let bytes = cid.as_bytes();
+
+        // CIDv0: "Qm" + base58btc, always 46 characters (sha2-256 multihash)
+        if bytes.len() == 46 && bytes.starts_with(b"Qm") {
+            return bytes.iter().all(|b| BASE58_ALPHABET.contains(b));
+        }
+
+        match bytes.split_first() {
+            // CIDv1 base32: version byte 0x01 always encodes to a leading 'a'
+            Some((b'b', body)) => {
+                (58..=120).contains(&body.len())
+                    && body[0] == b'a'
+                    && body.iter().all(|b| BASE32_ALPHABET.contains(b))
+            }
+            Some((b'z', body)) => {
+                (46..=100).contains(&body.len()) && body.iter().all(|b| BASE58_ALPHABET.contains(b))
+            }
+            _ => false,
+        }
+    }
+
+    /// Validate an `ipfs://<cid>[/path]` URI. Other schemes are not checked.
+    fn validate_ipfs_uri(uri: &str) -> Result<()> {
+        if let Some(rest) = uri.strip_prefix("ipfs://") {
+            let cid = rest.split('/').next().unwrap_or("");
+            if !is_valid_cid(cid) {
+                return Err(Error::InvalidUri);
+            }
+        }
+        Ok(())
+    }
+
+    // Events
+
+    /// Emitted when a passport is registered
+    #[ink(event)]
+    pub struct PassportRegistered {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub issuer: Address,
+        pub dataset_uri: String,
+        pub payload_hash: [u8; 32],
+        pub dataset_type: String,
+        pub version: u32,
+        pub created_at: u32,
+        pub transferable: bool,
+        pub expires_at: Option<u32>,
+        pub event_seq: u64,
+    }
+
+    /// Emitted once per `register_batch` call, after the per-token events
+    #[ink(event)]
+    pub struct BatchRegistered {
+        #[ink(topic)]
+        pub issuer: Address,
+        pub first_token_id: u128,
+        pub count: u32,
+    }
+
+    /// Emitted when an issuer reserves a range of token IDs
+    #[ink(event)]
+    pub struct TokenIdsReserved {
+        #[ink(topic)]
+        pub issuer: Address,
+        pub first_token_id: u128,
+        pub count: u32,
+    }
+
+    /// Emitted when a passport dataset is updated
+    #[ink(event)]
+    pub struct PassportUpdated {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub dataset_uri: String,
+        pub payload_hash: [u8; 32],
+        pub dataset_type: String,
+        pub version: u32,
+        pub updated_at: u32,
+        pub event_seq: u64,
+    }
+
+    /// Compact anchor event emitted on every registration and update.
+    ///
+    /// This is the minimal stream archive-light indexers must retain: it is
+    /// always emitted, even when verbose events are disabled.
+    #[ink(event)]
+    pub struct AnchorDigest {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub version: u32,
+        pub payload_hash: [u8; 32],
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the admin toggles verbose anchor events
+    #[ink(event)]
+    pub struct VerboseEventsChanged {
+        pub enabled: bool,
+    }
+
+    /// Emitted on every passport status change (activate, suspend, resume, revoke, archive, ...)
+    #[ink(event)]
+    pub struct PassportStatusChanged {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub old_status: PassportStatus,
+        pub new_status: PassportStatus,
+        pub changed_by: Address,
+        pub reason: Option<String>,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a passport is revoked
+    #[ink(event)]
+    pub struct PassportRevoked {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub issuer: Address,
+        pub reason: Option<String>,
+        pub revoked_at: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a Draft passport is activated
+    #[ink(event)]
+    pub struct PassportActivated {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub issuer: Address,
+        pub activated_at: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an issuer sets or clears its N-of-M issuance policy
+    #[ink(event)]
+    pub struct IssuancePolicyChanged {
+        #[ink(topic)]
+        pub issuer: Address,
+        /// `None` when the policy was cleared
+        pub policy: Option<IssuancePolicy>,
+    }
+
+    /// Emitted when an administrator freezes a token under investigation
+    #[ink(event)]
+    pub struct TokenFrozen {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub by: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an administrator lifts a token freeze
+    #[ink(event)]
+    pub struct TokenUnfrozen {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub by: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an issuer sets or clears its publication reviewer
+    #[ink(event)]
+    pub struct ReviewerChanged {
+        #[ink(topic)]
+        pub issuer: Address,
+        /// `None` when the reviewer was cleared
+        pub reviewer: Option<Address>,
+    }
+
+    /// Emitted when a dataset update is staged for reviewer approval
+    #[ink(event)]
+    pub struct UpdateSubmittedForReview {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub version: u32,
+        pub payload_hash: [u8; 32],
+        pub submitted_by: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a reviewer approves a pending registration or update
+    #[ink(event)]
+    pub struct PublicationApproved {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub version: u32,
+        #[ink(topic)]
+        pub reviewer: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a co-signer approves activation of a Draft passport
+    #[ink(event)]
+    pub struct ActivationApproved {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub signer: Address,
+        pub approvals: u32,
+        pub threshold: u32,
+        pub event_seq: u64,
+    }
+
+    /// Summary of a `revoke_batch` call (per-token `PassportRevoked` events are emitted too)
+    #[ink(event)]
+    pub struct BatchRevoked {
+        #[ink(topic)]
+        pub issuer: Address,
+        pub requested: u32,
+        pub revoked: u32,
+        pub reason: Option<String>,
+    }
+
+    /// Emitted when a passport is suspended (`until = None` for indefinite)
+    #[ink(event)]
+    pub struct PassportSuspended {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub issuer: Address,
+        pub reason: Option<String>,
+        pub suspended_at: u32,
+        pub until: Option<u32>,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a suspended passport is resumed
+    #[ink(event)]
+    pub struct PassportResumed {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub issuer: Address,
+        pub resumed_at: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the owner hands physical custody of an item to a custodian
+    #[ink(event)]
+    pub struct CustodianAssigned {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub custodian: Address,
+        pub owner: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the owner leases physical custody until a block
+    #[ink(event)]
+    pub struct CustodyLeased {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub custodian: Address,
+        pub owner: Address,
+        pub until: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a batch passport becomes co-owned
+    #[ink(event)]
+    pub struct CoOwnershipEnabled {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub total_shares: u32,
+        pub threshold: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when co-ownership shares change hands
+    #[ink(event)]
+    pub struct SharesTransferred {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub from: Address,
+        #[ink(topic)]
+        pub to: Address,
+        pub amount: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a share holder approves transferring a co-owned token
+    #[ink(event)]
+    pub struct CoOwnedTransferApproved {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub holder: Address,
+        pub to: Address,
+        /// Shares currently held by the approving holders
+        pub approved_shares: u32,
+        pub threshold: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a custodian's physical custody ends
+    #[ink(event)]
+    pub struct CustodyReleased {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub custodian: Address,
+        pub released_by: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when old custody entries are folded into the checkpoint digest
+    #[ink(event)]
+    pub struct CustodyHistoryPruned {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub pruned_count: u32,
+        pub total_pruned: u32,
+        pub digest: [u8; 32],
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a passport is archived at end of life
+    #[ink(event)]
+    pub struct PassportArchived {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub issuer: Address,
+        pub archived_at: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an issuer posts a notice on a passport
+    #[ink(event)]
+    pub struct NoticePosted {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub notice_type: NoticeType,
+        pub uri: String,
+        pub hash: [u8; 32],
+        pub expires_at: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a regulator flags a passport
+    #[ink(event)]
+    pub struct PassportFlagged {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub regulator: Address,
+        pub reason_hash: [u8; 32],
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a regulator clears a passport flag
+    #[ink(event)]
+    pub struct FlagCleared {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub regulator: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the owner decommissions a passport (product destroyed or recycled)
+    #[ink(event)]
+    pub struct PassportDecommissioned {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub owner: Address,
+        pub reason: EndOfLifeReason,
+        pub evidence_hash: [u8; 32],
+        pub decommissioned_at: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an account requests issuer onboarding
+    #[ink(event)]
+    pub struct IssuerOnboardingRequested {
+        #[ink(topic)]
+        pub account: Address,
+        pub did_hash: [u8; 32],
+        pub evidence_uri: String,
+        pub evidence_hash: [u8; 32],
+    }
+
+    /// Emitted when an issuer anchors or replaces its profile document
+    #[ink(event)]
+    pub struct IssuerProfileSet {
+        #[ink(topic)]
+        pub issuer: Address,
+        pub uri: String,
+        pub hash: [u8; 32],
+    }
+
+    /// Emitted when the admin approves an onboarding request
+    #[ink(event)]
+    pub struct IssuerApproved {
+        #[ink(topic)]
+        pub account: Address,
+        pub approved_by: Address,
+    }
+
+    /// Emitted when the admin rejects an onboarding request
+    #[ink(event)]
+    pub struct IssuerRejected {
+        #[ink(topic)]
+        pub account: Address,
+        pub rejected_by: Address,
+        pub reason: Option<String>,
+    }
+
+    /// Emitted when the issuer sets or clears the expiry of a passport
+    #[ink(event)]
+    pub struct PassportExpirySet {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub expires_at: Option<u32>,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an expired passport is archived
+    #[ink(event)]
+    pub struct PassportExpired {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub expires_at: u32,
+        pub expired_at: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a resolver endpoint is set (`token_id = None` for issuer-wide)
+    #[ink(event)]
+    pub struct ResolverEndpointSet {
+        #[ink(topic)]
+        pub issuer: Address,
+        #[ink(topic)]
+        pub token_id: Option<u128>,
+        pub url_hash: [u8; 32],
+        pub public_key: ink::prelude::vec::Vec<u8>,
+        /// Token event sequence number (`None` for issuer-wide endpoints)
+        pub event_seq: Option<u64>,
+    }
+
+    /// Emitted when an issuer links a token to another registry
+    #[ink(event)]
+    pub struct FederatedRefAdded {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub registry_code: String,
+        pub remote_ref_hash: [u8; 32],
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an issuer removes a link to another registry
+    #[ink(event)]
+    pub struct FederatedRefRemoved {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub registry_code: String,
+        pub remote_ref_hash: [u8; 32],
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a resolver endpoint is cleared (`token_id = None` for issuer-wide)
+    #[ink(event)]
+    pub struct ResolverEndpointCleared {
+        #[ink(topic)]
+        pub issuer: Address,
+        #[ink(topic)]
+        pub token_id: Option<u128>,
+        /// Token event sequence number (`None` for issuer-wide endpoints)
+        pub event_seq: Option<u64>,
+    }
+
+    /// Emitted when the admin changes the recovery challenge period
+    #[ink(event)]
+    pub struct RecoveryChallengePeriodChanged {
+        pub blocks: u32,
+    }
+
+    /// Emitted when an owner sets or clears their recovery account
+    #[ink(event)]
+    pub struct OwnerRecoverySet {
+        #[ink(topic)]
+        pub owner: Address,
+        pub recovery: Option<Address>,
+    }
+
+    /// Emitted when a recovery account starts reclaiming an owner's tokens
+    #[ink(event)]
+    pub struct RecoveryInitiated {
+        #[ink(topic)]
+        pub owner: Address,
+        #[ink(topic)]
+        pub recovery: Address,
+        pub new_owner: Address,
+        pub executable_at: u32,
+    }
+
+    /// Emitted when a pending recovery is cancelled by the owner
+    #[ink(event)]
+    pub struct RecoveryCancelled {
+        #[ink(topic)]
+        pub owner: Address,
+    }
+
+    /// Emitted after a recovery batch moved tokens to the new owner
+    #[ink(event)]
+    pub struct TokensRecovered {
+        #[ink(topic)]
+        pub owner: Address,
+        #[ink(topic)]
+        pub new_owner: Address,
+        pub recovered: u32,
+    }
+
+    /// Emitted when the issuer changes a passport's visibility
+    #[ink(event)]
+    pub struct VisibilityChanged {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub visibility: Visibility,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a passport is superseded by a new token
+    #[ink(event)]
+    pub struct PassportSuperseded {
+        #[ink(topic)]
+        pub old_token_id: u128,
+        #[ink(topic)]
+        pub new_token_id: u128,
+        pub superseded_at: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a role is granted to an account
+    #[ink(event)]
+    pub struct RoleGranted {
+        pub role: Role,
+        #[ink(topic)]
+        pub account: Address,
+        pub granted_by: Address,
+    }
+
+    /// Emitted when a role is revoked from or renounced by an account
+    #[ink(event)]
+    pub struct RoleRevoked {
+        pub role: Role,
+        #[ink(topic)]
+        pub account: Address,
+        pub revoked_by: Address,
+    }
+
+    /// Emitted when an admin action is announced through the timelock
+    #[ink(event)]
+    pub struct ActionQueued {
+        #[ink(topic)]
+        pub action_id: u32,
+        pub action: AdminAction,
+        pub eta: u32,
+    }
+
+    /// Emitted when an admin starts a migration
+    #[ink(event)]
+    pub struct MigrationStarted {
+        pub migration: Migration,
+        #[ink(topic)]
+        pub by: Address,
+    }
+
+    /// Emitted after each `continue_migration` batch
+    #[ink(event)]
+    pub struct MigrationProgressed {
+        pub migration: Migration,
+        pub cursor: u128,
+        pub processed: u128,
+        pub done: bool,
+    }
+
+    /// Emitted when the timelock delay for admin actions changes
+    #[ink(event)]
+    pub struct TimelockDelayChanged {
+        pub delay: u32,
+    }
+
+    /// Emitted when an admin confirms a queued action
+    #[ink(event)]
+    pub struct ActionConfirmed {
+        #[ink(topic)]
+        pub action_id: u32,
+        #[ink(topic)]
+        pub admin: Address,
+        pub confirmations: u32,
+    }
+
+    /// Emitted when the number of admin confirmations required changes
+    #[ink(event)]
+    pub struct AdminThresholdChanged {
+        pub threshold: u32,
+    }
+
+    /// Emitted when a queued admin action is executed
+    #[ink(event)]
+    pub struct ActionExecuted {
+        #[ink(topic)]
+        pub action_id: u32,
+    }
+
+    /// Emitted when a queued admin action is cancelled
+    #[ink(event)]
+    pub struct ActionCancelled {
+        #[ink(topic)]
+        pub action_id: u32,
+    }
+
+    /// Emitted when an Admin proposes handing its Admin role to another account
+    #[ink(event)]
+    pub struct AdminProposed {
+        #[ink(topic)]
+        pub proposer: Address,
+        #[ink(topic)]
+        pub proposed: Address,
+    }
+
+    /// Emitted when the proposed account accepts an Admin handover
+    #[ink(event)]
+    pub struct AdminAccepted {
+        #[ink(topic)]
+        pub previous: Address,
+        #[ink(topic)]
+        pub admin: Address,
+    }
+
+    /// Emitted when a Pauser pauses registration, updates, and transfers
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        pub by: Address,
+    }
+
+    /// Emitted when a Pauser lifts the pause
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        pub by: Address,
+    }
+
+    /// Emitted when the admin changes the per-token version limit
+    #[ink(event)]
+    pub struct MaxVersionsChanged {
+        pub max_versions: u32,
+    }
+
+    /// Emitted when the minimum update interval of a token changes
+    #[ink(event)]
+    pub struct MinUpdateIntervalChanged {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub blocks: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the issuer changes a token's transfer cool-down
+    #[ink(event)]
+    pub struct TransferCooldownChanged {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub blocks: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the issuer sets or removes a token's royalty
+    #[ink(event)]
+    pub struct RoyaltySet {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub receiver: Address,
+        /// `0` = royalty removed
+        pub basis_points: u16,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the issuer attaches, replaces, or removes a token's transfer terms
+    #[ink(event)]
+    pub struct TransferTermsChanged {
+        #[ink(topic)]
+        pub token_id: u128,
+        /// `None` when the terms were removed
+        pub terms_hash: Option<[u8; 32]>,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a prospective recipient accepts a token's transfer terms
+    #[ink(event)]
+    pub struct TermsAccepted {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub account: Address,
+        pub terms_hash: [u8; 32],
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an issuer registers a session key
+    #[ink(event)]
+    pub struct SessionKeyAdded {
+        #[ink(topic)]
+        pub issuer: Address,
+        #[ink(topic)]
+        pub session_key: Address,
+        pub first_token_id: u128,
+        pub last_token_id: u128,
+        pub expires_at: u32,
+    }
+
+    /// Emitted when an issuer revokes a session key
+    #[ink(event)]
+    pub struct SessionKeyRevoked {
+        #[ink(topic)]
+        pub issuer: Address,
+        #[ink(topic)]
+        pub session_key: Address,
+    }
+
+    /// Emitted when an issuer offers authority over a token to another account
+    #[ink(event)]
+    pub struct IssuerTransferOffered {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub issuer: Address,
+        #[ink(topic)]
+        pub new_issuer: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an issuer withdraws a pending issuer transfer offer
+    #[ink(event)]
+    pub struct IssuerTransferCancelled {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub issuer: Address,
+        pub new_issuer: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an owner offers a token to another account
+    #[ink(event)]
+    pub struct TransferOffered {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub owner: Address,
+        #[ink(topic)]
+        pub to: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an owner withdraws a pending transfer offer
+    #[ink(event)]
+    pub struct TransferOfferCancelled {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub owner: Address,
+        pub to: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when issuer authority moves to the account that accepted the offer
+    #[ink(event)]
+    pub struct IssuerTransferred {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub previous_issuer: Address,
+        #[ink(topic)]
+        pub new_issuer: Address,
+        /// Version current at the time of the transfer
+        pub version: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the controlling key of an issuer identity is rotated
+    #[ink(event)]
+    pub struct IssuerKeyRotated {
+        #[ink(topic)]
+        pub issuer: Address,
+        #[ink(topic)]
+        pub old_key: Address,
+        #[ink(topic)]
+        pub new_key: Address,
+    }
+
+    /// Emitted when an issuer starts or stops requiring consent before burns
+    #[ink(event)]
+    pub struct BurnConsentRequirementChanged {
+        #[ink(topic)]
+        pub issuer: Address,
+        pub required: bool,
+    }
+
+    /// Emitted when an issuer consents to the next burn of a token
+    #[ink(event)]
+    pub struct BurnConsentGiven {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub issuer: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a co-issuer is added to a token
+    #[ink(event)]
+    pub struct CoIssuerAdded {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub co_issuer: Address,
+        pub added_by: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a co-issuer is removed from a token
+    #[ink(event)]
+    pub struct CoIssuerRemoved {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub co_issuer: Address,
+        pub removed_by: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an issuer authorizes an account to update one token
+    #[ink(event)]
+    pub struct UpdateDelegateAdded {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub delegate: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an issuer withdraws a per-token update delegation
+    #[ink(event)]
+    pub struct UpdateDelegateRemoved {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub delegate: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the admin changes the update fee
+    #[ink(event)]
+    pub struct UpdateFeeChanged {
+        pub update_fee: U256,
+    }
+
+    /// Emitted when the admin changes the storage deposit rate used for quotes
+    #[ink(event)]
+    pub struct DepositPerByteChanged {
+        pub deposit_per_byte: U256,
+    }
+
+    /// Emitted when the admin changes a registration fee
+    #[ink(event)]
+    pub struct RegistrationFeeChanged {
+        pub granularity: Granularity,
+        pub fee: U256,
+    }
+
+    /// Emitted when data-availability fund balance is withdrawn
+    #[ink(event)]
+    pub struct FundWithdrawn {
+        #[ink(topic)]
+        pub to: Address,
+        pub amount: U256,
+    }
+
+    /// Emitted when the admin changes the address receiving fund withdrawals
+    #[ink(event)]
+    pub struct FundAddressChanged {
+        #[ink(topic)]
+        pub fund_address: Address,
+    }
+
+    /// Emitted when the admin changes the pre-claim bond or reservation window
+    #[ink(event)]
+    pub struct PreClaimTermsChanged {
+        pub bond: U256,
+        pub window: u32,
+    }
+
+    /// Emitted when a subject hash is reserved ahead of registration
+    #[ink(event)]
+    pub struct SubjectPreClaimed {
+        #[ink(topic)]
+        pub subject_hash: [u8; 32],
+        #[ink(topic)]
+        pub claimant: Address,
+        pub bond: U256,
+        pub expires_at: u32,
+    }
+
+    /// Emitted when the admin toggles the trusted issuer allowlist
+    #[ink(event)]
+    pub struct AllowlistModeChanged {
+        pub enabled: bool,
+    }
+
+    /// Emitted when the admin adds a trusted issuer
+    #[ink(event)]
+    pub struct TrustedIssuerAdded {
+        #[ink(topic)]
+        pub issuer: Address,
+    }
+
+    /// Emitted when the admin removes a trusted issuer
+    #[ink(event)]
+    pub struct TrustedIssuerRemoved {
+        #[ink(topic)]
+        pub issuer: Address,
+    }
+
+    /// Emitted when the admin blacklists an account
+    #[ink(event)]
+    pub struct AccountBlacklisted {
+        #[ink(topic)]
+        pub account: Address,
+        #[ink(topic)]
+        pub by: Address,
+    }
+
+    /// Emitted when the admin removes an account from the blacklist
+    #[ink(event)]
+    pub struct AccountUnblacklisted {
+        #[ink(topic)]
+        pub account: Address,
+        #[ink(topic)]
+        pub by: Address,
+    }
+
+    /// Emitted when a recipient allowlist is enabled or disabled
+    /// (`issuer: None` = contract-wide)
+    #[ink(event)]
+    pub struct RecipientAllowlistChanged {
+        #[ink(topic)]
+        pub issuer: Option<Address>,
+        pub enabled: bool,
+    }
+
+    /// Emitted when a recipient is added to or removed from an allowlist
+    #[ink(event)]
+    pub struct AllowedRecipientChanged {
+        #[ink(topic)]
+        pub issuer: Option<Address>,
+        #[ink(topic)]
+        pub recipient: Address,
+        pub allowed: bool,
+    }
+
+    /// Emitted when the admin registers a certifier
+    #[ink(event)]
+    pub struct CertifierAdded {
+        #[ink(topic)]
+        pub certifier: Address,
+    }
+
+    /// Emitted when the admin removes a certifier
+    #[ink(event)]
+    pub struct CertifierRemoved {
+        #[ink(topic)]
+        pub certifier: Address,
+    }
+
+    /// Emitted when a certifier endorses a passport (or replaces its endorsement)
+    #[ink(event)]
+    pub struct PassportEndorsed {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub certifier: Address,
+        pub claim_hash: [u8; 32],
+        pub evidence_hash: [u8; 32],
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a certifier withdraws its endorsement
+    #[ink(event)]
+    pub struct EndorsementWithdrawn {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub certifier: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a token's software bill of materials is anchored
+    #[ink(event)]
+    pub struct SbomSet {
+        #[ink(topic)]
+        pub token_id: u128,
+        pub software_version: String,
+        pub sbom_hash: [u8; 32],
+        pub index: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the admin registers a repairer or recycler
+    #[ink(event)]
+    pub struct ServiceProviderAdded {
+        #[ink(topic)]
+        pub account: Address,
+        pub provider: ServiceProvider,
+    }
+
+    /// Emitted when the admin removes a repairer or recycler
+    #[ink(event)]
+    pub struct ServiceProviderRemoved {
+        #[ink(topic)]
+        pub account: Address,
+        pub provider: ServiceProvider,
+    }
+
+    /// Emitted when a service provider appends a repair or end-of-life record
+    #[ink(event)]
+    pub struct ServiceRecordAppended {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub recorded_by: Address,
+        pub kind: ServiceRecordKind,
+        pub hash: [u8; 32],
+        pub index: u32,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when the admin sets or clears the external issuer registry
+    #[ink(event)]
+    pub struct IssuerRegistryChanged {
+        pub registry: Option<Address>,
+    }
+
+    /// Emitted when the admin toggles strict URI validation
+    #[ink(event)]
+    pub struct StrictUriModeChanged {
+        pub enabled: bool,
+    }
+
+    /// Emitted when the admin sets the reporting epoch length
+    #[ink(event)]
+    pub struct EpochLengthChanged {
+        pub epoch_length: u32,
+    }
+
+    /// Emitted when the admin sets or clears the token URI base
+    #[ink(event)]
+    pub struct TokenBaseUriChanged {
+        pub base_uri: Option<String>,
+    }
+
+    /// Emitted when the admin changes the Suspended status policy
+    #[ink(event)]
+    pub struct StatusPolicyChanged {
+        pub policy: StatusPolicy,
+    }
+
+    /// Emitted when the admin changes the transfer cooldown after registration
+    /// or an update
+    #[ink(event)]
+    pub struct UpdateTransferCooldownChanged {
+        pub blocks: u32,
+    }
+
+    /// Emitted when the admin changes who may lock token transfers
+    #[ink(event)]
+    pub struct LockAuthorityChanged {
+        pub authority: LockAuthority,
+    }
+
+    /// Emitted when a token's transfers are locked (ERC-5192)
+    #[ink(event)]
+    pub struct Locked {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub by: Address,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when a token's transfers are unlocked (ERC-5192)
+    #[ink(event)]
+    pub struct Unlocked {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub by: Address,
+        pub event_seq: u64,
+    }
+
+    // Ownership events (ERC-721 compatible). Transfers do not change issuer authority.
+
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        pub from: Option<Address>,
+        #[ink(topic)]
+        pub to: Option<Address>,
+        #[ink(topic)]
+        pub token_id: u128,
+        pub event_seq: u64,
+    }
+
+    /// Memo of a `transfer_with_data` handover, emitted right after its
+    /// `Transfer` (`event_seq` one higher)
+    #[ink(event)]
+    pub struct TransferMemo {
+        #[ink(topic)]
+        pub token_id: u128,
+        #[ink(topic)]
+        pub memo_hash: [u8; 32],
+        pub event_seq: u64,
+    }
+
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        pub owner: Address,
+        #[ink(topic)]
+        pub approved: Address,
+        #[ink(topic)]
+        pub token_id: u128,
+        pub event_seq: u64,
+    }
+
+    /// Emitted when an owner or operator explicitly clears a token approval
+    #[ink(event)]
+    pub struct ApprovalCleared {
+        #[ink(topic)]
+        pub owner: Address,
+        #[ink(topic)]
+        pub approved: Address,
+        #[ink(topic)]
+        pub token_id: u128,
+        pub event_seq: u64,
+    }
+
+    #[ink(event)]
+    pub struct ApprovalForAll {
+        #[ink(topic)]
+        pub owner: Address,
+        #[ink(topic)]
+        pub operator: Address,
+        pub approved: bool,
+    }
+
+    /// Emitted when an operator approval is granted with a deadline
+    #[ink(event)]
+    pub struct ApprovalForAllUntil {
+        #[ink(topic)]
+        pub owner: Address,
+        #[ink(topic)]
+        pub operator: Address,
+        /// First block at which the approval no longer applies
+        pub expires_at: u32,
+    }
+
+    #[ink(storage)]
+    pub struct DppContractV2 {
+        passports: Mapping<u128, PassportRecord>,
+
+        next_token_id: u128,
+        // issuer key -> token ID ranges (first, count, registered) reserved for
+        // pre-assignment; a range is dropped once all its IDs are registered
+        id_reservations: Mapping<Address, ink::prelude::vec::Vec<(u128, u32, u32)>>,
+
+        // Incremental counters for `health`: existing tokens and the sum of
+        // `owned_tokens_count` over all owners
+        live_tokens: u128,
+        owned_total: u128,
+
+        // Drafts among `live_tokens`, overall and per owner. Public counts and
+        // the enumeration indexes leave Drafts out until they are published.
+        draft_count: u128,
+        draft_balances: Mapping<Address, u128>,
+
+        // Enumeration: position -> token ID over all tokens and per owner, plus
+        // each token's position for swap-removal
+        all_tokens: Mapping<u128, u128>,
+        all_tokens_len: u128,
+        all_tokens_index: Mapping<u128, u128>,
+        owned_tokens: Mapping<(Address, u128), u128>,
+        owned_tokens_len: Mapping<Address, u128>,
+        owned_tokens_index: Mapping<u128, u128>,
+
+        // Running migration (at most one), and the block each one last completed
+        migration: Option<MigrationState>,
+        completed_migrations: Mapping<Migration, u32>,
+
+        version_history: Mapping<(u128, u32), VersionHistory>,
+
+        // subject_id_hash -> token_id (best-effort reverse lookup)
+        subject_id_to_token: Mapping<[u8; 32], u128>,
+
+        token_owner: Mapping<u128, Address>,
+        token_approvals: Mapping<u128, Address>,
+        owned_tokens_count: Mapping<Address, u128>,
+        operator_approvals: Mapping<(Address, Address), ()>,
+        // (owner, operator) -> expiry block, for approvals granted with a deadline
+        operator_approval_expiry: Mapping<(Address, Address), u32>,
+
+        // (role, account) -> granted. The deployer starts with every role but Regulator.
+        roles: Mapping<(Role, Address), ()>,
+        admin_count: u32,
+
+        // Pending two-step Admin handover: (proposing admin, proposed account)
+        pending_admin: Option<(Address, Address)>,
+
+        // Timelock for sensitive admin actions (0 = disabled, actions apply directly)
+        timelock_delay: u32,
+        queued_actions: Mapping<u32, QueuedAction>,
+        next_action_id: u32,
+
+        // Admin confirmations a queued action needs (1 = single admin suffices)
+        admin_threshold: u32,
+
+        // Emergency stop for registration, updates, and transfers
+        paused: bool,
+
+        // Maximum versions per token (0 = unlimited).
+        max_versions: u32,
+
+        // Supersession links: old -> new and new -> old.
+        superseded_by: Mapping<u128, u128>,
+        supersedes: Mapping<u128, u128>,
+
+        // session key -> scoped rights granted by an issuer
+        session_keys: Mapping<Address, SessionKey>,
+
+        // issuer (as recorded on tokens) -> rotated controlling key, plus the
+        // per-issuer rotation nonce signed over to prevent replays
+        issuer_controllers: Mapping<Address, Address>,
+        issuer_rotation_nonces: Mapping<Address, u32>,
+        // controlling key -> number of other issuer identities rotated to it
+        controlled_identities: Mapping<Address, u32>,
+
+        // issuer -> nonce of the next relayed registration (`register_passport_signed`)
+        registration_nonces: Mapping<Address, u64>,
+
+        // token_id -> account offered issuer authority (pending acceptance)
+        pending_issuer_transfers: Mapping<u128, Address>,
+
+        // token_id -> account offered ownership (pending acceptance)
+        pending_transfers: Mapping<u128, Address>,
+
+        // token_id -> minimum blocks between dataset updates (absent = no limit)
+        min_update_interval: Mapping<u128, u32>,
+
+        // token_id -> minimum blocks between custody changes (absent = no limit),
+        // and the block of the latest custody change (mint or transfer)
+        transfer_cooldown: Mapping<u128, u32>,
+        last_transfer_at: Mapping<u128, u32>,
+
+        // Blocks after registration or the latest update before a token can move
+        update_transfer_cooldown: u32,
+
+        // token_id -> hash of the terms a recipient must accept, and
+        // (token_id, account) -> block at which the account accepted which terms
+        transfer_terms: Mapping<u128, [u8; 32]>,
+        terms_acceptances: Mapping<(u128, Address), ([u8; 32], u32)>,
+
+        // token_id -> (royalty receiver, basis points) for secondary sales
+        royalties: Mapping<u128, (Address, u16)>,
+
+        // token_id -> co-issuers (bounded); they may update and revoke like the issuer
+        co_issuers: Mapping<u128, ink::prelude::vec::Vec<Address>>,
+
+        // (token_id, account) -> granting issuer. Only valid while that issuer
+        // still holds authority over the token.
+        update_delegates: Mapping<(u128, Address), Address>,
+
+        // Reporting buckets: epoch index (block_number / epoch_length) -> counters
+        epoch_length: u32,
+        epoch_counters: Mapping<u32, EpochCounters>,
+
+        // Data-availability fund: fees collected on updates pay for IPFS pinning
+        update_fee: U256,
+        registration_fees: RegistrationFees,
+        fund_address: Address,
+        fund_balance: U256,
+
+        // Chain storage deposit per byte, mirrored for fee quotes (0 = not quoted)
+        deposit_per_byte: U256,
+
+        // Reject `ipfs://` URIs without a syntactically valid CID
+        strict_uri_mode: bool,
+
+        // `token_uri` prefix; `None` = the passport's current dataset URI
+        token_base_uri: Option<String>,
+
+        // Restrict registration to accounts in `trusted_issuers`
+        allowlist_mode: bool,
+        trusted_issuers: Mapping<Address, ()>,
+
+        // Transfer recipient allowlists: `None` = contract-wide, `Some(issuer)` =
+        // that issuer's tokens. Enabled lists, then (list, recipient) entries.
+        recipient_allowlists: Mapping<Option<Address>, ()>,
+        allowed_recipients: Mapping<(Option<Address>, Address), ()>,
+
+        // Sanctioned or compromised accounts (no registration, receipt, or approvals)
+        blacklist: Mapping<Address, ()>,
+
+        // External IssuerRegistry contract consulted on registration (if set)
+        issuer_registry: Option<Address>,
+
+        // controlling key -> Draft token IDs (only readable by that key via `my_drafts`)
+        issuer_drafts: Mapping<Address, ink::prelude::vec::Vec<u128>>,
+
+        // Chain of custody: (token_id, index) -> entry. Entries below the
+        // checkpoint's `pruned_count` have been folded into its digest.
+        custody_log: Mapping<(u128, u32), CustodyEntry>,
+        custody_len: Mapping<u128, u32>,
+        custody_checkpoints: Mapping<u128, CustodyCheckpoint>,
+
+        // token_id -> physical custodian (independent of `token_owner`), and the
+        // end block of a custody lease
+        custodians: Mapping<u128, Address>,
+        custody_leases: Mapping<u128, u32>,
+
+        // token_id -> co-ownership shares, and the proposed recipient of a
+        // whole-token transfer with the holders that approved it
+        co_ownerships: Mapping<u128, CoOwnership>,
+        co_owned_transfer_approvals: Mapping<u128, (Address, ink::prelude::vec::Vec<Address>)>,
+
+        // account -> issuer onboarding trail
+        onboarding: Mapping<Address, OnboardingRecord>,
+        issuer_profiles: Mapping<Address, IssuerProfile>,
+
+        // Emit PassportRegistered / PassportUpdated in addition to AnchorDigest
+        verbose_events: bool,
+
+        // Append-only status log: (token_id, index) -> change, plus entry count
+        status_history: Mapping<(u128, u32), StatusChange>,
+        status_history_len: Mapping<u128, u32>,
+
+        // controlling key -> N-of-M co-signer policy, and Draft token -> approving signers
+        issuance_policies: Mapping<Address, IssuancePolicy>,
+        activation_approvals: Mapping<u128, ink::prelude::vec::Vec<Address>>,
+
+        // Tokens frozen by an administrator (no transfers or updates)
+        frozen: Mapping<u128, ()>,
+
+        // controlling key -> publication reviewer (maker/checker), and token -> staged update
+        reviewers: Mapping<Address, Address>,
+        pending_updates: Mapping<u128, PendingUpdate>,
+        // controlling key -> number of staged updates awaiting review, and the
+        // same per issuer identity (moved on key rotation)
+        pending_update_counts: Mapping<Address, u32>,
+        issuer_pending_updates: Mapping<Address, u32>,
+
+        // token_id -> policy snapshot taken at registration
+        issuance_context: Mapping<u128, IssuanceContext>,
+
+        // token_id -> sequence number of the latest event emitted for the token
+        event_seqs: Mapping<u128, u64>,
+
+        // token_id -> block of the last update, status change, or custody transfer
+        last_activity: Mapping<u128, u32>,
+
+        // token_id -> visibility (absent = Public)
+        visibility: Mapping<u128, Visibility>,
+
+        // token_id -> revocation details (kept for verifiers querying state)
+        revocations: Mapping<u128, RevocationInfo>,
+
+        // token_id -> issuer notices (bounded, expired ones dropped on post)
+        notices: Mapping<u128, ink::prelude::vec::Vec<Notice>>,
+
+        // token_id -> regulator flag (at most one active flag per token)
+        flags: Mapping<u128, PassportFlag>,
+
+        // Accounts allowed to endorse passports, and token_id -> endorsements
+        certifiers: Mapping<Address, ()>,
+        endorsements: Mapping<u128, ink::prelude::vec::Vec<Endorsement>>,
+        // certificate hash -> (token_id, certifier) of the endorsement carrying it
+        certificates: Mapping<[u8; 32], (u128, Address)>,
+
+        // (provider kind, account) -> registered, and append-only service log:
+        // (token_id, index) -> record, plus entry count
+        service_providers: Mapping<(ServiceProvider, Address), ()>,
+        service_log: Mapping<(u128, u32), ServiceRecord>,
+        service_log_len: Mapping<u128, u32>,
+
+        // Append-only SBOM history: (token_id, index) -> entry, plus entry count
+        sbom_history: Mapping<(u128, u32), SbomEntry>,
+        sbom_len: Mapping<u128, u32>,
+
+        // token_id -> final end-of-life anchor (set by `decommission`)
+        end_of_life: Mapping<u128, EndOfLifeRecord>,
+
+        // Burned tokens, controlling keys requiring consent to burn, and pending consents
+        burned: Mapping<u128, BurnRecord>,
+        burn_consent_required: Mapping<Address, ()>,
+        burn_consents: Mapping<u128, ()>,
+
+        // token_id -> end block of a timed suspension
+        suspended_until: Mapping<u128, u32>,
+
+        // Which operations a Suspended passport blocks
+        status_policy: StatusPolicy,
+
+        // Tokens with transfers locked, and who may lock them
+        locked: Mapping<u128, ()>,
+        lock_authority: LockAuthority,
+
+        // Owner recovery: owner -> recovery account, pending recoveries, challenge period
+        owner_recovery: Mapping<Address, Address>,
+        pending_recoveries: Mapping<Address, PendingRecovery>,
+        recovery_challenge_period: u32,
+
+        // Subject pre-claims: subject hash -> reservation, required bond, window length
+        pre_claims: Mapping<[u8; 32], PreClaim>,
+        pre_claim_bond: U256,
+        pre_claim_window: u32,
+
+        // Resolver endpoints: per token (overrides) and issuer-wide (fallback)
+        token_resolvers: Mapping<u128, ResolverEndpoint>,
+        issuer_resolvers: Mapping<Address, ResolverEndpoint>,
+
+        // token_id -> references to the same product in other registries (bounded)
+        federated_refs: Mapping<u128, ink::prelude::vec::Vec<FederatedRef>>,
+    }
+
+    impl DppContractV2 {
+        /// Constructor.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            let caller = Self::env().caller();
+            let mut roles = Mapping::new();
+            for role in [Role::Admin, Role::Pauser, Role::Registrar] {
+                roles.insert((role, caller), &());
+            }
+
+            Self {
+                passports: Mapping::new(),
+                next_token_id: 0,
+                id_reservations: Mapping::new(),
+                live_tokens: 0,
+                owned_total: 0,
+                draft_count: 0,
+                draft_balances: Mapping::new(),
+                all_tokens: Mapping::new(),
+                all_tokens_len: 0,
+                all_tokens_index: Mapping::new(),
+                owned_tokens: Mapping::new(),
+                owned_tokens_len: Mapping::new(),
+                owned_tokens_index: Mapping::new(),
+                migration: None,
+                completed_migrations: Mapping::new(),
+                version_history: Mapping::new(),
+                subject_id_to_token: Mapping::new(),
+                token_owner: Mapping::new(),
+                token_approvals: Mapping::new(),
+                owned_tokens_count: Mapping::new(),
+                operator_approvals: Mapping::new(),
+                operator_approval_expiry: Mapping::new(),
+                roles,
+                admin_count: 1,
+                pending_admin: None,
+                timelock_delay: 0,
+                queued_actions: Mapping::new(),
+                next_action_id: 0,
+                admin_threshold: 1,
+                paused: false,
+                max_versions: 0,
+                superseded_by: Mapping::new(),
+                supersedes: Mapping::new(),
+                session_keys: Mapping::new(),
+                issuer_controllers: Mapping::new(),
+                issuer_rotation_nonces: Mapping::new(),
+                controlled_identities: Mapping::new(),
+                registration_nonces: Mapping::new(),
+                pending_issuer_transfers: Mapping::new(),
+                pending_transfers: Mapping::new(),
+                min_update_interval: Mapping::new(),
+                transfer_cooldown: Mapping::new(),
+                transfer_terms: Mapping::new(),
+                terms_acceptances: Mapping::new(),
+                royalties: Mapping::new(),
+                last_transfer_at: Mapping::new(),
+                update_transfer_cooldown: 0,
+                co_issuers: Mapping::new(),
+                update_delegates: Mapping::new(),
+                epoch_length: DEFAULT_EPOCH_LENGTH,
+                epoch_counters: Mapping::new(),
+                update_fee: U256::zero(),
+                deposit_per_byte: U256::zero(),
+                registration_fees: RegistrationFees::default(),
+                fund_address: Self::env().caller(),
+                fund_balance: U256::zero(),
+                strict_uri_mode: false,
+                token_base_uri: None,
+                allowlist_mode: false,
+                trusted_issuers: Mapping::new(),
+                recipient_allowlists: Mapping::new(),
+                allowed_recipients: Mapping::new(),
+                blacklist: Mapping::new(),
+                issuer_registry: None,
+                issuer_drafts: Mapping::new(),
+                custody_log: Mapping::new(),
+                custody_len: Mapping::new(),
+                custody_checkpoints: Mapping::new(),
+                custodians: Mapping::new(),
+                custody_leases: Mapping::new(),
+                co_ownerships: Mapping::new(),
+                co_owned_transfer_approvals: Mapping::new(),
+                onboarding: Mapping::new(),
+                issuer_profiles: Mapping::new(),
+                verbose_events: true,
+                status_history: Mapping::new(),
+                status_history_len: Mapping::new(),
+                issuance_policies: Mapping::new(),
+                activation_approvals: Mapping::new(),
+                frozen: Mapping::new(),
+                reviewers: Mapping::new(),
+                pending_updates: Mapping::new(),
+                pending_update_counts: Mapping::new(),
+                issuer_pending_updates: Mapping::new(),
+                issuance_context: Mapping::new(),
+                event_seqs: Mapping::new(),
+                last_activity: Mapping::new(),
+                visibility: Mapping::new(),
+                revocations: Mapping::new(),
+                notices: Mapping::new(),
+                flags: Mapping::new(),
+                certifiers: Mapping::new(),
+                endorsements: Mapping::new(),
+                certificates: Mapping::new(),
+                service_providers: Mapping::new(),
+                service_log: Mapping::new(),
+                service_log_len: Mapping::new(),
+                sbom_history: Mapping::new(),
+                sbom_len: Mapping::new(),
+                end_of_life: Mapping::new(),
+                burned: Mapping::new(),
+                burn_consent_required: Mapping::new(),
+                burn_consents: Mapping::new(),
+                suspended_until: Mapping::new(),
+                status_policy: StatusPolicy::default(),
+                locked: Mapping::new(),
+                lock_authority: LockAuthority::default(),
+                owner_recovery: Mapping::new(),
+                pending_recoveries: Mapping::new(),
+                recovery_challenge_period: DEFAULT_RECOVERY_CHALLENGE_PERIOD,
+                pre_claims: Mapping::new(),
+                pre_claim_bond: U256::zero(),
+                pre_claim_window: DEFAULT_PRE_CLAIM_WINDOW,
+                token_resolvers: Mapping::new(),
+                issuer_resolvers: Mapping::new(),
+                federated_refs: Mapping::new(),
+            }
+        }
+
+        /// Register a new passport anchor.
+        /// * `payload_hash` - SHA-256 hash of the JWT string
+        /// * `dataset_type` - MIME type (e.g., "application/vc+jwt")
+        /// * `granularity` - Granularity level (ProductClass, Batch, or Item)
+        /// * `subject_id_hash` - Optional hashed canonical subject identifier
+        /// * `validity_blocks` - Optional validity period; the passport expires
+        ///   this many blocks after registration (see `set_expiry`)
+        ///
+        /// # Returns
+        ///
+        /// Token ID of the newly registered passport
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty dataset_uri or dataset_type, or zero validity_blocks
+        /// * `InsufficientFee` - Transferred value below `fee_for(granularity)`
+        /// * `Unauthorized` - Caller is an issuer identity whose key was rotated
+        #[ink(message, payable)]
+        pub fn register_passport(
+            &mut self,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            granularity: Granularity,
+            subject_id_hash: Option<[u8; 32]>,
+            validity_blocks: Option<u32>,
+        ) -> Result<u128> {
+            let caller = self.env().caller();
+            self.mint_passport(
+                RegistrationInput {
+                    dataset_uri,
+                    payload_hash,
+                    dataset_type,
+                    granularity,
+                    subject_id_hash,
+                    validity_blocks,
+                },
+                MintParams::new(caller, caller),
+            )
+        }
+
+        /// Register a passport owned by `owner` from the start (e.g. a product
+        /// already sold to a customer).
+        ///
+        /// The caller keeps issuer authority; the mint `Transfer` goes to `owner`.
+        /// Same fee and errors as `register_passport`.
+        #[ink(message, payable)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn register_passport_for(
+            &mut self,
+            owner: Address,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            granularity: Granularity,
+            subject_id_hash: Option<[u8; 32]>,
+            validity_blocks: Option<u32>,
+        ) -> Result<u128> {
+            let caller = self.env().caller();
+            self.mint_passport(
+                RegistrationInput {
+                    dataset_uri,
+                    payload_hash,
+                    dataset_type,
+                    granularity,
+                    subject_id_hash,
+                    validity_blocks,
+                },
+                MintParams::new(caller, owner),
+            )
+        }
+
+        /// Register a passport permanently bound to the caller (soulbound).
+        ///
+        /// Same arguments, fee, and errors as `register_passport`. Transfers and
+        /// token approvals of the passport fail with `NotAllowed`.
+        #[ink(message, payable)]
+        pub fn register_soulbound_passport(
+            &mut self,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            granularity: Granularity,
+            subject_id_hash: Option<[u8; 32]>,
+            validity_blocks: Option<u32>,
+        ) -> Result<u128> {
+            let caller = self.env().caller();
+            self.mint_passport(
+                RegistrationInput {
+                    dataset_uri,
+                    payload_hash,
+                    dataset_type,
+                    granularity,
+                    subject_id_hash,
+                    validity_blocks,
+                },
+                MintParams {
+                    transferable: false,
+                    ..MintParams::new(caller, caller)
+                },
+            )
+        }
+
+        /// Register up to `MAX_BATCH_SIZE` passports owned by the caller in one
+        /// call, e.g. item-level passports of a production run.
+        ///
+        /// Every input is validated before any is minted, so the batch succeeds
+        /// or fails as a whole. Token IDs are contiguous and returned in input
+        /// order. The transferred value must cover the summed fees.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty or oversized batch, an empty dataset_uri or
+        ///   dataset_type, or zero validity_blocks
+        /// * `InvalidUri` - An invalid `ipfs://` URI in strict URI mode
+        /// * `InsufficientFee` - Transferred value below the summed fees
+        /// * Otherwise as `register_passport`
+        #[ink(message, payable)]
+        pub fn register_batch(
+            &mut self,
+            inputs: ink::prelude::vec::Vec<RegistrationInput>,
+        ) -> Result<ink::prelude::vec::Vec<u128>> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            if inputs.is_empty() || inputs.len() > MAX_BATCH_SIZE {
+                return Err(Error::InvalidInput);
+            }
+
+            let mut total_fee = U256::zero();
+            for input in &inputs {
+                if input.dataset_uri.is_empty()
+                    || input.dataset_type.is_empty()
+                    || input.validity_blocks == Some(0)
+                {
+                    return Err(Error::InvalidInput);
+                }
+                if self.strict_uri_mode {
+                    validate_ipfs_uri(&input.dataset_uri)?;
+                }
+                total_fee = total_fee.saturating_add(self.fee_for(input.granularity.clone()));
+            }
+            self.collect_fee(total_fee)?;
+
+            let first_token_id = self.next_token_id;
+            let mut token_ids = ink::prelude::vec::Vec::with_capacity(inputs.len());
+            for input in inputs {
+                let token_id = self.mint_passport(
+                    input,
+                    MintParams {
+                        prepaid: true,
+                        ..MintParams::new(caller, caller)
+                    },
+                )?;
+                token_ids.push(token_id);
+            }
+
+            self.env().emit_event(BatchRegistered {
+                issuer: caller,
+                first_token_id,
+                count: token_ids.len() as u32,
+            });
+
+            Ok(token_ids)
+        }
+
+        /// Reserve `count` consecutive token IDs for the caller, so its ERP can
+        /// pre-assign IDs and print data carriers before registration.
+        ///
+        /// The range is taken from the sequential ID space and never assigned
+        /// by other registrations; register into it with
+        /// `register_reserved_passport`. Returns the first reserved ID. Ranges move
+        /// to the new key when the caller's key is rotated and are dropped once
+        /// every ID in them is registered.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - `count` is zero or above `MAX_RESERVED_IDS`, the
+        ///   caller already holds `MAX_ID_RESERVATIONS` ranges, or the ID space
+        ///   is exhausted
+        /// * `AccountBlacklisted` - Caller is blacklisted
+        /// * `Unauthorized` - Caller is an issuer identity whose key was rotated
+        /// * `NotTrustedIssuer` - Caller may not issue under the allowlist or
+        ///   issuer registry
+        #[ink(message)]
+        pub fn reserve_token_ids(&mut self, count: u32) -> Result<u128> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            self.ensure_can_issue(caller)?;
+
+            let mut reservations = self.id_reservations.get(caller).unwrap_or_default();
+            if count == 0 || count > MAX_RESERVED_IDS || reservations.len() >= MAX_ID_RESERVATIONS {
+                return Err(Error::InvalidInput);
+            }
+
+            let first_token_id = self.next_token_id;
+            self.next_token_id = first_token_id
+                .checked_add(u128::from(count))
+                .ok_or(Error::InvalidInput)?;
+            reservations.push((first_token_id, count, 0));
+            self.id_reservations.insert(caller, &reservations);
+            self.env().emit_event(TokenIdsReserved {
+                issuer: caller,
+                first_token_id,
+                count,
+            });
+
+            Ok(first_token_id)
+        }
+
+        /// Register a passport under a token ID the caller reserved.
+        ///
+        /// Same arguments (after `token_id`), fee, and errors as
+        /// `register_passport`, plus:
+        ///
+        /// * `Unauthorized` - `token_id` is not in one of the caller's reservations
+        /// * `NotAllowed` - `token_id` is already registered
+        #[ink(message, payable)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn register_reserved_passport(
+            &mut self,
+            token_id: u128,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            granularity: Granularity,
+            subject_id_hash: Option<[u8; 32]>,
+            validity_blocks: Option<u32>,
+        ) -> Result<u128> {
+            let caller = self.env().caller();
+            let mut reservations = self.id_reservations.get(caller).unwrap_or_default();
+            let index = reservations
+                .iter()
+                .position(|(first, count, _)| {
+                    token_id >= *first && token_id - *first < u128::from(*count)
+                })
+                .ok_or(Error::Unauthorized)?;
+            if self.passports.contains(token_id) {
+                return Err(Error::NotAllowed);
+            }
+
+            let token_id = self.mint_passport(
+                RegistrationInput {
+                    dataset_uri,
+                    payload_hash,
+                    dataset_type,
+                    granularity,
+                    subject_id_hash,
+                    validity_blocks,
+                },
+                MintParams {
+                    reserved_id: Some(token_id),
+                    ..MintParams::new(caller, caller)
+                },
+            )?;
+
+            let (_, count, registered) = &mut reservations[index];
+            *registered += 1;
+            if *registered == *count {
+                reservations.remove(index);
+            }
+            if reservations.is_empty() {
+                self.id_reservations.remove(caller);
+            } else {
+                self.id_reservations.insert(caller, &reservations);
+            }
+
+            Ok(token_id)
+        }
+
+        /// Get the token ID ranges (first, count) an issuer key has reserved
+        /// and not yet fully registered.
+        #[ink(message)]
+        pub fn token_id_reservations(
+            &self,
+            issuer: Address,
+        ) -> ink::prelude::vec::Vec<(u128, u32)> {
+            self.id_reservations
+                .get(issuer)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(first, count, _)| (first, count))
+                .collect()
+        }
+
+        /// Register a passport on behalf of an issuer that signed it off-chain.
+        ///
+        /// Any relayer may submit the call and pays the registration fee; the
+        /// passport is issued to and owned by `issuer`. `signature` is a 65-byte
+        /// secp256k1 signature by the issuer's current key over
+        /// `registration_message(...)` with the same arguments.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidSignature` - Signature does not recover to the issuer's key
+        /// * Otherwise as `register_passport`
+        #[ink(message, payable)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn register_passport_signed(
+            &mut self,
+            issuer: Address,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            granularity: Granularity,
+            subject_id_hash: Option<[u8; 32]>,
+            validity_blocks: Option<u32>,
+            signature: [u8; 65],
+        ) -> Result<u128> {
+            let message_hash = self.registration_message(
+                issuer,
+                dataset_uri.clone(),
+                payload_hash,
+                dataset_type.clone(),
+                granularity.clone(),
+                subject_id_hash,
+                validity_blocks,
+            );
+            if self.recover_signer(&signature, &message_hash)? != self.controller_of(issuer) {
+                return Err(Error::InvalidSignature);
+            }
+
+            let nonce = self.registration_nonce(issuer);
+            self.registration_nonces.insert(issuer, &(nonce + 1));
+
+            self.mint_passport(
+                RegistrationInput {
+                    dataset_uri,
+                    payload_hash,
+                    dataset_type,
+                    granularity,
+                    subject_id_hash,
+                    validity_blocks,
+                },
+                MintParams::new(issuer, issuer),
+            )
+        }
+
+        /// Keccak-256 hash an issuer signs to authorize `register_passport_signed`.
+        ///
+        /// Covers this contract's address and the issuer's registration nonce,
+        /// so a signature registers at most once and only on this deployment.
+        #[ink(message)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn registration_message(
+            &self,
+            issuer: Address,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            granularity: Granularity,
+            subject_id_hash: Option<[u8; 32]>,
+            validity_blocks: Option<u32>,
+        ) -> [u8; 32] {
+            self.env().hash_encoded::<ink::env::hash::Keccak256, _>(&(
+                b"fides-dpp:register",
+                self.env().address(),
+                issuer,
+                dataset_uri,
+                payload_hash,
+                dataset_type,
+                granularity,
+                subject_id_hash,
+                validity_blocks,
+                self.registration_nonce(issuer),
+            ))
+        }
+
+        /// Number of signed registrations relayed for an issuer (the next nonce).
+        #[ink(message)]
+        pub fn registration_nonce(&self, issuer: Address) -> u64 {
+            self.registration_nonces.get(issuer).unwrap_or(0)
+        }
+
+        /// Register a new passport anchor in `Draft` status.
+        ///
+        /// Drafts are hidden from public lookups, can be updated by the issuer,
+        /// and cannot be transferred until `activate_passport` is called.
+        /// Same arguments, fee, and errors as `register_passport` (the validity
+        /// period runs from registration, not activation), plus `NotAllowed`
+        /// once the issuer holds `MAX_DRAFTS_PER_ISSUER` Drafts.
+        #[ink(message, payable)]
+        pub fn register_draft(
+            &mut self,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            granularity: Granularity,
+            subject_id_hash: Option<[u8; 32]>,
+            validity_blocks: Option<u32>,
+        ) -> Result<u128> {
+            let caller = self.env().caller();
+            self.mint_passport(
+                RegistrationInput {
+                    dataset_uri,
+                    payload_hash,
+                    dataset_type,
+                    granularity,
+                    subject_id_hash,
+                    validity_blocks,
+                },
+                MintParams {
+                    status: PassportStatus::Draft,
+                    ..MintParams::new(caller, caller)
+                },
+            )
+        }
+
+        /// Activate a Draft passport, making it publicly visible (issuer-only).
+        ///
+        /// Not available to issuers with an issuance policy or a reviewer; their
+        /// Drafts are activated through `approve_activation` or
+        /// `approve_publication`.
+        #[ink(message)]
+        pub fn activate_passport(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.is_issuer(&record, caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            if record.status != PassportStatus::Draft
+                || self.issuance_policy_of(record.issuer).is_some()
+                || self.reviewer_of(record.issuer).is_some()
+            {
+                return Err(Error::NotAllowed);
+            }
+
+            self.activate(&mut record);
+
+            Ok(())
+        }
+
+        /// Require `threshold` of `signers` to approve activation of the caller's
+        /// passports (N-of-M issuance).
+        ///
+        /// While set, the caller can only register Drafts (the proposals); direct
+        /// registration as Active, supersession, and `activate_passport` fail
+        /// with `NotAllowed`.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty, duplicate, or more than `MAX_CO_SIGNERS`
+        ///   signers, or threshold not in `1..=signers.len()`
+        #[ink(message)]
+        pub fn set_issuance_policy(
+            &mut self,
+            signers: ink::prelude::vec::Vec<Address>,
+            threshold: u32,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+
+            let has_duplicates = signers
+                .iter()
+                .enumerate()
+                .any(|(i, signer)| signers[..i].contains(signer));
+            if signers.len() > MAX_CO_SIGNERS
+                || has_duplicates
+                || threshold == 0
+                || threshold as usize > signers.len()
+            {
+                return Err(Error::InvalidInput);
+            }
+
+            let policy = IssuancePolicy { signers, threshold };
+            self.issuance_policies.insert(caller, &policy);
+            self.env().emit_event(IssuancePolicyChanged {
+                issuer: caller,
+                policy: Some(policy),
+            });
+
+            Ok(())
+        }
+
+        /// Remove the caller's issuance policy.
+        ///
+        /// # Errors
+        ///
+        /// * `NotAllowed` - No policy is set, or Drafts proposed under it are
+        ///   still awaiting co-signer approval
+        #[ink(message)]
+        pub fn clear_issuance_policy(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            // Pending proposals would otherwise become activatable without co-signers
+            if self.issuer_drafts.contains(caller) {
+                return Err(Error::NotAllowed);
+            }
+            if self.issuance_policies.take(caller).is_none() {
+                return Err(Error::NotAllowed);
+            }
+            self.env().emit_event(IssuancePolicyChanged {
+                issuer: caller,
+                policy: None,
+            });
+            Ok(())
+        }
+
+        /// Get the issuance policy of the key controlling an issuer identity.
+        #[ink(message)]
+        pub fn get_issuance_policy(&self, issuer: Address) -> Option<IssuancePolicy> {
+            self.issuance_policy_of(issuer)
+        }
+
+        /// Approve activation of a proposed Draft passport (co-signers only).
+        ///
+        /// The passport becomes Active once approvals from current signers
+        /// reach the issuer's threshold.
+        #[ink(message)]
+        pub fn approve_activation(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+            let policy = self
+                .issuance_policy_of(record.issuer)
+                .ok_or(Error::NotAllowed)?;
+
+            if !policy.signers.contains(&caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            if record.status != PassportStatus::Draft {
+                return Err(Error::NotAllowed);
+            }
+
+            let mut approvals = self.activation_approvals.get(token_id).unwrap_or_default();
+            if approvals.contains(&caller) {
+                return Err(Error::NotAllowed);
+            }
+            approvals.push(caller);
+
+            // Signers removed from the policy since approving no longer count
+            let count = approvals
+                .iter()
+                .filter(|signer| policy.signers.contains(signer))
+                .count() as u32;
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(ActivationApproved {
+                token_id,
+                signer: caller,
+                approvals: count,
+                threshold: policy.threshold,
+                event_seq,
+            });
+
+            if count >= policy.threshold {
+                self.activation_approvals.remove(token_id);
+                self.activate(&mut record);
+            } else {
+                self.activation_approvals.insert(token_id, &approvals);
+            }
+
+            Ok(())
+        }
+
+        /// Get the signers that approved activation of a Draft passport so far.
+        #[ink(message)]
+        pub fn get_activation_approvals(&self, token_id: u128) -> ink::prelude::vec::Vec<Address> {
+            if self.is_hidden_draft(token_id) {
+                return ink::prelude::vec::Vec::new();
+            }
+            self.activation_approvals.get(token_id).unwrap_or_default()
+        }
+
+        /// Set or clear the reviewer for the caller's passports (maker/checker).
+        ///
+        /// While set, new registrations are held as Drafts and dataset updates
+        /// to published passports are staged; neither becomes visible until the
+        /// reviewer calls `approve_publication`. Updates to Drafts apply
+        /// directly, as Drafts are not public.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - `reviewer` is the caller
+        /// * `NotAllowed` - Clearing the reviewer while the caller still has
+        ///   Drafts or staged updates
+        /// * `Unauthorized` - Caller is an issuer identity whose key was rotated
+        #[ink(message)]
+        pub fn set_reviewer(&mut self, reviewer: Option<Address>) -> Result<()> {
+            let caller = self.env().caller();
+            if self.issuer_controllers.contains(caller) {
+                return Err(Error::Unauthorized);
+            }
+            match reviewer {
+                Some(reviewer) if reviewer == caller => return Err(Error::InvalidInput),
+                Some(reviewer) => {
+                    self.reviewers.insert(caller, &reviewer);
+                }
+                None => {
+                    if self.issuer_drafts.contains(caller)
+                        || self.pending_update_counts.contains(caller)
+                    {
+                        return Err(Error::NotAllowed);
+                    }
+                    self.reviewers.remove(caller);
+                }
+            }
+            self.env().emit_event(ReviewerChanged {
+                issuer: caller,
+                reviewer,
+            });
+            Ok(())
+        }
+
+        /// Get an issuer's publication reviewer (set by its controlling key).
+        #[ink(message)]
+        pub fn get_reviewer(&self, issuer: Address) -> Option<Address> {
+            self.reviewer_of(issuer)
+        }
+
+        /// Get the update awaiting review for a passport, if any (`None` for
+        /// callers that cannot read the passport, except its reviewer).
+        #[ink(message)]
+        pub fn get_pending_update(&self, token_id: u128) -> Option<PendingUpdate> {
+            let pending = self.pending_updates.get(token_id)?;
+            let is_reviewer = self
+                .passports
+                .get(token_id)
+                .is_some_and(|record| self.reviewer_of(record.issuer) == Some(self.env().caller()));
+            if !is_reviewer && !self.can_read(token_id) {
+                return None;
+            }
+            Some(pending)
+        }
+
+        /// Approve a pending registration or update (reviewer-only).
+        ///
+        /// For a Draft held for review, `version` is its current version and the
+        /// passport is activated. Otherwise `version` must match the staged
+        /// update, which becomes the current version.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized` - Caller is not the issuer's reviewer
+        /// * `NotAllowed` - Nothing is pending, or the issuer's Drafts are
+        ///   activated by an issuance policy instead
+        /// * `InvalidInput` - `version` does not match the pending version
+        /// * `PassportSuspended` - The passport is Suspended and the status
+        ///   policy blocks updates
+        #[ink(message)]
+        pub fn approve_publication(&mut self, token_id: u128, version: u32) -> Result<()> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if self.reviewer_of(record.issuer) != Some(caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            if record.status == PassportStatus::Draft {
+                if self.issuance_policy_of(record.issuer).is_some() {
+                    return Err(Error::NotAllowed);
+                }
+                if version != record.version {
+                    return Err(Error::InvalidInput);
+                }
+                let event_seq = self.next_event_seq(token_id);
+                self.env().emit_event(PublicationApproved {
+                    token_id,
+                    version,
+                    reviewer: caller,
+                    event_seq,
+                });
+                self.activate(&mut record);
+                return Ok(());
+            }
+
+            let pending = self
+                .pending_updates
+                .get(token_id)
+                .ok_or(Error::NotAllowed)?;
+            if version != pending.entry.version {
+                return Err(Error::InvalidInput);
+            }
+            if self.frozen.contains(token_id) {
+                return Err(Error::TokenFrozen);
+            }
+            if record.status == PassportStatus::Revoked {
+                return Err(Error::PassportRevoked);
+            }
+            if record.status == PassportStatus::Archived {
+                return Err(Error::PassportArchived);
+            }
+            if self.superseded_by.contains(token_id) {
+                return Err(Error::AlreadySuperseded);
+            }
+            if self.status_policy.suspended_blocks_updates
+                && self.effective_status_of(&record) == PassportStatus::Suspended
+            {
+                return Err(Error::PassportSuspended);
+            }
+
+            self.clear_pending_update(token_id);
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PublicationApproved {
+                token_id,
+                version,
+                reviewer: caller,
+                event_seq,
+            });
+
+            let mut entry = pending.entry;
+            entry.updated_at = self.env().block_number();
+            self.publish_version(record, entry, pending.subject_id_hash);
+
+            Ok(())
+        }
+
+        /// Supersede a passport that reached its version limit (issuer-only).
+        ///
+        /// Registers a new token with the same granularity, owned by the current
+        /// owner of the old token, and links old → new. The old token stays
+        /// readable but can no longer be updated. The successor pays the
+        /// registration fee for its granularity.
+        ///
+        /// # Returns
+        ///
+        /// Token ID of the new passport
+        #[ink(message, payable)]
+        pub fn supersede_passport(
+            &mut self,
+            token_id: u128,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            subject_id_hash: Option<[u8; 32]>,
+        ) -> Result<u128> {
+            let caller = self.env().caller();
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.is_issuer(&record, caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            if record.status == PassportStatus::Revoked {
+                return Err(Error::PassportRevoked);
+            }
+
+            if record.status == PassportStatus::Archived {
+                return Err(Error::PassportArchived);
+            }
+
+            if self.superseded_by.contains(token_id) {
+                return Err(Error::AlreadySuperseded);
+            }
+
+            let owner = self.token_owner.get(token_id).ok_or(Error::TokenNotFound)?;
+            let new_token_id = self.mint_passport(
+                RegistrationInput {
+                    dataset_uri,
+                    payload_hash,
+                    dataset_type,
+                    granularity: record.granularity,
+                    subject_id_hash,
+                    validity_blocks: None,
+                },
+                MintParams {
+                    transferable: record.transferable,
+                    ..MintParams::new(caller, owner)
+                },
+            )?;
+
+            self.superseded_by.insert(token_id, &new_token_id);
+            self.supersedes.insert(new_token_id, &token_id);
+            self.clear_pending_update(token_id);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportSuperseded {
+                old_token_id: token_id,
+                new_token_id,
+                superseded_at: self.env().block_number(),
+                event_seq,
+            });
+
+            Ok(new_token_id)
+        }
+
+        /// Pause registration, updates, and transfers (Pauser-only).
+        ///
+        /// Read queries keep working. Intended for incident response and migrations.
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            self.ensure_role(Role::Pauser)?;
+            if self.paused {
+                return Err(Error::ContractPaused);
+            }
+            self.paused = true;
+            self.env().emit_event(Paused {
+                by: self.env().caller(),
+            });
+            Ok(())
+        }
+
+        /// Lift a pause (Pauser-only).
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            self.ensure_role(Role::Pauser)?;
+            if !self.paused {
+                return Err(Error::NotAllowed);
+            }
+            self.paused = false;
+            self.env().emit_event(Unpaused {
+                by: self.env().caller(),
+            });
+            Ok(())
+        }
+
+        /// Whether registration, updates, and transfers are paused.
+        #[ink(message)]
+        pub fn paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Freeze a token under investigation (Admin-only).
+        ///
+        /// Blocks transfers and updates regardless of the passport's status,
+        /// which stays under the issuer's control.
+        #[ink(message)]
+        pub fn freeze_token(&mut self, token_id: u128) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::FreezeToken(token_id))
+        }
+
+        /// Lift a token freeze (Admin-only).
+        #[ink(message)]
+        pub fn unfreeze_token(&mut self, token_id: u128) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::UnfreezeToken(token_id))
+        }
+
+        /// Whether a token is frozen.
+        #[ink(message)]
+        pub fn is_frozen(&self, token_id: u128) -> bool {
+            if self.is_hidden_draft(token_id) {
+                return false;
+            }
+            self.frozen.contains(token_id)
+        }
+
+        /// Set the maximum number of versions per token (admin-only, 0 = unlimited).
+        #[ink(message)]
+        pub fn set_max_versions(&mut self, max_versions: u32) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetMaxVersions(max_versions))
+        }
+
+        /// Get the configured maximum versions per token (0 = unlimited).
+        #[ink(message)]
+        pub fn max_versions(&self) -> u32 {
+            self.max_versions
+        }
+
+        /// Set the minimum number of blocks between dataset updates of a token
+        /// (issuer or admin, 0 = no limit).
+        ///
+        /// Guards against integrations flooding a token with redundant versions.
+        /// Admins that are not the issuer queue `AdminAction::SetMinUpdateInterval`
+        /// while an admin threshold or timelock is set.
+        #[ink(message)]
+        pub fn set_min_update_interval(&mut self, token_id: u128, blocks: u32) -> Result<()> {
+            let caller = self.env().caller();
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.is_issuer(&record, caller) {
+                self.ensure_direct_admin()?;
+            }
+            self.apply_admin_action(AdminAction::SetMinUpdateInterval(token_id, blocks))
+        }
+
+        /// Get the minimum number of blocks between dataset updates of a token.
+        #[ink(message)]
+        pub fn min_update_interval(&self, token_id: u128) -> u32 {
+            if self.is_hidden_draft(token_id) {
+                return 0;
+            }
+            self.min_update_interval.get(token_id).unwrap_or(0)
+        }
+
+        /// Set the minimum number of blocks between transfers of a token
+        /// (issuer-only, 0 = no limit).
+        ///
+        /// Rejects rapid back-and-forth transfers that would fake supply-chain movement.
+        #[ink(message)]
+        pub fn set_transfer_cooldown(&mut self, token_id: u128, blocks: u32) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            if blocks == 0 {
+                self.transfer_cooldown.remove(token_id);
+            } else {
+                self.transfer_cooldown.insert(token_id, &blocks);
+            }
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(TransferCooldownChanged {
+                token_id,
+                blocks,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the minimum number of blocks between transfers of a token.
+        #[ink(message)]
+        pub fn transfer_cooldown(&self, token_id: u128) -> u32 {
+            if self.is_hidden_draft(token_id) {
+                return 0;
+            }
+            self.transfer_cooldown.get(token_id).unwrap_or(0)
+        }
+
+        /// Set the blocks after registration or a dataset update during which a
+        /// passport cannot be transferred (admin-only, `0` = none).
+        ///
+        /// Narrows the window for anchor-and-flip fraud. Applies in addition to
+        /// per-token `set_transfer_cooldown`.
+        #[ink(message)]
+        pub fn set_update_transfer_cooldown(&mut self, blocks: u32) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetUpdateTransferCooldown(blocks))
+        }
+
+        /// Get the transfer cooldown after registration or an update.
+        #[ink(message)]
+        pub fn update_transfer_cooldown(&self) -> u32 {
+            self.update_transfer_cooldown
+        }
+
+        /// First block at which both transfer cooldowns of a token have passed.
+        #[ink(message)]
+        pub fn transfers_allowed_from(&self, token_id: u128) -> Option<u32> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            let record = self.passports.get(token_id)?;
+            Some(self.transfers_allowed_from_block(&record))
+        }
+
+        /// Attach terms of transfer to a token, or remove them with `None`
+        /// (issuer-only).
+        ///
+        /// While set, a transfer completes only if the recipient has accepted
+        /// exactly these terms with `accept_terms`.
+        #[ink(message)]
+        pub fn set_transfer_terms(
+            &mut self,
+            token_id: u128,
+            terms_hash: Option<[u8; 32]>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            match terms_hash {
+                Some(hash) => {
+                    self.transfer_terms.insert(token_id, &hash);
+                }
+                None => {
+                    self.transfer_terms.remove(token_id);
+                }
+            }
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(TransferTermsChanged {
+                token_id,
+                terms_hash,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the hash of a token's transfer terms.
+        #[ink(message)]
+        pub fn get_transfer_terms(&self, token_id: u128) -> Option<[u8; 32]> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.transfer_terms.get(token_id)
+        }
+
+        /// Accept a token's current transfer terms as a prospective recipient.
+        ///
+        /// `terms_hash` must match the terms attached to the token, so the
+        /// caller commits to the exact document it reviewed.
+        ///
+        /// # Errors
+        ///
+        /// * `NotAllowed` - The token has no transfer terms
+        /// * `InvalidInput` - `terms_hash` differs from the current terms
+        #[ink(message)]
+        pub fn accept_terms(&mut self, token_id: u128, terms_hash: [u8; 32]) -> Result<()> {
+            let caller = self.env().caller();
+            if !self.passports.contains(token_id) {
+                return Err(Error::TokenNotFound);
+            }
+
+            let current = self.transfer_terms.get(token_id).ok_or(Error::NotAllowed)?;
+            if current != terms_hash {
+                return Err(Error::InvalidInput);
+            }
+
+            self.terms_acceptances
+                .insert((token_id, caller), &(terms_hash, self.env().block_number()));
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(TermsAccepted {
+                token_id,
+                account: caller,
+                terms_hash,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the terms an account accepted for a token and the block of acceptance.
+        #[ink(message)]
+        pub fn get_terms_acceptance(
+            &self,
+            token_id: u128,
+            account: Address,
+        ) -> Option<([u8; 32], u32)> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.terms_acceptances.get((token_id, account))
+        }
+
+        /// Set the royalty marketplaces should pay on secondary sales of a token
+        /// (issuer-only, `0` basis points removes it).
+        ///
+        /// Informational like ERC-2981: the contract does not enforce payment.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - More than `MAX_ROYALTY_BASIS_POINTS`
+        #[ink(message)]
+        pub fn set_royalty(
+            &mut self,
+            token_id: u128,
+            receiver: Address,
+            basis_points: u16,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            if basis_points > MAX_ROYALTY_BASIS_POINTS {
+                return Err(Error::InvalidInput);
+            }
+            if basis_points == 0 {
+                self.royalties.remove(token_id);
+            } else {
+                self.royalties.insert(token_id, &(receiver, basis_points));
+            }
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(RoyaltySet {
+                token_id,
+                receiver,
+                basis_points,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Royalty receiver and amount for a sale at `sale_price` (`None` if no
+        /// royalty is set).
+        #[ink(message)]
+        pub fn royalty_info(&self, token_id: u128, sale_price: U256) -> Option<(Address, U256)> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            let (receiver, basis_points) = self.royalties.get(token_id)?;
+            let basis_points = U256::from(basis_points);
+            let denominator = U256::from(MAX_ROYALTY_BASIS_POINTS);
+            // Split the price so the multiplication cannot overflow
+            let amount = sale_price / denominator * basis_points
+                + sale_price % denominator * basis_points / denominator;
+            Some((receiver, amount))
+        }
+
+        // Role-based access control
+
+        /// Grant a role to an account (Admin-only).
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: Address) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::GrantRole(role, account))
+        }
+
+        /// Revoke a role from an account (Admin-only).
+        ///
+        /// # Errors
+        ///
+        /// * `NotAllowed` - Account lacks the role, or removing it would leave
+        ///   fewer Admins than the admin threshold
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: Address) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::RevokeRole(role, account))
+        }
+
+        /// Give up a role held by the caller. Admins cannot renounce below the
+        /// admin threshold (the last Admin never can).
+        #[ink(message)]
+        pub fn renounce_role(&mut self, role: Role) -> Result<()> {
+            let caller = self.env().caller();
+            self.remove_role(role, caller)
+        }
+
+        /// Propose handing the caller's Admin role to `new_admin` (Admin-only).
+        ///
+        /// Nothing changes until `new_admin` calls `accept_admin`. A new
+        /// proposal replaces the pending one. Under a timelock or an admin
+        /// threshold above one, hand over through queued `GrantRole` and
+        /// `RevokeRole` actions instead.
+        #[ink(message)]
+        pub fn propose_admin(&mut self, new_admin: Address) -> Result<()> {
+            self.ensure_direct_admin()?;
+            if self.has_role(Role::Admin, new_admin) {
+                return Err(Error::InvalidInput);
+            }
+
+            let caller = self.env().caller();
+            self.pending_admin = Some((caller, new_admin));
+            self.env().emit_event(AdminProposed {
+                proposer: caller,
+                proposed: new_admin,
+            });
+
+            Ok(())
+        }
+
+        /// Accept a pending Admin handover (proposed account only).
+        ///
+        /// Grants Admin to the caller and revokes it from the proposer.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized` - Caller is not the proposed account
+        /// * `NotAllowed` - The proposer no longer holds the Admin role, or a
+        ///   timelock or an admin threshold above one was set after the proposal
+        #[ink(message)]
+        pub fn accept_admin(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            let (proposer, proposed) = self.pending_admin.ok_or(Error::NotAllowed)?;
+            if caller != proposed {
+                return Err(Error::Unauthorized);
+            }
+            if !self.has_role(Role::Admin, proposer)
+                || self.timelock_delay > 0
+                || self.admin_threshold > 1
+            {
+                return Err(Error::NotAllowed);
+            }
+
+            self.pending_admin = None;
+            if self.roles.insert((Role::Admin, caller), &()).is_none() {
+                self.admin_count += 1;
+            }
+            self.env().emit_event(RoleGranted {
+                role: Role::Admin,
+                account: caller,
+                granted_by: proposer,
+            });
+            self.remove_role(Role::Admin, proposer)?;
+            self.env().emit_event(AdminAccepted {
+                previous: proposer,
+                admin: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Get the pending Admin handover as (proposer, proposed account).
+        #[ink(message)]
+        pub fn pending_admin(&self) -> Option<(Address, Address)> {
+            self.pending_admin
+        }
+
+        // Timelocked admin actions
+
+        /// Enable the timelock for sensitive admin actions (admin-only).
+        ///
+        /// While the delay is non-zero, every admin-only change (fees,
+        /// allowlists, blacklist, freezes, fund routing and withdrawals, roles,
+        /// and the other settings) fails with `NotAllowed` when called directly
+        /// and must be announced with `queue_action`; changing the delay itself
+        /// is then also queued.
+        #[ink(message)]
+        pub fn set_timelock_delay(&mut self, delay: u32) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetTimelockDelay(delay))
+        }
+
+        /// Get the timelock delay in blocks (0 = disabled).
+        #[ink(message)]
+        pub fn timelock_delay(&self) -> u32 {
+            self.timelock_delay
+        }
+
+        /// Require `threshold` Admin confirmations for sensitive actions (admin-only).
+        ///
+        /// Above one, the same actions as under a timelock must be queued,
+        /// confirmed by other Admins with `confirm_action`, and executed once
+        /// enough current Admins confirmed. Changing the threshold itself is
+        /// then also queued.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Threshold is zero or exceeds the number of Admins
+        #[ink(message)]
+        pub fn set_admin_threshold(&mut self, threshold: u32) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetAdminThreshold(threshold))
+        }
+
+        /// Get the number of Admin confirmations sensitive actions require.
+        #[ink(message)]
+        pub fn admin_threshold(&self) -> u32 {
+            self.admin_threshold
+        }
+
+        /// Announce an admin action, executable after the timelock delay (admin-only).
+        ///
+        /// # Returns
+        ///
+        /// ID of the queued action
+        #[ink(message)]
+        pub fn queue_action(&mut self, action: AdminAction) -> Result<u32> {
+            self.ensure_role(Role::Admin)?;
+
+            let action_id = self.next_action_id;
+            let eta = self
+                .env()
+                .block_number()
+                .saturating_add(self.timelock_delay);
+            self.queued_actions.insert(
+                action_id,
+                &QueuedAction {
+                    action: action.clone(),
+                    queued_by: self.env().caller(),
+                    eta,
+                    confirmations: ink::prelude::vec![self.env().caller()],
+                },
+            );
+            self.next_action_id += 1;
+
+            self.env().emit_event(ActionQueued {
+                action_id,
+                action,
+                eta,
+            });
+
+            Ok(action_id)
+        }
+
+        /// Confirm a queued action (admin-only, once per Admin).
+        #[ink(message)]
+        pub fn confirm_action(&mut self, action_id: u32) -> Result<()> {
+            self.ensure_role(Role::Admin)?;
+            let caller = self.env().caller();
+            let mut queued = self
+                .queued_actions
+                .get(action_id)
+                .ok_or(Error::NotAllowed)?;
+            if queued.confirmations.contains(&caller) {
+                return Err(Error::NotAllowed);
+            }
+            queued.confirmations.push(caller);
+            self.queued_actions.insert(action_id, &queued);
+
+            self.env().emit_event(ActionConfirmed {
+                action_id,
+                admin: caller,
+                confirmations: queued.confirmations.len() as u32,
+            });
+
+            Ok(())
+        }
+
+        /// Execute a queued action once its delay has passed and enough Admins
+        /// confirmed it (admin-only).
+        ///
+        /// # Errors
+        ///
+        /// * `NotAllowed` - No such queued action
+        /// * `TimelockActive` - The delay has not passed yet
+        /// * `NotApproved` - Fewer current Admins confirmed than the admin threshold
+        #[ink(message)]
+        pub fn execute_action(&mut self, action_id: u32) -> Result<()> {
+            self.ensure_role(Role::Admin)?;
+            let queued = self
+                .queued_actions
+                .get(action_id)
+                .ok_or(Error::NotAllowed)?;
+            if self.env().block_number() < queued.eta {
+                return Err(Error::TimelockActive);
+            }
+
+            // Admins revoked since confirming no longer count
+            let confirmed = queued
+                .confirmations
+                .iter()
+                .filter(|admin| self.has_role(Role::Admin, **admin))
+                .count() as u32;
+            if confirmed < self.admin_threshold {
+                return Err(Error::NotApproved);
+            }
+
+            self.queued_actions.remove(action_id);
+            self.apply_admin_action(queued.action)?;
+            self.env().emit_event(ActionExecuted { action_id });
+
+            Ok(())
+        }
+
+        /// Cancel a queued action (admin-only).
+        #[ink(message)]
+        pub fn cancel_action(&mut self, action_id: u32) -> Result<()> {
+            self.ensure_role(Role::Admin)?;
+            if self.queued_actions.take(action_id).is_none() {
+                return Err(Error::NotAllowed);
+            }
+            self.env().emit_event(ActionCancelled { action_id });
+            Ok(())
+        }
+
+        /// Get a queued admin action.
+        #[ink(message)]
+        pub fn get_queued_action(&self, action_id: u32) -> Option<QueuedAction> {
+            self.queued_actions.get(action_id)
+        }
+
+        /// Whether `account` holds `role`.
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: Address) -> bool {
+            self.roles.contains((role, account))
+        }
+
+        /// Get the token that superseded `token_id`, if any.
+        #[ink(message)]
+        pub fn superseded_by(&self, token_id: u128) -> Option<u128> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.superseded_by.get(token_id)
+        }
+
+        /// Get the token that `token_id` supersedes, if any.
+        #[ink(message)]
+        pub fn supersedes(&self, token_id: u128) -> Option<u128> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.supersedes.get(token_id)
+        }
+
+        /// Get the current anchor record.
+        ///
+        /// Restricted passports return `None` unless the caller is the issuer,
+        /// the owner, or an approved account; use `get_passport_stub` instead.
+        /// Drafts return `None` for anyone other than their issuer.
+        #[ink(message)]
+        pub fn get_passport(&self, token_id: u128) -> Option<PassportRecord> {
+            if !self.can_read(token_id) {
+                return None;
+            }
+            self.passports.get(token_id)
+        }
+
+        /// Get the minimal public view (issuer + status) of a passport.
+        #[ink(message)]
+        pub fn get_passport_stub(&self, token_id: u128) -> Option<PassportStub> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            let record = self.passports.get(token_id)?;
+            Some(PassportStub {
+                token_id,
+                issuer: record.issuer,
+                status: self.effective_status_of(&record),
+                visibility: self.visibility.get(token_id).unwrap_or_default(),
+            })
+        }
+
+        /// Set the read visibility of a passport (issuer-only).
+        #[ink(message)]
+        pub fn set_visibility(&mut self, token_id: u128, visibility: Visibility) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            match visibility {
+                Visibility::Public => self.visibility.remove(token_id),
+                Visibility::Restricted => {
+                    self.visibility.insert(token_id, &visibility);
+                }
+            }
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(VisibilityChanged {
+                token_id,
+                visibility,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the read visibility of a passport.
+        #[ink(message)]
+        pub fn get_visibility(&self, token_id: u128) -> Visibility {
+            if self.is_hidden_draft(token_id) {
+                return Visibility::default();
+            }
+            self.visibility.get(token_id).unwrap_or_default()
+        }
+
+        /// Whether the issuer is still the current owner of the token.
+        #[ink(message)]
+        pub fn issuer_holds_custody(&self, token_id: u128) -> bool {
+            if self.is_hidden_draft(token_id) {
+                return false;
+            }
+            match (self.passports.get(token_id), self.token_owner.get(token_id)) {
+                (Some(record), Some(owner)) => record.issuer == owner,
+                _ => false,
+            }
+        }
+
+        /// Get the verification summary of a passport.
+        ///
+        /// Returns `None` for unknown tokens, for Restricted passports the
+        /// caller may not read in full, and for Drafts queried by anyone other
+        /// than their issuer.
+        #[ink(message)]
+        pub fn get_verification_summary(&self, token_id: u128) -> Option<VerificationSummary> {
+            if !self.can_read(token_id) {
+                return None;
+            }
+
+            let record = self.passports.get(token_id)?;
+            let owner = self.token_owner.get(token_id);
+
+            Some(VerificationSummary {
+                token_id,
+                issuer_holds_custody: owner == Some(record.issuer),
+                issuer: record.issuer,
+                owner,
+                status: self.effective_status_of(&record),
+                version: record.version,
+                payload_hash: record.payload_hash,
+                notices: self.get_notices(token_id),
+            })
+        }
+
+        /// Contract-wide counters and invariants for monitoring.
+        ///
+        /// Counters are maintained incrementally, so this is constant-time;
+        /// alert on `healthy == false`.
+        #[ink(message)]
+        pub fn health(&self) -> HealthReport {
+            let contract_balance = self.env().balance();
+            let balances_consistent = self.owned_total == self.live_tokens;
+            let fund_covered = contract_balance >= self.fund_balance;
+
+            HealthReport {
+                minted: self.next_token_id,
+                live_tokens: self.live_tokens,
+                owned_total: self.owned_total,
+                admin_count: self.admin_count,
+                paused: self.paused,
+                fund_balance: self.fund_balance,
+                contract_balance,
+                balances_consistent,
+                fund_covered,
+                healthy: balances_consistent
+                    && fund_covered
+                    && self.live_tokens <= self.next_token_id
+                    && self.admin_count > 0,
+            }
+        }
+
+        /// Validate the storage invariants of one token (e.g. after a migration).
+        ///
+        /// Returns `None` for unknown tokens. Reads at most
+        /// `MAX_SELF_CHECK_VERSIONS` version entries, so the cost is bounded.
+        /// A subject hash later reassigned to another token is reported as
+        /// inconsistent.
+        #[ink(message)]
+        pub fn self_check(&self, token_id: u128) -> Option<SelfCheckReport> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            let record = self.passports.get(token_id)?;
+            let owner = self.token_owner.get(token_id);
+
+            let oldest_checked = record
+                .version
+                .saturating_sub(MAX_SELF_CHECK_VERSIONS - 1)
+                .max(1);
+            let history_contiguous = (oldest_checked..=record.version)
+                .all(|v| self.version_history.contains((token_id, v)));
+
+            let burned_by = self.burned.get(token_id).map(|burn| burn.burned_by);
+            let owner_set = match burned_by {
+                Some(_) => owner.is_none(),
+                None => owner.is_some_and(|owner| self.balance_of(owner) > 0),
+            };
+
+            let status_len = self.status_history_len(token_id);
+            let status_log_consistent = status_len > 0
+                && self
+                    .status_history
+                    .get((token_id, status_len - 1))
+                    .is_some_and(|entry| entry.new_status == record.status);
+
+            let custody_len = self.custody_len.get(token_id).unwrap_or(0);
+            let pruned = self.custody_checkpoint(token_id).pruned_count;
+            let custody_log_consistent = custody_len > 0
+                && pruned <= custody_len
+                && (pruned == custody_len
+                    || self
+                        .custody_log
+                        .get((token_id, custody_len - 1))
+                        .is_some_and(|entry| Some(entry.to) == owner.or(burned_by)));
+
+            let subject_mapping_consistent = record
+                .subject_id_hash
+                .is_none_or(|hash| self.subject_id_to_token.get(hash) == Some(token_id));
+
+            let in_all_tokens = self
+                .all_tokens_index
+                .get(token_id)
+                .is_some_and(|index| self.all_tokens.get(index) == Some(token_id));
+            let counters_consistent = match owner {
+                // Drafts are counted separately and indexed on publication
+                Some(owner) if record.status == PassportStatus::Draft => {
+                    !in_all_tokens
+                        && !self.owned_tokens_index.contains(token_id)
+                        && self.draft_balances.get(owner).unwrap_or(0) > 0
+                }
+                Some(owner) => {
+                    in_all_tokens
+                        && self.owned_tokens_count.get(owner).unwrap_or(0)
+                            - self.draft_balances.get(owner).unwrap_or(0)
+                            == self.owned_tokens_len.get(owner).unwrap_or(0)
+                        && self.owned_tokens_index.get(token_id).is_some_and(|index| {
+                            self.owned_tokens.get((owner, index)) == Some(token_id)
+                        })
+                }
+                None => !in_all_tokens && !self.owned_tokens_index.contains(token_id),
+            };
+
+            Some(SelfCheckReport {
+                token_id,
+                history_contiguous,
+                owner_set,
+                status_log_consistent,
+                custody_log_consistent,
+                subject_mapping_consistent,
+                counters_consistent,
+                healthy: history_contiguous
+                    && owner_set
+                    && status_log_consistent
+                    && custody_log_consistent
+                    && subject_mapping_consistent
+                    && counters_consistent,
+            })
+        }
+
+        /// Start a named migration (admin-only).
+        ///
+        /// The migration then advances through `continue_migration`, which
+        /// anyone may call until it reports completion.
+        ///
+        /// # Errors
+        ///
+        /// * `NotAllowed` - Another migration is still running
+        #[ink(message)]
+        pub fn start_migration(&mut self, migration: Migration) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::StartMigration(migration))
+        }
+
+        /// Process up to `max_items` items of the running migration.
+        ///
+        /// At most `MAX_MIGRATION_ITEMS_PER_CALL` items are processed per call.
+        /// Tokens registered while the migration runs are picked up as well.
+        ///
+        /// # Returns
+        ///
+        /// `true` once the migration has completed
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - `max_items` is zero
+        /// * `NotAllowed` - No migration is running
+        #[ink(message)]
+        pub fn continue_migration(&mut self, max_items: u32) -> Result<bool> {
+            if max_items == 0 {
+                return Err(Error::InvalidInput);
+            }
+            let mut state = self.migration.clone().ok_or(Error::NotAllowed)?;
+
+            let end = state
+                .cursor
+                .saturating_add(u128::from(max_items.min(MAX_MIGRATION_ITEMS_PER_CALL)))
+                .min(self.next_token_id);
+
+            match state.migration {
+                Migration::RecountTokens => {
+                    for token_id in state.cursor..end {
+                        if self.passports.contains(token_id) && !self.burned.contains(token_id) {
+                            state.live += 1;
+                        }
+                        if self.token_owner.contains(token_id) {
+                            state.owned += 1;
+                        }
+                    }
+                }
+                Migration::BackfillTokenIndex => {
+                    for token_id in state.cursor..end {
+                        // Drafts are indexed on publication
+                        let Some(record) = self.passports.get(token_id) else {
+                            continue;
+                        };
+                        if record.status == PassportStatus::Draft {
+                            continue;
+                        }
+                        if !self.burned.contains(token_id) {
+                            self.index_token(token_id);
+                        }
+                        if let Some(owner) = self.token_owner.get(token_id) {
+                            self.index_owned(owner, token_id);
+                        }
+                    }
+                }
+            }
+            state.processed += end - state.cursor;
+            state.cursor = end;
+
+            let done = state.cursor >= self.next_token_id;
+            if done {
+                match state.migration {
+                    Migration::RecountTokens => {
+                        self.live_tokens = state.live;
+                        self.owned_total = state.owned;
+                    }
+                    Migration::BackfillTokenIndex => {}
+                }
+                self.migration = None;
+                self.completed_migrations
+                    .insert(state.migration, &self.env().block_number());
+            } else {
+                self.migration = Some(state.clone());
+            }
+
+            self.env().emit_event(MigrationProgressed {
+                migration: state.migration,
+                cursor: state.cursor,
+                processed: state.processed,
+                done,
+            });
+
+            Ok(done)
+        }
+
+        /// Get the running migration, if any.
+        #[ink(message)]
+        pub fn migration_status(&self) -> Option<MigrationState> {
+            self.migration.clone()
+        }
+
+        /// Get the block at which `migration` last completed.
+        #[ink(message)]
+        pub fn migration_completed_at(&self, migration: Migration) -> Option<u32> {
+            self.completed_migrations.get(migration)
+        }
+
+        /// Whether this deployment implements an extension (see the `INTERFACE_*`
+        /// constants), so SDKs and wallets can feature-detect.
+        #[ink(message)]
+        pub fn supports_interface(&self, interface_id: [u8; 4]) -> bool {
+            SUPPORTED_INTERFACES.contains(&interface_id)
+        }
+
+        /// Short human-readable summary of a call to this contract, for
+        /// hardware-wallet and multisig confirmation screens.
+        ///
+        /// Covers the commonly signed state-changing messages. Returns `None`
+        /// for other selectors and for arguments that do not decode exactly.
+        #[ink(message)]
+        pub fn describe_call(
+            &self,
+            selector: [u8; 4],
+            encoded_args: ink::prelude::vec::Vec<u8>,
+        ) -> Option<String> {
+            use ink::prelude::format;
+            use scale::DecodeAll;
+
+            const REGISTER_PASSPORT: [u8; 4] = ink::selector_bytes!("register_passport");
+            const UPDATE_DATASET: [u8; 4] = ink::selector_bytes!("update_dataset");
+            const REVOKE_PASSPORT: [u8; 4] = ink::selector_bytes!("revoke_passport");
+            const SUSPEND_PASSPORT: [u8; 4] = ink::selector_bytes!("suspend_passport");
+            const ARCHIVE_PASSPORT: [u8; 4] = ink::selector_bytes!("archive_passport");
+            const TRANSFER: [u8; 4] = ink::selector_bytes!("transfer");
+            const TRANSFER_FROM: [u8; 4] = ink::selector_bytes!("transfer_from");
+            const APPROVE: [u8; 4] = ink::selector_bytes!("approve");
+            const SET_APPROVAL_FOR_ALL: [u8; 4] = ink::selector_bytes!("set_approval_for_all");
+            const FREEZE_TOKEN: [u8; 4] = ink::selector_bytes!("freeze_token");
+            const PAUSE: [u8; 4] = ink::selector_bytes!("pause");
+            const UNPAUSE: [u8; 4] = ink::selector_bytes!("unpause");
+            const GRANT_ROLE: [u8; 4] = ink::selector_bytes!("grant_role");
+            const REVOKE_ROLE: [u8; 4] = ink::selector_bytes!("revoke_role");
+            const QUEUE_ACTION: [u8; 4] = ink::selector_bytes!("queue_action");
+
+            fn with_reason(summary: String, reason: Option<String>) -> String {
+                match reason {
+                    Some(reason) => format!("{}: reason {}", summary, reason),
+                    None => summary,
+                }
+            }
+
+            let args = &mut &encoded_args[..];
+            let description = match selector {
+                REGISTER_PASSPORT => {
+                    let (uri, _, _, granularity, _, validity_blocks): (
+                        String,
+                        [u8; 32],
+                        String,
+                        Granularity,
+                        Option<[u8; 32]>,
+                        Option<u32>,
+                    ) = DecodeAll::decode_all(args).ok()?;
+                    let summary = format!("Register {:?} passport: {}", granularity, uri);
+                    match validity_blocks {
+                        Some(blocks) => format!("{} (valid for {} blocks)", summary, blocks),
+                        None => summary,
+                    }
+                }
+                UPDATE_DATASET => {
+                    let (token_id, uri, _, _, _, _, _): (
+                        u128,
+                        String,
+                        [u8; 32],
+                        String,
+                        Option<[u8; 32]>,
+                        Option<String>,
+                        Option<[u8; 32]>,
+                    ) = DecodeAll::decode_all(args).ok()?;
+                    format!("Update passport #{} to {}", token_id, uri)
+                }
+                REVOKE_PASSPORT => {
+                    let (token_id, reason): (u128, Option<String>) =
+                        DecodeAll::decode_all(args).ok()?;
+                    with_reason(format!("Revoke passport #{}", token_id), reason)
+                }
+                SUSPEND_PASSPORT => {
+                    let (token_id, reason): (u128, Option<String>) =
+                        DecodeAll::decode_all(args).ok()?;
+                    with_reason(format!("Suspend passport #{}", token_id), reason)
+                }
+                ARCHIVE_PASSPORT => {
+                    let token_id: u128 = DecodeAll::decode_all(args).ok()?;
+                    format!("Archive passport #{}", token_id)
+                }
+                TRANSFER => {
+                    let (to, token_id): (Address, u128) = DecodeAll::decode_all(args).ok()?;
+                    format!("Transfer passport #{} to {:?}", token_id, to)
+                }
+                TRANSFER_FROM => {
+                    let (from, to, token_id): (Address, Address, u128) =
+                        DecodeAll::decode_all(args).ok()?;
+                    format!(
+                        "Transfer passport #{} from {:?} to {:?}",
+                        token_id, from, to
+                    )
+                }
+                APPROVE => {
+                    let (to, token_id): (Address, u128) = DecodeAll::decode_all(args).ok()?;
+                    format!("Allow {:?} to transfer passport #{}", to, token_id)
+                }
+                SET_APPROVAL_FOR_ALL => {
+                    let (operator, approved): (Address, bool) = DecodeAll::decode_all(args).ok()?;
+                    if approved {
+                        format!("Allow {:?} to transfer all your passports", operator)
+                    } else {
+                        format!("Stop {:?} from transferring your passports", operator)
+                    }
+                }
+                FREEZE_TOKEN => {
+                    let token_id: u128 = DecodeAll::decode_all(args).ok()?;
+                    format!("Freeze passport #{}", token_id)
+                }
+                PAUSE if encoded_args.is_empty() => String::from("Pause the registry"),
+                UNPAUSE if encoded_args.is_empty() => String::from("Unpause the registry"),
+                GRANT_ROLE => {
+                    let (role, account): (Role, Address) = DecodeAll::decode_all(args).ok()?;
+                    format!("Grant {:?} role to {:?}", role, account)
+                }
+                REVOKE_ROLE => {
+                    let (role, account): (Role, Address) = DecodeAll::decode_all(args).ok()?;
+                    format!("Revoke {:?} role from {:?}", role, account)
+                }
+                QUEUE_ACTION => {
+                    let action: AdminAction = DecodeAll::decode_all(args).ok()?;
+                    format!("Queue admin action {:?}", action)
+                }
+                _ => return None,
+            };
+
+            Some(description)
+        }
+
+        /// Post a time-limited notice on a passport (issuer-only).
+        ///
+        /// Notices carry non-revocation communications such as safety
+        /// information or available firmware updates. They stop being returned
+        /// once `expires_at` is reached.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty URI or expiry not in the future
+        /// * `NotAllowed` - `MAX_NOTICES` unexpired notices already posted
+        #[ink(message)]
+        pub fn post_notice(
+            &mut self,
+            token_id: u128,
+            notice_type: NoticeType,
+            uri: String,
+            hash: [u8; 32],
+            expires_at: u32,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            let block_number = self.env().block_number();
+            if uri.is_empty() || expires_at <= block_number {
+                return Err(Error::InvalidInput);
+            }
+
+            let mut notices = self.notices.get(token_id).unwrap_or_default();
+            notices.retain(|n| n.expires_at > block_number);
+            if notices.len() >= MAX_NOTICES {
+                return Err(Error::NotAllowed);
+            }
+
+            notices.push(Notice {
+                notice_type: notice_type.clone(),
+                uri: uri.clone(),
+                hash,
+                posted_at: block_number,
+                expires_at,
+            });
+            self.notices.insert(token_id, &notices);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(NoticePosted {
+                token_id,
+                notice_type,
+                uri,
+                hash,
+                expires_at,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the unexpired notices of a passport (oldest first).
+        #[ink(message)]
+        pub fn get_notices(&self, token_id: u128) -> ink::prelude::vec::Vec<Notice> {
+            if self.is_hidden_draft(token_id) {
+                return ink::prelude::vec::Vec::new();
+            }
+            let block_number = self.env().block_number();
+            let mut notices = self.notices.get(token_id).unwrap_or_default();
+            notices.retain(|n| n.expires_at > block_number);
+            notices
+        }
+
+        /// Flag a passport with a market-surveillance concern (Regulator-only).
+        ///
+        /// Informational only: the flag grants no update rights and does not
+        /// change the passport's status. Flagging again replaces the reason.
+        #[ink(message)]
+        pub fn flag_passport(&mut self, token_id: u128, reason_hash: [u8; 32]) -> Result<()> {
+            self.ensure_role(Role::Regulator)?;
+            if !self.passports.contains(token_id) {
+                return Err(Error::TokenNotFound);
+            }
+            self.ensure_not_draft(token_id)?;
+
+            let caller = self.env().caller();
+            self.flags.insert(
+                token_id,
+                &PassportFlag {
+                    reason_hash,
+                    flagged_by: caller,
+                    flagged_at: self.env().block_number(),
+                },
+            );
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportFlagged {
+                token_id,
+                regulator: caller,
+                reason_hash,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Clear a passport flag (Regulator-only).
+        #[ink(message)]
+        pub fn clear_flag(&mut self, token_id: u128) -> Result<()> {
+            self.ensure_role(Role::Regulator)?;
+            if self.flags.take(token_id).is_none() {
+                return Err(Error::NotAllowed);
+            }
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(FlagCleared {
+                token_id,
+                regulator: self.env().caller(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Get the active regulator flag on a passport, if any.
+        #[ink(message)]
+        pub fn get_flag(&self, token_id: u128) -> Option<PassportFlag> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.flags.get(token_id)
+        }
+
+        // Certifiers and endorsements
+
+        /// Register a certifier allowed to endorse passports (admin-only).
+        #[ink(message)]
+        pub fn add_certifier(&mut self, certifier: Address) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::AddCertifier(certifier))
+        }
+
+        /// Remove a certifier (admin-only). Its existing endorsements are kept.
+        #[ink(message)]
+        pub fn remove_certifier(&mut self, certifier: Address) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::RemoveCertifier(certifier))
+        }
+
+        /// Whether an account is a registered certifier.
+        #[ink(message)]
+        pub fn is_certifier(&self, account: Address) -> bool {
+            self.certifiers.contains(account)
+        }
+
+        /// Attach a conformity endorsement to a passport (registered certifiers only).
+        ///
+        /// Each certifier holds at most one endorsement per token; endorsing
+        /// again replaces it. `certificate_hash` is the hash of the external
+        /// certificate number, looked up by `find_attestation_by_certificate`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized` - Caller is not a registered certifier
+        /// * `InvalidInput` - Empty evidence_uri
+        /// * `NotAllowed` - `MAX_ENDORSEMENTS` certifiers already endorsed the
+        ///   token, or `certificate_hash` is indexed for another endorsement
+        #[ink(message)]
+        pub fn endorse_passport(
+            &mut self,
+            token_id: u128,
+            claim_hash: [u8; 32],
+            evidence_uri: String,
+            evidence_hash: [u8; 32],
+            certificate_hash: Option<[u8; 32]>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            if !self.certifiers.contains(caller) {
+                return Err(Error::Unauthorized);
+            }
+            if !self.passports.contains(token_id) {
+                return Err(Error::TokenNotFound);
+            }
+            self.ensure_not_draft(token_id)?;
+            if evidence_uri.is_empty() {
+                return Err(Error::InvalidInput);
+            }
+            if self.strict_uri_mode {
+                validate_ipfs_uri(&evidence_uri)?;
+            }
+
+            self.add_endorsement(
+                token_id,
+                Endorsement {
+                    certifier: caller,
+                    claim_hash,
+                    evidence_uri,
+                    evidence_hash,
+                    certificate_hash,
+                    endorsed_at: self.env().block_number(),
+                },
+            )
+        }
+
+        /// Withdraw the caller's endorsement of a passport.
+        #[ink(message)]
+        pub fn withdraw_endorsement(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            let mut endorsements = self.endorsements.get(token_id).unwrap_or_default();
+            let index = endorsements
+                .iter()
+                .position(|e| e.certifier == caller)
+                .ok_or(Error::NotAllowed)?;
+            if let Some(certificate_hash) = endorsements.remove(index).certificate_hash {
+                self.certificates.remove(certificate_hash);
+            }
+            self.endorsements.insert(token_id, &endorsements);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(EndorsementWithdrawn {
+                token_id,
+                certifier: caller,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Register a passport and attach certifier attestations signed
+        /// off-chain, so it never exists without its conformity evidence.
+        ///
+        /// Each attestation is signed by its certifier over
+        /// `attestation_message(issuer, payload_hash, ...)` and becomes an
+        /// endorsement as if the certifier had called `endorse_passport`.
+        /// Issuers whose registrations are held as Drafts (issuance policy or
+        /// reviewer) cannot use it.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - No attestations, more than `MAX_ENDORSEMENTS`,
+        ///   two from one certifier or with one certificate hash, or an empty
+        ///   evidence_uri
+        /// * `Unauthorized` - An attestation is not from a registered certifier
+        /// * `InvalidSignature` - An attestation signature does not recover to
+        ///   its certifier
+        /// * `NotAllowed` - The caller's registrations are held as Drafts, or a
+        ///   certificate hash is already indexed for another endorsement
+        /// * Otherwise as `register_passport`
+        #[ink(message, payable)]
+        pub fn register_with_attestations(
+            &mut self,
+            registration: RegistrationInput,
+            attestations: ink::prelude::vec::Vec<SignedAttestation>,
+        ) -> Result<u128> {
+            let caller = self.env().caller();
+            if attestations.is_empty() || attestations.len() > MAX_ENDORSEMENTS {
+                return Err(Error::InvalidInput);
+            }
+            if self.issuance_policy_of(caller).is_some() || self.reviewer_of(caller).is_some() {
+                return Err(Error::NotAllowed);
+            }
+
+            // Every attestation is checked before the passport is minted
+            for (i, attestation) in attestations.iter().enumerate() {
+                if attestations[..i].iter().any(|other| {
+                    other.certifier == attestation.certifier
+                        || (attestation.certificate_hash.is_some()
+                            && other.certificate_hash == attestation.certificate_hash)
+                }) || attestation.evidence_uri.is_empty()
+                {
+                    return Err(Error::InvalidInput);
+                }
+                if !self.certifiers.contains(attestation.certifier) {
+                    return Err(Error::Unauthorized);
+                }
+                if attestation
+                    .certificate_hash
+                    .is_some_and(|hash| self.certificates.contains(hash))
+                {
+                    return Err(Error::NotAllowed);
+                }
+                if self.strict_uri_mode {
+                    validate_ipfs_uri(&attestation.evidence_uri)?;
+                }
+                let message_hash = self.attestation_message(
+                    caller,
+                    registration.payload_hash,
+                    attestation.claim_hash,
+                    attestation.evidence_uri.clone(),
+                    attestation.evidence_hash,
+                    attestation.certificate_hash,
+                );
+                if self.recover_signer(&attestation.signature, &message_hash)?
+                    != attestation.certifier
+                {
+                    return Err(Error::InvalidSignature);
+                }
+            }
+
+            let token_id = self.mint_passport(registration, MintParams::new(caller, caller))?;
+            let endorsed_at = self.env().block_number();
+            for attestation in attestations {
+                self.add_endorsement(
+                    token_id,
+                    Endorsement {
+                        certifier: attestation.certifier,
+                        claim_hash: attestation.claim_hash,
+                        evidence_uri: attestation.evidence_uri,
+                        evidence_hash: attestation.evidence_hash,
+                        certificate_hash: attestation.certificate_hash,
+                        endorsed_at,
+                    },
+                )?;
+            }
+
+            Ok(token_id)
+        }
+
+        /// Hash a certifier signs to attest a registration by `issuer` of the
+        /// dataset with `payload_hash` (see `register_with_attestations`).
+        #[ink(message)]
+        pub fn attestation_message(
+            &self,
+            issuer: Address,
+            payload_hash: [u8; 32],
+            claim_hash: [u8; 32],
+            evidence_uri: String,
+            evidence_hash: [u8; 32],
+            certificate_hash: Option<[u8; 32]>,
+        ) -> [u8; 32] {
+            self.env().hash_encoded::<ink::env::hash::Keccak256, _>(&(
+                b"fides-dpp:attest",
+                self.env().address(),
+                issuer,
+                payload_hash,
+                claim_hash,
+                evidence_uri,
+                evidence_hash,
+                certificate_hash,
+            ))
+        }
+
+        /// Get the endorsements attached to a passport (oldest first).
+        ///
+        /// Includes endorsements by certifiers removed since; check
+        /// `is_certifier` for current standing.
+        #[ink(message)]
+        pub fn get_endorsements(&self, token_id: u128) -> ink::prelude::vec::Vec<Endorsement> {
+            if !self.can_read(token_id) {
+                return ink::prelude::vec::Vec::new();
+            }
+            self.endorsements.get(token_id).unwrap_or_default()
+        }
+
+        /// Find the endorsement carrying an external certificate, by the hash
+        /// of its certificate number, as (token_id, endorsement).
+        ///
+        /// Returns `None` if no endorsement carries it or the caller cannot
+        /// read the passport.
+        #[ink(message)]
+        pub fn find_attestation_by_certificate(
+            &self,
+            certificate_hash: [u8; 32],
+        ) -> Option<(u128, Endorsement)> {
+            let (token_id, certifier) = self.certificates.get(certificate_hash)?;
+            if !self.can_read(token_id) {
+                return None;
+            }
+            self.endorsements
+                .get(token_id)?
+                .into_iter()
+                .find(|e| e.certifier == certifier)
+                .map(|endorsement| (token_id, endorsement))
+        }
+
+        // Software bill of materials
+
+        /// Anchor the software state of a connected product.
+        ///
+        /// Allowed to everyone with update rights (issuer, co-issuers, update
+        /// delegates, session keys), so devices can report firmware changes.
+        /// Kept separately from the dataset versions; `version` is not bumped.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty software_version or sbom_uri
+        /// * `PassportRevoked` / `PassportArchived` / `TokenFrozen`
+        #[ink(message)]
+        pub fn set_sbom(
+            &mut self,
+            token_id: u128,
+            software_version: String,
+            sbom_uri: String,
+            sbom_hash: [u8; 32],
+        ) -> Result<()> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.can_update(caller, &record) {
+                return Err(Error::Unauthorized);
+            }
+            if self.frozen.contains(token_id) {
+                return Err(Error::TokenFrozen);
+            }
+            if record.status == PassportStatus::Revoked {
+                return Err(Error::PassportRevoked);
+            }
+            if record.status == PassportStatus::Archived {
+                return Err(Error::PassportArchived);
+            }
+            if record.status == PassportStatus::Draft {
+                return Err(Error::PassportDraft);
+            }
+
+            if software_version.is_empty() || sbom_uri.is_empty() {
+                return Err(Error::InvalidInput);
+            }
+            if self.strict_uri_mode {
+                validate_ipfs_uri(&sbom_uri)?;
+            }
+
+            let index = self.sbom_len.get(token_id).unwrap_or(0);
+            self.sbom_history.insert(
+                (token_id, index),
+                &SbomEntry {
+                    software_version: software_version.clone(),
+                    sbom_uri,
+                    sbom_hash,
+                    set_by: caller,
+                    set_at: self.env().block_number(),
+                },
+            );
+            self.sbom_len.insert(token_id, &(index + 1));
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(SbomSet {
+                token_id,
+                software_version,
+                sbom_hash,
+                index,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the current SBOM of a passport, if any.
+        #[ink(message)]
+        pub fn get_sbom(&self, token_id: u128) -> Option<SbomEntry> {
+            if !self.can_read(token_id) {
+                return None;
+            }
+            let len = self.sbom_len.get(token_id).unwrap_or(0);
+            self.sbom_history.get((token_id, len.checked_sub(1)?))
+        }
+
+        /// Get SBOM history entries (oldest first, paginated).
+        #[ink(message)]
+        pub fn get_sbom_history(
+            &self,
+            token_id: u128,
+            offset: u32,
+            limit: u32,
+        ) -> ink::prelude::vec::Vec<SbomEntry> {
+            if !self.can_read(token_id) {
+                return ink::prelude::vec::Vec::new();
+            }
+            let len = self.sbom_len.get(token_id).unwrap_or(0);
+            let end = offset.saturating_add(limit).min(len);
+
+            (offset..end)
+                .filter_map(|i| self.sbom_history.get((token_id, i)))
+                .collect()
+        }
+
+        // Repairers and recyclers
+
+        /// Register a repairer or recycler (admin-only).
+        #[ink(message)]
+        pub fn add_service_provider(
+            &mut self,
+            provider: ServiceProvider,
+            account: Address,
+        ) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::AddServiceProvider(provider, account))
+        }
+
+        /// Remove a repairer or recycler (admin-only). Its records are kept.
+        #[ink(message)]
+        pub fn remove_service_provider(
+            &mut self,
+            provider: ServiceProvider,
+            account: Address,
+        ) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::RemoveServiceProvider(provider, account))
+        }
+
+        /// Whether an account is registered as the given service provider.
+        #[ink(message)]
+        pub fn is_service_provider(&self, provider: ServiceProvider, account: Address) -> bool {
+            self.service_providers.contains((provider, account))
+        }
+
+        /// Append a repair record to a passport (registered repairers only).
+        ///
+        /// Grants no update rights to the dataset and leaves the status unchanged.
+        #[ink(message)]
+        pub fn append_repair_record(
+            &mut self,
+            token_id: u128,
+            uri: String,
+            hash: [u8; 32],
+        ) -> Result<()> {
+            self.append_service_record(ServiceProvider::Repairer, token_id, uri, hash)
+        }
+
+        /// Append an end-of-life record to a passport (registered recyclers only).
+        ///
+        /// Does not archive the passport; the owner still calls `decommission`.
+        #[ink(message)]
+        pub fn append_end_of_life_record(
+            &mut self,
+            token_id: u128,
+            uri: String,
+            hash: [u8; 32],
+        ) -> Result<()> {
+            self.append_service_record(ServiceProvider::Recycler, token_id, uri, hash)
+        }
+
+        /// Get service records of a passport (oldest first, paginated).
+        #[ink(message)]
+        pub fn get_service_records(
+            &self,
+            token_id: u128,
+            offset: u32,
+            limit: u32,
+        ) -> ink::prelude::vec::Vec<ServiceRecord> {
+            if !self.can_read(token_id) {
+                return ink::prelude::vec::Vec::new();
+            }
+            let len = self.service_log_len.get(token_id).unwrap_or(0);
+            let end = offset.saturating_add(limit).min(len);
+
+            (offset..end)
+                .filter_map(|i| self.service_log.get((token_id, i)))
+                .collect()
+        }
+
+        /// Update the anchor (issuer, a valid session key of the issuer, or a
+        /// per-token update delegate). Increments `version`.
+        ///
+        /// `diff_uri` / `diff_hash` optionally reference a machine-readable change
+        /// summary against the previous version. Both must be set together.
+        ///
+        /// If an update fee is configured, at least that value must be transferred.
+        /// The whole transferred value accrues to the data-availability fund.
+        ///
+        /// While an update is staged for the issuer's reviewer, further updates
+        /// fail with `NotAllowed` until it is approved.
+        ///
+        /// NOTE: Granularity is immutable after registration.
+        #[ink(message, payable)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn update_dataset(
+            &mut self,
+            token_id: u128,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            subject_id_hash: Option<[u8; 32]>,
+            diff_uri: Option<String>,
+            diff_hash: Option<[u8; 32]>,
+        ) -> Result<()> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.can_update(caller, &record) {
+                return Err(Error::Unauthorized);
+            }
+
+            if self.frozen.contains(token_id) {
+                return Err(Error::TokenFrozen);
+            }
+
+            // Cannot update revoked passports
+            if record.status == PassportStatus::Revoked {
+                return Err(Error::PassportRevoked);
+            }
+
+            // Archived passports are read-only
+            if record.status == PassportStatus::Archived {
+                return Err(Error::PassportArchived);
+            }
+
+            if self.status_policy.suspended_blocks_updates
+                && self.effective_status_of(&record) == PassportStatus::Suspended
+            {
+                return Err(Error::PassportSuspended);
+            }
+
+            // Superseded passports are frozen; updates go to the successor
+            if self.superseded_by.contains(token_id) {
+                return Err(Error::AlreadySuperseded);
+            }
+
+            // Bounded history: once the limit is reached the token must be superseded
+            if self.max_versions != 0 && record.version >= self.max_versions {
+                return Err(Error::MaxVersionsReached);
+            }
+
+            // Rate limit: measured from the last dataset version, not status changes
+            if let Some(interval) = self.min_update_interval.get(token_id) {
+                let last_update = self
+                    .version_history
+                    .get((token_id, record.version))
+                    .map_or(record.created_at, |entry| entry.updated_at);
+                if self.env().block_number() < last_update.saturating_add(interval) {
+                    return Err(Error::TooFrequent);
+                }
+            }
+
+            // A staged (and paid) update must be approved before the next one
+            if self.pending_updates.contains(token_id) {
+                return Err(Error::NotAllowed);
+            }
+
+            self.collect_fee(self.update_fee)?;
+
+            // Validation: check for empty strings
+            if dataset_uri.is_empty() || dataset_type.is_empty() {
+                return Err(Error::InvalidInput);
+            }
+
+            if self.strict_uri_mode {
+                validate_ipfs_uri(&dataset_uri)?;
+            }
+
+            // Diff reference must be complete (URI + hash) or absent
+            match (&diff_uri, &diff_hash) {
+                (Some(uri), Some(_)) if !uri.is_empty() => {}
+                (None, None) => {}
+                _ => return Err(Error::InvalidInput),
+            }
+
+            // A newly attached subject hash may be reserved by a pre-claim
+            if let Some(new_hash) = subject_id_hash {
+                if record.subject_id_hash != Some(new_hash) {
+                    self.settle_pre_claim(record.issuer, new_hash)?;
+                }
+            }
+
+            let history_entry = VersionHistory {
+                version: record.version + 1,
+                dataset_uri,
+                payload_hash,
+                dataset_type,
+                updated_at: self.env().block_number(),
+                updated_by: caller,
+                diff_uri,
+                diff_hash,
+                issuer: record.issuer,
+            };
+
+            // Maker/checker: updates to published passports wait for the reviewer
+            if record.status != PassportStatus::Draft && self.reviewer_of(record.issuer).is_some() {
+                let version = history_entry.version;
+                let controller = self.controller_of(record.issuer);
+                let pending = self.pending_update_counts.get(controller).unwrap_or(0);
+                self.pending_update_counts
+                    .insert(controller, &(pending + 1));
+                let pending = self.issuer_pending_updates.get(record.issuer).unwrap_or(0);
+                self.issuer_pending_updates
+                    .insert(record.issuer, &(pending + 1));
+                self.pending_updates.insert(
+                    token_id,
+                    &PendingUpdate {
+                        entry: history_entry,
+                        subject_id_hash,
+                    },
+                );
+                let event_seq = self.next_event_seq(token_id);
+                self.env().emit_event(UpdateSubmittedForReview {
+                    token_id,
+                    version,
+                    payload_hash,
+                    submitted_by: caller,
+                    event_seq,
+                });
+                return Ok(());
+            }
+
+            self.publish_version(record, history_entry, subject_id_hash);
+
+            Ok(())
+        }
+
+        /// Revoke a passport (issuer-only).
+        #[ink(message)]
+        pub fn revoke_passport(&mut self, token_id: u128, reason: Option<String>) -> Result<()> {
+            self.revoke(token_id, reason)
+        }
+
+        /// Revoke many passports in one call, e.g. for a batch recall (issuer-only per token).
+        ///
+        /// Each token is processed independently: failures are reported per token
+        /// and do not abort the batch. At most `MAX_BATCH_SIZE` tokens per call.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty list or more than `MAX_BATCH_SIZE` tokens
+        #[ink(message)]
+        pub fn revoke_batch(
+            &mut self,
+            token_ids: ink::prelude::vec::Vec<u128>,
+            reason: Option<String>,
+        ) -> Result<ink::prelude::vec::Vec<BatchItemResult>> {
+            if token_ids.is_empty() || token_ids.len() > MAX_BATCH_SIZE {
+                return Err(Error::InvalidInput);
+            }
+
+            let requested = token_ids.len() as u32;
+            let mut revoked = 0u32;
+            let mut results = ink::prelude::vec::Vec::with_capacity(token_ids.len());
+
+            for token_id in token_ids {
+                let error = self.revoke(token_id, reason.clone()).err();
+                if error.is_none() {
+                    revoked += 1;
+                }
+                results.push(BatchItemResult { token_id, error });
+            }
+
+            self.env().emit_event(BatchRevoked {
+                issuer: self.env().caller(),
+                requested,
+                revoked,
+                reason,
+            });
+
+            Ok(results)
+        }
+
+        /// Number of entries in a passport's status history.
+        #[ink(message)]
+        pub fn status_history_len(&self, token_id: u128) -> u32 {
+            if self.is_hidden_draft(token_id) {
+                return 0;
+            }
+            self.status_history_len.get(token_id).unwrap_or(0)
+        }
+
+        /// Get status history entries (oldest first, paginated).
+        ///
+        /// The first entry records the initial status set at registration.
+        #[ink(message)]
+        pub fn get_status_history(
+            &self,
+            token_id: u128,
+            offset: u32,
+            limit: u32,
+        ) -> ink::prelude::vec::Vec<StatusChange> {
+            if !self.can_read(token_id) {
+                return ink::prelude::vec::Vec::new();
+            }
+            let len = self.status_history_len(token_id);
+            let end = offset.saturating_add(limit).min(len);
+
+            (offset..end)
+                .filter_map(|i| self.status_history.get((token_id, i)))
+                .collect()
+        }
+
+        /// Get the policy snapshot recorded when a passport was registered.
+        #[ink(message)]
+        pub fn get_issuance_context(&self, token_id: u128) -> Option<IssuanceContext> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.issuance_context.get(token_id)
+        }
+
+        /// Sequence number of the latest event emitted for a token (0 = none).
+        ///
+        /// Every token-scoped event carries an `event_seq` that increases by one
+        /// per event, so indexers can detect gaps and restore ordering.
+        #[ink(message)]
+        pub fn latest_event_seq(&self, token_id: u128) -> u64 {
+            if self.is_hidden_draft(token_id) {
+                return 0;
+            }
+            self.event_seqs.get(token_id).unwrap_or(0)
+        }
+
+        /// Block of the last activity (update, status change, or custody transfer).
+        #[ink(message)]
+        pub fn last_activity(&self, token_id: u128) -> Option<u32> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.last_activity.get(token_id)
+        }
+
+        /// Blocks elapsed since the last activity on a passport.
+        ///
+        /// Lets buyers gauge whether passport data is being maintained.
+        #[ink(message)]
+        pub fn staleness(&self, token_id: u128) -> Option<u32> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            let last = self.last_activity.get(token_id)?;
+            Some(self.env().block_number().saturating_sub(last))
+        }
+
+        /// Get the stored revocation details of a revoked passport.
+        #[ink(message)]
+        pub fn get_revocation_info(&self, token_id: u128) -> Option<RevocationInfo> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.revocations.get(token_id)
+        }
+
+        /// Suspend an Active passport (issuer-only).
+        ///
+        /// A Suspended passport stays readable. Whether it can still be updated
+        /// or transferred is governed by `status_policy()`; by default the issuer
+        /// may correct data but transfers are blocked. Revocation remains possible.
+        #[ink(message)]
+        pub fn suspend_passport(&mut self, token_id: u128, reason: Option<String>) -> Result<()> {
+            self.suspend(token_id, reason, None)
+        }
+
+        /// Suspend an Active passport until block `until` (issuer-only).
+        ///
+        /// Once `until` is reached the suspension is lapsed: transfers are allowed
+        /// again, `effective_status` reports Active, and anyone may call
+        /// `lift_expired_suspension` to persist the Active status.
+        #[ink(message)]
+        pub fn suspend_until(&mut self, token_id: u128, until: u32) -> Result<()> {
+            if until <= self.env().block_number() {
+                return Err(Error::InvalidInput);
+            }
+            self.suspend(token_id, None, Some(until))
+        }
+
+        /// Return a passport whose timed suspension has lapsed to Active. Callable by anyone.
+        #[ink(message)]
+        pub fn lift_expired_suspension(&mut self, token_id: u128) -> Result<()> {
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if record.status != PassportStatus::Suspended || !self.suspension_lapsed(token_id) {
+                return Err(Error::NotAllowed);
+            }
+
+            self.suspended_until.remove(token_id);
+            let block_number = self.change_status(
+                &mut record,
+                PassportStatus::Active,
+                Some(String::from("suspension expired")),
+            );
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportResumed {
+                token_id,
+                issuer: record.issuer,
+                resumed_at: block_number,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the end block of a timed suspension, if any.
+        #[ink(message)]
+        pub fn get_suspended_until(&self, token_id: u128) -> Option<u32> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.suspended_until.get(token_id)
+        }
+
+        /// Status as seen by verifiers: a Suspended passport whose timed
+        /// suspension has lapsed is reported as Active.
+        #[ink(message)]
+        pub fn effective_status(&self, token_id: u128) -> Option<PassportStatus> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            let record = self.passports.get(token_id)?;
+            Some(self.effective_status_of(&record))
+        }
+
+        /// Resume a Suspended passport back to Active (issuer-only).
+        #[ink(message)]
+        pub fn resume_passport(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.is_issuer(&record, caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            if record.status != PassportStatus::Suspended {
+                return Err(Error::NotAllowed);
+            }
+
+            self.suspended_until.remove(token_id);
+            let block_number = self.change_status(&mut record, PassportStatus::Active, None);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportResumed {
+                token_id,
+                issuer: caller,
+                resumed_at: block_number,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Archive a passport at end of life (issuer-only).
+        ///
+        /// Archived passports remain readable but can no longer be updated,
+        /// suspended, or transferred. Archiving is final.
+        #[ink(message)]
+        pub fn archive_passport(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.is_issuer(&record, caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            match record.status {
+                PassportStatus::Revoked => return Err(Error::PassportRevoked),
+                PassportStatus::Archived => return Err(Error::PassportArchived),
+                _ => {}
+            }
+
+            // A Draft is published first (see `change_status`)
+            record.archived_at = Some(self.env().block_number());
+            let block_number = self.change_status(&mut record, PassportStatus::Archived, None);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportArchived {
+                token_id,
+                issuer: caller,
+                archived_at: block_number,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Decommission a passport when the physical product is destroyed or
+        /// recycled (owner-only).
+        ///
+        /// Records a final end-of-life anchor and archives the passport, which
+        /// locks it against further transfers and updates. Unlike revocation,
+        /// this does not signal that the issuer's data was wrong.
+        ///
+        /// # Errors
+        ///
+        /// * `NotOwner` - Caller does not own the token
+        /// * `NotAllowed` - Token is co-owned
+        /// * `PassportRevoked` / `PassportArchived` - Passport already terminal
+        /// * `PassportDraft` - Drafts were never issued and cannot be decommissioned
+        #[ink(message)]
+        pub fn decommission(
+            &mut self,
+            token_id: u128,
+            reason: EndOfLifeReason,
+            evidence_hash: [u8; 32],
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if self.token_owner.get(token_id) != Some(caller) {
+                return Err(Error::NotOwner);
+            }
+            if self.co_ownerships.contains(token_id) {
+                return Err(Error::NotAllowed);
+            }
+
+            match record.status {
+                PassportStatus::Revoked => return Err(Error::PassportRevoked),
+                PassportStatus::Archived => return Err(Error::PassportArchived),
+                PassportStatus::Draft => return Err(Error::PassportDraft),
+                _ => {}
+            }
+
+            let block_number = self.env().block_number();
+            self.end_of_life.insert(
+                token_id,
+                &EndOfLifeRecord {
+                    reason: reason.clone(),
+                    evidence_hash,
+                    decommissioned_by: caller,
+                    decommissioned_at: block_number,
+                },
+            );
+
+            record.archived_at = Some(block_number);
+            self.clear_approval(token_id);
+            self.change_status(&mut record, PassportStatus::Archived, None);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportDecommissioned {
+                token_id,
+                owner: caller,
+                reason,
+                evidence_hash,
+                decommissioned_at: block_number,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the end-of-life anchor of a decommissioned passport.
+        #[ink(message)]
+        pub fn get_end_of_life(&self, token_id: u128) -> Option<EndOfLifeRecord> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.end_of_life.get(token_id)
+        }
+
+        /// Destroy a token, e.g. one issued by mistake (owner-only).
+        ///
+        /// Removes ownership state and emits `Transfer { to: None }`. The
+        /// passport record stays readable and is archived unless already
+        /// Revoked or Archived; `get_burn_record` keeps who burned it and when.
+        ///
+        /// # Errors
+        ///
+        /// * `NotOwner` - Caller does not own the token
+        /// * `NotApproved` - The issuer requires consent and has not given it
+        /// * `TokenFrozen` / `TokenLocked` - Token is frozen or locked
+        /// * `NotAllowed` - Token is co-owned
+        #[ink(message)]
+        pub fn burn(&mut self, token_id: u128) -> Result<()> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if self.token_owner.get(token_id) != Some(caller) {
+                return Err(Error::NotOwner);
+            }
+            if self.frozen.contains(token_id) {
+                return Err(Error::TokenFrozen);
+            }
+            if self.locked.contains(token_id) {
+                return Err(Error::TokenLocked);
+            }
+            if self.co_ownerships.contains(token_id) {
+                return Err(Error::NotAllowed);
+            }
+
+            // Drafts were never issued, so their issuer discards them freely
+            let issuer_consented = self.burn_consents.take(token_id).is_some();
+            if !issuer_consented
+                && record.status != PassportStatus::Draft
+                && self.burn_consent_required(record.issuer)
+            {
+                return Err(Error::NotApproved);
+            }
+
+            // Archive while the owner is still recorded, so a Draft is
+            // published (and its mint announced) before the burn
+            let block_number = self.env().block_number();
+            match record.status {
+                PassportStatus::Revoked | PassportStatus::Archived => {}
+                _ => {
+                    record.archived_at = Some(block_number);
+                    self.change_status(
+                        &mut record,
+                        PassportStatus::Archived,
+                        Some(String::from("burned")),
+                    );
+                }
+            }
+
+            self.clear_approval(token_id);
+            self.pending_transfers.remove(token_id);
+            self.custodians.remove(token_id);
+            self.custody_leases.remove(token_id);
+            self.clear_pending_update(token_id);
+            self.remove_token_from(&caller, token_id)?;
+            self.live_tokens -= 1;
+            self.recount_scanned(token_id, |state| state.live = state.live.saturating_sub(1));
+            self.unindex_token(token_id);
+
+            self.burned.insert(
+                token_id,
+                &BurnRecord {
+                    burned_by: caller,
+                    burned_at: block_number,
+                    issuer_consented,
+                },
+            );
+
+            self.emit_transfer(Some(caller), None, token_id);
+
+            Ok(())
+        }
+
+        /// Get the audit record of a burned token.
+        #[ink(message)]
+        pub fn get_burn_record(&self, token_id: u128) -> Option<BurnRecord> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.burned.get(token_id)
+        }
+
+        /// Require (or stop requiring) the caller's consent before owners burn
+        /// its passports.
+        ///
+        /// The setting belongs to the controlling key, so it covers every
+        /// issuer identity the caller controls and follows key rotations.
+        #[ink(message)]
+        pub fn set_burn_consent_required(&mut self, required: bool) -> Result<()> {
+            let caller = self.env().caller();
+            if required {
+                self.burn_consent_required.insert(caller, &());
+            } else {
+                self.burn_consent_required.remove(caller);
+            }
+            self.env().emit_event(BurnConsentRequirementChanged {
+                issuer: caller,
+                required,
+            });
+            Ok(())
+        }
+
+        /// Whether `issuer` requires consent before its passports are burned.
+        #[ink(message)]
+        pub fn burn_consent_required(&self, issuer: Address) -> bool {
+            self.burn_consent_required
+                .contains(self.controller_of(issuer))
+        }
+
+        /// Consent to the owner burning a token (issuer-only, used by the next burn).
+        #[ink(message)]
+        pub fn consent_to_burn(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+            self.burn_consents.insert(token_id, &());
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(BurnConsentGiven {
+                token_id,
+                issuer: caller,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Offer issuer authority over a token to another account (issuer-only).
+        ///
+        /// Authority only moves once `new_issuer` calls `accept_issuer_transfer`.
+        /// A new offer replaces any pending one.
+        #[ink(message)]
+        pub fn offer_issuer_transfer(&mut self, token_id: u128, new_issuer: Address) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            if new_issuer == caller {
+                return Err(Error::InvalidInput);
+            }
+
+            self.pending_issuer_transfers.insert(token_id, &new_issuer);
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(IssuerTransferOffered {
+                token_id,
+                issuer: caller,
+                new_issuer,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw a pending issuer transfer offer (issuer-only).
+        #[ink(message)]
+        pub fn cancel_issuer_transfer(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            let new_issuer = self
+                .pending_issuer_transfers
+                .take(token_id)
+                .ok_or(Error::NotAllowed)?;
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(IssuerTransferCancelled {
+                token_id,
+                issuer: caller,
+                new_issuer,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Accept a pending issuer transfer offer (offered account only).
+        ///
+        /// Subsequent versions record the new issuer in their history entry.
+        /// Session keys and update delegates of the previous issuer stop applying.
+        #[ink(message)]
+        pub fn accept_issuer_transfer(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if self.pending_issuer_transfers.get(token_id) != Some(caller) {
+                return Err(Error::Unauthorized);
+            }
+            self.pending_issuer_transfers.remove(token_id);
+
+            let previous_issuer = core::mem::replace(&mut record.issuer, caller);
+            self.passports.insert(token_id, &record);
+            self.touch(token_id);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(IssuerTransferred {
+                token_id,
+                previous_issuer,
+                new_issuer: caller,
+                version: record.version,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the account a token's issuer authority has been offered to, if any.
+        #[ink(message)]
+        pub fn pending_issuer_transfer(&self, token_id: u128) -> Option<Address> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.pending_issuer_transfers.get(token_id)
+        }
+
+        /// Rotate the key controlling all tokens of an issuer identity.
+        ///
+        /// `signature` is a 65-byte secp256k1 signature by the current key over
+        /// `issuer_rotation_message(issuer, new_key)`. Anyone may relay it, so a
+        /// retired key can be replaced without per-token transfers. The tokens
+        /// keep `issuer` as recorded; authority checks resolve it to the new key.
+        /// Session keys and update delegates granted by the old key stop applying,
+        /// and the identity's own address can no longer register passports.
+        /// The identity's Drafts and staged updates move to the new key, as do
+        /// token ID ranges reserved by the identity's own address. The old
+        /// key's issuance policy and reviewer carry over unless the new key has
+        /// its own, as does its burn-consent requirement; they are copied rather
+        /// than moved while the old key still controls another identity
+        /// (including its own address).
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - `new_key` already controls the identity
+        /// * `InvalidSignature` - Signature does not recover to the current key
+        #[ink(message)]
+        pub fn rotate_issuer_key(
+            &mut self,
+            issuer: Address,
+            new_key: Address,
+            signature: [u8; 65],
+        ) -> Result<()> {
+            let old_key = self.controller_of(issuer);
+            if new_key == old_key {
+                return Err(Error::InvalidInput);
+            }
+
+            let message_hash = self.issuer_rotation_message(issuer, new_key);
+            if self.recover_signer(&signature, &message_hash)? != old_key {
+                return Err(Error::InvalidSignature);
+            }
+
+            let nonce = self.issuer_rotation_nonces.get(issuer).unwrap_or(0);
+            self.issuer_rotation_nonces.insert(issuer, &(nonce + 1));
+            if old_key != issuer {
+                let count = self.controlled_identities.get(old_key).unwrap_or(1) - 1;
+                if count == 0 {
+                    self.controlled_identities.remove(old_key);
+                } else {
+                    self.controlled_identities.insert(old_key, &count);
+                }
+            }
+            if new_key == issuer {
+                self.issuer_controllers.remove(issuer);
+            } else {
+                self.issuer_controllers.insert(issuer, &new_key);
+                let count = self.controlled_identities.get(new_key).unwrap_or(0);
+                self.controlled_identities.insert(new_key, &(count + 1));
+            }
+
+            // Key-level settings stay with a key that still controls another
+            // identity (including its own); the new key gets a copy instead
+            let old_key_in_use = self.controlled_identities.contains(old_key)
+                || (old_key != issuer && !self.issuer_controllers.contains(old_key));
+            let policy = if old_key_in_use {
+                self.issuance_policies.get(old_key)
+            } else {
+                self.issuance_policies.take(old_key)
+            };
+            if let Some(policy) = policy {
+                if !self.issuance_policies.contains(new_key) {
+                    self.issuance_policies.insert(new_key, &policy);
+                }
+            }
+            let burn_consent = if old_key_in_use {
+                self.burn_consent_required.contains(old_key)
+            } else {
+                self.burn_consent_required.take(old_key).is_some()
+            };
+            if burn_consent {
+                self.burn_consent_required.insert(new_key, &());
+            }
+            let reviewer = if old_key_in_use {
+                self.reviewers.get(old_key)
+            } else {
+                self.reviewers.take(old_key)
+            };
+            if let Some(reviewer) = reviewer {
+                if !self.reviewers.contains(new_key) && reviewer != new_key {
+                    self.reviewers.insert(new_key, &reviewer);
+                }
+            }
+
+            // Drafts, staged updates and reserved IDs of this identity follow its tokens
+            if old_key == issuer {
+                if let Some(mut reservations) = self.id_reservations.take(issuer) {
+                    reservations.extend(self.id_reservations.get(new_key).unwrap_or_default());
+                    self.id_reservations.insert(new_key, &reservations);
+                }
+            }
+            if let Some(pending) = self.issuer_pending_updates.get(issuer) {
+                self.move_pending_updates(old_key, Some(new_key), pending);
+            }
+            let (moved, kept): (ink::prelude::vec::Vec<u128>, _) = self
+                .issuer_drafts
+                .take(old_key)
+                .unwrap_or_default()
+                .into_iter()
+                .partition(|id| {
+                    self.passports
+                        .get(id)
+                        .is_some_and(|record| record.issuer == issuer)
+                });
+            if !kept.is_empty() {
+                self.issuer_drafts.insert(old_key, &kept);
+            }
+            if !moved.is_empty() {
+                let mut drafts = moved;
+                drafts.extend(self.issuer_drafts.get(new_key).unwrap_or_default());
+                self.issuer_drafts.insert(new_key, &drafts);
+            }
+
+            self.env().emit_event(IssuerKeyRotated {
+                issuer,
+                old_key,
+                new_key,
+            });
+
+            Ok(())
+        }
+
+        /// Keccak-256 hash the current key must sign to rotate to `new_key`.
+        ///
+        /// Covers this contract's address and the issuer's rotation nonce, so a
+        /// signature cannot be replayed on another deployment or after a later rotation.
+        #[ink(message)]
+        pub fn issuer_rotation_message(&self, issuer: Address, new_key: Address) -> [u8; 32] {
+            let nonce = self.issuer_rotation_nonces.get(issuer).unwrap_or(0);
+            self.env().hash_encoded::<ink::env::hash::Keccak256, _>(&(
+                b"fides-dpp:rotate-issuer",
+                self.env().address(),
+                issuer,
+                new_key,
+                nonce,
+            ))
+        }
+
+        /// Key currently controlling an issuer identity (the issuer itself unless rotated).
+        #[ink(message)]
+        pub fn issuer_controller(&self, issuer: Address) -> Address {
+            self.controller_of(issuer)
+        }
+
+        /// Set or clear the expiry block of a passport (issuer-only).
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Expiry not in the future
+        /// * `PassportRevoked` / `PassportArchived` - Passport already terminal
+        #[ink(message)]
+        pub fn set_expiry(&mut self, token_id: u128, expires_at: Option<u32>) -> Result<()> {
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.is_issuer(&record, caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            match record.status {
+                PassportStatus::Revoked => return Err(Error::PassportRevoked),
+                PassportStatus::Archived => return Err(Error::PassportArchived),
+                PassportStatus::Draft => return Err(Error::PassportDraft),
+                _ => {}
+            }
+
+            if let Some(deadline) = expires_at {
+                if deadline <= self.env().block_number() {
+                    return Err(Error::InvalidInput);
+                }
+            }
+
+            record.expires_at = expires_at;
+            self.passports.insert(token_id, &record);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportExpirySet {
+                token_id,
+                expires_at,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Whether the passport's expiry block has been reached.
+        #[ink(message)]
+        pub fn is_expired(&self, token_id: u128) -> bool {
+            if self.is_hidden_draft(token_id) {
+                return false;
+            }
+            self.passports
+                .get(token_id)
+                .and_then(|record| record.expires_at)
+                .is_some_and(|deadline| self.env().block_number() >= deadline)
+        }
+
+        /// Archive a passport whose expiry has passed. Callable by anyone.
+        #[ink(message)]
+        pub fn expire_passport(&mut self, token_id: u128) -> Result<()> {
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            match record.status {
+                PassportStatus::Revoked => return Err(Error::PassportRevoked),
+                PassportStatus::Archived => return Err(Error::PassportArchived),
+                PassportStatus::Draft => return Err(Error::PassportDraft),
+                _ => {}
+            }
+
+            let expires_at = match record.expires_at {
+                Some(deadline) if self.env().block_number() >= deadline => deadline,
+                _ => return Err(Error::NotExpired),
+            };
+
+            record.archived_at = Some(self.env().block_number());
+            let block_number = self.change_status(
+                &mut record,
+                PassportStatus::Archived,
+                Some(String::from("expired")),
+            );
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportExpired {
+                token_id,
+                expires_at,
+                expired_at: block_number,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        // Ownership (NFT-like).
+
+        /// Number of tokens owned by `owner`, not counting Drafts.
+        #[ink(message)]
+        pub fn balance_of(&self, owner: Address) -> u128 {
+            self.owned_tokens_count.get(owner).unwrap_or(0)
+                - self.draft_balances.get(owner).unwrap_or(0)
+        }
+
+        #[ink(message)]
+        pub fn owner_of(&self, token_id: u128) -> Option<Address> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.token_owner.get(token_id)
+        }
+
+        /// Number of existing tokens, not counting Drafts.
+        #[ink(message)]
+        pub fn total_supply(&self) -> u128 {
+            self.live_tokens - self.draft_count
+        }
+
+        /// Token at `index` in the list of all tokens (order changes on burn).
+        ///
+        /// Drafts are listed once published.
+        #[ink(message)]
+        pub fn token_by_index(&self, index: u128) -> Option<u128> {
+            self.all_tokens.get(index)
+        }
+
+        /// Token at `index` in `owner`'s tokens (order changes when one leaves).
+        ///
+        /// Drafts are listed once published.
+        #[ink(message)]
+        pub fn token_of_owner_by_index(&self, owner: Address, index: u128) -> Option<u128> {
+            self.owned_tokens.get((owner, index))
+        }
+
+        #[ink(message)]
+        pub fn collection_name(&self) -> String {
+            String::from(COLLECTION_NAME)
+        }
+
+        #[ink(message)]
+        pub fn collection_symbol(&self) -> String {
+            String::from(COLLECTION_SYMBOL)
+        }
+
+        /// Metadata URI for NFT tooling: the base URI plus the token ID when
+        /// one is set, otherwise the passport's current dataset URI.
+        ///
+        /// Returns `None` for Restricted passports the caller may not read in
+        /// full and for Drafts queried by anyone other than their issuer.
+        #[ink(message)]
+        pub fn token_uri(&self, token_id: u128) -> Option<String> {
+            if !self.can_read(token_id) {
+                return None;
+            }
+            let record = self.passports.get(token_id)?;
+            Some(match &self.token_base_uri {
+                Some(base_uri) => ink::prelude::format!("{}{}", base_uri, token_id),
+                None => record.dataset_uri,
+            })
+        }
+
+        #[ink(message)]
+        pub fn get_approved(&self, token_id: u128) -> Option<Address> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.token_approvals.get(token_id)
+        }
+
+        #[ink(message)]
+        pub fn is_approved_for_all(&self, owner: Address, operator: Address) -> bool {
+            !self.blacklist.contains(operator)
+                && self.operator_approvals.contains((owner, operator))
+                && self
+                    .operator_approval_expiry
+                    .get((owner, operator))
+                    .is_none_or(|expires_at| self.env().block_number() < expires_at)
+        }
+
+        /// Expiry block of an operator approval (`None` if indefinite or not granted).
+        #[ink(message)]
+        pub fn approval_for_all_expiry(&self, owner: Address, operator: Address) -> Option<u32> {
+            self.operator_approval_expiry.get((owner, operator))
+        }
+
+        #[ink(message)]
+        pub fn approve(&mut self, to: Address, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            let owner = self.token_owner.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if to == owner
+                || self
+                    .passports
+                    .get(token_id)
+                    .is_some_and(|r| !r.transferable)
+            {
+                return Err(Error::NotAllowed);
+            }
+            if self.blacklist.contains(to) {
+                return Err(Error::AccountBlacklisted);
+            }
+
+            if caller != owner && !self.is_approved_for_all(owner, caller) {
+                return Err(Error::NotApproved);
+            }
+            self.ensure_not_draft(token_id)?;
+
+            self.token_approvals.insert(token_id, &to);
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(Approval {
+                owner,
+                approved: to,
+                token_id,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Approve `to` for many tokens in one call, e.g. when onboarding a
+        /// fulfillment partner.
+        ///
+        /// Each token is processed like `approve`; failures are reported per
+        /// token and do not abort the batch.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty list or more than `MAX_BATCH_SIZE` tokens
+        #[ink(message)]
+        pub fn batch_approve(
+            &mut self,
+            to: Address,
+            token_ids: ink::prelude::vec::Vec<u128>,
+        ) -> Result<ink::prelude::vec::Vec<BatchItemResult>> {
+            if token_ids.is_empty() || token_ids.len() > MAX_BATCH_SIZE {
+                return Err(Error::InvalidInput);
+            }
+
+            Ok(token_ids
+                .into_iter()
+                .map(|token_id| BatchItemResult {
+                    token_id,
+                    error: self.approve(to, token_id).err(),
+                })
+                .collect())
+        }
+
+        /// Clear the token approvals of many tokens in one call (owner or operator).
+        ///
+        /// Failures are reported per token and do not abort the batch.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty list or more than `MAX_BATCH_SIZE` tokens
+        #[ink(message)]
+        pub fn batch_clear_approvals(
+            &mut self,
+            token_ids: ink::prelude::vec::Vec<u128>,
+        ) -> Result<ink::prelude::vec::Vec<BatchItemResult>> {
+            if token_ids.is_empty() || token_ids.len() > MAX_BATCH_SIZE {
+                return Err(Error::InvalidInput);
+            }
+
+            let caller = self.env().caller();
+            Ok(token_ids
+                .into_iter()
+                .map(|token_id| {
+                    let error = match self.token_owner.get(token_id) {
+                        None => Some(Error::TokenNotFound),
+                        Some(owner)
+                            if caller != owner && !self.is_approved_for_all(owner, caller) =>
+                        {
+                            Some(Error::NotApproved)
+                        }
+                        Some(owner) => {
+                            self.revoke_approval(owner, token_id);
+                            None
+                        }
+                    };
+                    BatchItemResult { token_id, error }
+                })
+                .collect())
+        }
+
+        #[ink(message)]
+        pub fn set_approval_for_all(&mut self, operator: Address, approved: bool) -> Result<()> {
+            let caller = self.env().caller();
+
+            if operator == caller {
+                return Err(Error::NotAllowed);
+            }
+            if approved && self.blacklist.contains(operator) {
+                return Err(Error::AccountBlacklisted);
+            }
+
+            if approved {
+                self.operator_approvals.insert((caller, operator), &());
+            } else {
+                self.operator_approvals.remove((caller, operator));
+            }
+            self.operator_approval_expiry.remove((caller, operator));
+
+            self.env().emit_event(ApprovalForAll {
+                owner: caller,
+                operator,
+                approved,
+            });
+
+            Ok(())
+        }
+
+        /// Approve an operator for all of the caller's tokens until `expires_at`.
+        ///
+        /// The approval stops applying at block `expires_at`, e.g. at the end of
+        /// a shipment. `set_approval_for_all` replaces it with an indefinite
+        /// approval or removes it.
+        #[ink(message)]
+        pub fn set_approval_for_all_until(
+            &mut self,
+            operator: Address,
+            expires_at: u32,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+
+            if operator == caller {
+                return Err(Error::NotAllowed);
+            }
+            if self.blacklist.contains(operator) {
+                return Err(Error::AccountBlacklisted);
+            }
+
+            if expires_at <= self.env().block_number() {
+                return Err(Error::InvalidInput);
+            }
+
+            self.operator_approvals.insert((caller, operator), &());
+            self.operator_approval_expiry
+                .insert((caller, operator), &expires_at);
+
+            self.env().emit_event(ApprovalForAllUntil {
+                owner: caller,
+                operator,
+                expires_at,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn transfer(&mut self, to: Address, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            self.transfer_token_from(&caller, &to, token_id)
+        }
+
+        /// `transfer` that links the handover to an off-chain document (e.g. a
+        /// shipping note) by emitting its hash in `TransferMemo`. Nothing extra
+        /// is stored.
+        #[ink(message)]
+        pub fn transfer_with_data(
+            &mut self,
+            to: Address,
+            token_id: u128,
+            memo_hash: [u8; 32],
+        ) -> Result<()> {
+            self.transfer(to, token_id)?;
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(TransferMemo {
+                token_id,
+                memo_hash,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn transfer_from(&mut self, from: Address, to: Address, token_id: u128) -> Result<()> {
+            self.transfer_token_from(&from, &to, token_id)
+        }
+
+        /// `transfer_from` that refuses to strand the token in a contract.
+        ///
+        /// When `to` is a contract, its `on_dpp_received` hook is called with
+        /// `data` after the transfer; the call reverts unless the hook returns
+        /// `ON_DPP_RECEIVED_SELECTOR`.
+        ///
+        /// # Errors
+        ///
+        /// * `ReceiverRejected` - Hook missing, failed, or returned another value
+        #[ink(message)]
+        pub fn safe_transfer_from(
+            &mut self,
+            from: Address,
+            to: Address,
+            token_id: u128,
+            data: ink::prelude::vec::Vec<u8>,
+        ) -> Result<()> {
+            self.transfer_token_from(&from, &to, token_id)?;
+
+            if self.env().is_contract(&to) {
+                self.check_on_dpp_received(to, from, token_id, data)?;
+            }
+
+            Ok(())
+        }
+
+        /// Temporarily disable transfers of a token (owner or issuer, see
+        /// `lock_authority`).
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized` - Caller is not the lock authority for the token
+        /// * `TokenLocked` - Already locked
+        /// * `NotAllowed` - Soulbound token (permanently locked)
+        #[ink(message)]
+        pub fn lock(&mut self, token_id: u128) -> Result<()> {
+            let record = self.ensure_lock_authority(token_id)?;
+            if !record.transferable {
+                return Err(Error::NotAllowed);
+            }
+            if self.locked.insert(token_id, &()).is_some() {
+                return Err(Error::TokenLocked);
+            }
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(Locked {
+                token_id,
+                by: self.env().caller(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Re-enable transfers of a locked token (same authority as `lock`).
+        #[ink(message)]
+        pub fn unlock(&mut self, token_id: u128) -> Result<()> {
+            self.ensure_lock_authority(token_id)?;
+            if self.locked.take(token_id).is_none() {
+                return Err(Error::NotAllowed);
+            }
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(Unlocked {
+                token_id,
+                by: self.env().caller(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Offer a token to `to`, who must `accept_transfer` it (owner-only).
+        ///
+        /// Guards high-value passports against transfers to a wrong or unowned
+        /// address. A new offer replaces any pending one; the offer lapses when
+        /// the token changes owner.
+        ///
+        /// # Errors
+        ///
+        /// * `NotOwner` - Caller does not own the token
+        /// * `InvalidInput` - `to` is the caller
+        /// * `NotAllowed` - Soulbound token
+        #[ink(message)]
+        pub fn offer_transfer(&mut self, to: Address, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if self.token_owner.get(token_id) != Some(caller) {
+                return Err(Error::NotOwner);
+            }
+            self.ensure_not_draft(token_id)?;
+            if to == caller {
+                return Err(Error::InvalidInput);
+            }
+            if !record.transferable {
+                return Err(Error::NotAllowed);
+            }
+
+            self.pending_transfers.insert(token_id, &to);
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(TransferOffered {
+                token_id,
+                owner: caller,
+                to,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw a pending transfer offer (owner-only).
+        #[ink(message)]
+        pub fn cancel_transfer_offer(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            if self.token_owner.get(token_id) != Some(caller) {
+                return Err(Error::NotOwner);
+            }
+            let to = self
+                .pending_transfers
+                .take(token_id)
+                .ok_or(Error::NotAllowed)?;
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(TransferOfferCancelled {
+                token_id,
+                owner: caller,
+                to,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Accept a pending transfer offer (offered account only).
+        ///
+        /// The same lifecycle, lock, cooldown, and terms checks as `transfer` apply.
+        #[ink(message)]
+        pub fn accept_transfer(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            if self.pending_transfers.get(token_id) != Some(caller) {
+                return Err(Error::Unauthorized);
+            }
+            let owner = self.token_owner.get(token_id).ok_or(Error::TokenNotFound)?;
+            self.move_checked(&owner, &caller, token_id, false)
+        }
+
+        /// Get the account a token has been offered to, if any.
+        #[ink(message)]
+        pub fn pending_transfer(&self, token_id: u128) -> Option<Address> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.pending_transfers.get(token_id)
+        }
+
+        /// Whether a token cannot be transferred because it is locked or soulbound
+        /// (ERC-5192 `locked`).
+        #[ink(message)]
+        pub fn is_locked(&self, token_id: u128) -> bool {
+            if self.is_hidden_draft(token_id) {
+                return false;
+            }
+            self.locked.contains(token_id)
+                || self
+                    .passports
+                    .get(token_id)
+                    .is_some_and(|r| !r.transferable)
+        }
+
+        // Session keys
+
+        /// Register a session key for a constrained device (issuer = caller).
+        ///
+        /// The key may update datasets of the caller's tokens in
+        /// `first_token_id..=last_token_id` until block `expires_at`.
+        /// Re-registering an existing key of the same issuer replaces its scope.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty token range or expiry not in the future
+        /// * `NotAllowed` - Key is the caller or belongs to another issuer
+        #[ink(message)]
+        pub fn add_session_key(
+            &mut self,
+            session_key: Address,
+            first_token_id: u128,
+            last_token_id: u128,
+            expires_at: u32,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+
+            if session_key == caller {
+                return Err(Error::NotAllowed);
+            }
+
+            if first_token_id > last_token_id || expires_at <= self.env().block_number() {
+                return Err(Error::InvalidInput);
+            }
+
+            if let Some(existing) = self.session_keys.get(session_key) {
+                if existing.issuer != caller {
+                    return Err(Error::NotAllowed);
+                }
+            }
+
+            self.session_keys.insert(
+                session_key,
+                &SessionKey {
+                    issuer: caller,
+                    first_token_id,
+                    last_token_id,
+                    expires_at,
+                },
+            );
+
+            self.env().emit_event(SessionKeyAdded {
+                issuer: caller,
+                session_key,
+                first_token_id,
+                last_token_id,
+                expires_at,
+            });
+
+            Ok(())
+        }
+
+        /// Revoke a session key (only the issuer that registered it).
+        #[ink(message)]
+        pub fn revoke_session_key(&mut self, session_key: Address) -> Result<()> {
+            let caller = self.env().caller();
+            let existing = self
+                .session_keys
+                .get(session_key)
+                .ok_or(Error::NotAllowed)?;
+
+            if existing.issuer != caller {
+                return Err(Error::Unauthorized);
+            }
+
+            self.session_keys.remove(session_key);
+            self.env().emit_event(SessionKeyRevoked {
+                issuer: caller,
+                session_key,
+            });
+
+            Ok(())
+        }
+
+        /// Get the scope of a session key (including expired ones not yet revoked).
+        #[ink(message)]
+        pub fn get_session_key(&self, session_key: Address) -> Option<SessionKey> {
+            self.session_keys.get(session_key)
+        }
+
+        /// Let `delegate` update one token's dataset (issuer-only).
+        ///
+        /// Unlike session keys, delegations do not expire and are scoped to a
+        /// single token; the issuer remains the token's authority.
+        #[ink(message)]
+        pub fn add_update_delegate(&mut self, token_id: u128, delegate: Address) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            self.update_delegates.insert((token_id, delegate), &caller);
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(UpdateDelegateAdded {
+                token_id,
+                delegate,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw a per-token update delegation (issuer-only).
+        #[ink(message)]
+        pub fn remove_update_delegate(&mut self, token_id: u128, delegate: Address) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            if self.update_delegates.take((token_id, delegate)).is_none() {
+                return Err(Error::NotAllowed);
+            }
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(UpdateDelegateRemoved {
+                token_id,
+                delegate,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Whether `account` is an update delegate for a token.
+        #[ink(message)]
+        pub fn is_update_delegate(&self, token_id: u128, account: Address) -> bool {
+            if self.is_hidden_draft(token_id) {
+                return false;
+            }
+            self.passports.get(token_id).is_some_and(|record| {
+                self.update_delegates.get((token_id, account))
+                    == Some(self.controller_of(record.issuer))
+            })
+        }
+
+        /// Add a co-issuer to a token (issuer or existing co-issuer).
+        ///
+        /// Co-issuers (e.g. a brand owner next to the manufacturer) may update
+        /// and revoke the passport and manage co-issuers. Other issuer rights
+        /// stay with the primary issuer.
+        ///
+        /// # Errors
+        ///
+        /// * `NotAllowed` - Already an issuer of the token or `MAX_CO_ISSUERS` reached
+        #[ink(message)]
+        pub fn add_co_issuer(&mut self, token_id: u128, co_issuer: Address) -> Result<()> {
+            let caller = self.env().caller();
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.is_issuer(&record, caller) && !self.is_co_issuer(token_id, caller) {
+                return Err(Error::Unauthorized);
+            }
+            self.ensure_not_draft(token_id)?;
+
+            let mut co_issuers = self.co_issuers.get(token_id).unwrap_or_default();
+            if co_issuers.len() >= MAX_CO_ISSUERS
+                || co_issuers.contains(&co_issuer)
+                || self.is_issuer(&record, co_issuer)
+            {
+                return Err(Error::NotAllowed);
+            }
+            co_issuers.push(co_issuer);
+            self.co_issuers.insert(token_id, &co_issuers);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(CoIssuerAdded {
+                token_id,
+                co_issuer,
+                added_by: caller,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Remove a co-issuer from a token (issuer or existing co-issuer).
+        #[ink(message)]
+        pub fn remove_co_issuer(&mut self, token_id: u128, co_issuer: Address) -> Result<()> {
+            let caller = self.env().caller();
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.is_issuer(&record, caller) && !self.is_co_issuer(token_id, caller) {
+                return Err(Error::Unauthorized);
+            }
+            self.ensure_not_draft(token_id)?;
+
+            let mut co_issuers = self.co_issuers.get(token_id).unwrap_or_default();
+            let index = co_issuers
+                .iter()
+                .position(|a| *a == co_issuer)
+                .ok_or(Error::NotAllowed)?;
+            co_issuers.remove(index);
+
+            if co_issuers.is_empty() {
+                self.co_issuers.remove(token_id);
+            } else {
+                self.co_issuers.insert(token_id, &co_issuers);
+            }
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(CoIssuerRemoved {
+                token_id,
+                co_issuer,
+                removed_by: caller,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the co-issuers of a token (excluding the primary issuer).
+        #[ink(message)]
+        pub fn get_co_issuers(&self, token_id: u128) -> ink::prelude::vec::Vec<Address> {
+            if self.is_hidden_draft(token_id) {
+                return ink::prelude::vec::Vec::new();
+            }
+            self.co_issuers.get(token_id).unwrap_or_default()
+        }
+
+        /// Enable or disable strict URI validation (admin-only).
+        ///
+        /// When enabled, `ipfs://` dataset URIs must carry a syntactically valid
+        /// CIDv0/CIDv1, otherwise registration and updates fail with `InvalidUri`.
+        #[ink(message)]
+        pub fn set_strict_uri_mode(&mut self, enabled: bool) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetStrictUriMode(enabled))
+        }
+
+        /// Whether strict URI validation is enabled.
+        #[ink(message)]
+        pub fn strict_uri_mode(&self) -> bool {
+            self.strict_uri_mode
+        }
+
+        /// Set or clear the base URI `token_uri` appends token IDs to (admin-only).
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty base URI (use `None` to clear)
+        #[ink(message)]
+        pub fn set_token_base_uri(&mut self, base_uri: Option<String>) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetTokenBaseUri(base_uri))
+        }
+
+        /// Get the base URI `token_uri` appends token IDs to, if set.
+        #[ink(message)]
+        pub fn token_base_uri(&self) -> Option<String> {
+            self.token_base_uri.clone()
+        }
+
+        /// Enable or disable the trusted issuer allowlist (admin-only).
+        ///
+        /// When enabled, only trusted issuers may register passports; others
+        /// fail with `NotTrustedIssuer`. Existing passports are unaffected.
+        #[ink(message)]
+        pub fn set_allowlist_mode(&mut self, enabled: bool) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetAllowlistMode(enabled))
+        }
+
+        /// Whether registration is restricted to trusted issuers.
+        #[ink(message)]
+        pub fn allowlist_mode(&self) -> bool {
+            self.allowlist_mode
+        }
+
+        /// Add an account to the trusted issuer allowlist (admin-only).
+        #[ink(message)]
+        pub fn add_trusted_issuer(&mut self, issuer: Address) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::AddTrustedIssuer(issuer))
+        }
+
+        /// Remove an account from the trusted issuer allowlist (admin-only).
+        #[ink(message)]
+        pub fn remove_trusted_issuer(&mut self, issuer: Address) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::RemoveTrustedIssuer(issuer))
+        }
+
+        /// Whether an account is on the trusted issuer allowlist.
+        #[ink(message)]
+        pub fn is_trusted_issuer(&self, issuer: Address) -> bool {
+            self.trusted_issuers.contains(issuer)
+        }
+
+        /// Blacklist a sanctioned or compromised account (admin-only).
+        ///
+        /// A blacklisted account cannot register passports or receive tokens,
+        /// and cannot be approved; approvals it already holds stop applying.
+        /// Tokens it already owns stay with it.
+        #[ink(message)]
+        pub fn blacklist_account(&mut self, account: Address) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::BlacklistAccount(account))
+        }
+
+        /// Remove an account from the blacklist (admin-only).
+        #[ink(message)]
+        pub fn unblacklist_account(&mut self, account: Address) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::UnblacklistAccount(account))
+        }
+
+        /// Whether an account is blacklisted.
+        #[ink(message)]
+        pub fn is_blacklisted(&self, account: Address) -> bool {
+            self.blacklist.contains(account)
+        }
+
+        /// Enable or disable the contract-wide recipient allowlist (admin-only).
+        ///
+        /// When enabled, transfers to accounts not on the list fail with
+        /// `RecipientNotAllowed`. Issuers can keep their own list as well
+        /// (`set_issuer_recipient_allowlist`); both apply.
+        #[ink(message)]
+        pub fn set_recipient_allowlist(&mut self, enabled: bool) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetRecipientAllowlist(enabled))
+        }
+
+        /// Add or remove a recipient on the contract-wide allowlist (admin-only).
+        #[ink(message)]
+        pub fn set_allowed_recipient(&mut self, recipient: Address, allowed: bool) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetAllowedRecipient(recipient, allowed))
+        }
+
+        /// Enable or disable the recipient allowlist for an issuer identity's tokens.
+        ///
+        /// The list belongs to the identity and is managed by its controlling
+        /// key, so it survives `rotate_issuer_key`.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized` - Caller does not control `issuer`
+        #[ink(message)]
+        pub fn set_issuer_recipient_allowlist(
+            &mut self,
+            issuer: Address,
+            enabled: bool,
+        ) -> Result<()> {
+            self.ensure_controller(issuer)?;
+            self.set_recipient_allowlist_for(Some(issuer), enabled);
+            Ok(())
+        }
+
+        /// Add or remove a recipient on an issuer identity's allowlist.
+        ///
+        /// # Errors
+        ///
+        /// * `Unauthorized` - Caller does not control `issuer`
+        #[ink(message)]
+        pub fn set_issuer_allowed_recipient(
+            &mut self,
+            issuer: Address,
+            recipient: Address,
+            allowed: bool,
+        ) -> Result<()> {
+            self.ensure_controller(issuer)?;
+            self.set_allowed_recipient_for(Some(issuer), recipient, allowed);
+            Ok(())
+        }
+
+        /// Whether `recipient` passes the recipient allowlists that apply to a token.
+        #[ink(message)]
+        pub fn is_recipient_allowed(&self, token_id: u128, recipient: Address) -> bool {
+            if self.is_hidden_draft(token_id) {
+                return false;
+            }
+            self.passports
+                .get(token_id)
+                .is_some_and(|record| self.recipient_allowed(record.issuer, recipient))
+        }
+
+        /// Set or clear the external issuer registry contract (admin-only).
+        ///
+        /// When set, registration calls `is_registered(issuer)` on the registry
+        /// and fails with `NotTrustedIssuer` if it returns `false`. Applies in
+        /// addition to the built-in allowlist.
+        #[ink(message)]
+        pub fn set_issuer_registry(&mut self, registry: Option<Address>) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetIssuerRegistry(registry))
+        }
+
+        /// Get the configured external issuer registry contract.
+        #[ink(message)]
+        pub fn issuer_registry(&self) -> Option<Address> {
+            self.issuer_registry
+        }
+
+        /// Configure which operations a Suspended passport blocks (admin-only).
+        #[ink(message)]
+        pub fn set_status_policy(&mut self, policy: StatusPolicy) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetStatusPolicy(policy))
+        }
+
+        /// Current Suspended status policy.
+        #[ink(message)]
+        pub fn status_policy(&self) -> StatusPolicy {
+            self.status_policy.clone()
+        }
+
+        /// Configure whether token owners or issuers may lock transfers (admin-only).
+        #[ink(message)]
+        pub fn set_lock_authority(&mut self, authority: LockAuthority) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetLockAuthority(authority))
+        }
+
+        /// Who may lock and unlock token transfers.
+        #[ink(message)]
+        pub fn lock_authority(&self) -> LockAuthority {
+            self.lock_authority
+        }
+
+        /// Enable or disable verbose anchor events (admin-only).
+        ///
+        /// When disabled, `PassportRegistered` and `PassportUpdated` are no longer
+        /// emitted; the compact `AnchorDigest` event is always emitted.
+        #[ink(message)]
+        pub fn set_verbose_events(&mut self, enabled: bool) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetVerboseEvents(enabled))
+        }
+
+        /// Whether verbose anchor events are emitted.
+        #[ink(message)]
+        pub fn verbose_events(&self) -> bool {
+            self.verbose_events
+        }
+
+        // Data-availability fund
+
+        /// Set the fee charged on `update_dataset` (admin-only, 0 = free).
+        #[ink(message)]
+        pub fn set_update_fee(&mut self, update_fee: U256) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetUpdateFee(update_fee))
+        }
+
+        /// Get the fee charged on `update_dataset`.
+        #[ink(message)]
+        pub fn update_fee(&self) -> U256 {
+            self.update_fee
+        }
+
+        /// Set the registration fee for one granularity level (admin-only, 0 = free).
+        ///
+        /// Item-level anchors are high-volume/low-value, so each level can be priced separately.
+        #[ink(message)]
+        pub fn set_registration_fee(&mut self, granularity: Granularity, fee: U256) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetRegistrationFee(granularity, fee))
+        }
+
+        /// Get the registration fee for a granularity level.
+        #[ink(message)]
+        pub fn fee_for(&self, granularity: Granularity) -> U256 {
+            match granularity {
+                Granularity::ProductClass => self.registration_fees.product_class,
+                Granularity::Batch => self.registration_fees.batch,
+                Granularity::Item => self.registration_fees.item,
+            }
+        }
+
+        /// Set the chain's storage deposit per byte used by quotes (admin-only).
+        ///
+        /// Mirrors the runtime's deposit rate so quotes can include the deposit;
+        /// it does not change what the chain charges.
+        #[ink(message)]
+        pub fn set_deposit_per_byte(&mut self, deposit_per_byte: U256) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetDepositPerByte(deposit_per_byte))
+        }
+
+        /// Get the storage deposit per byte used by quotes.
+        #[ink(message)]
+        pub fn deposit_per_byte(&self) -> U256 {
+            self.deposit_per_byte
+        }
+
+        /// Quote the cost of a `register_passport` call with these arguments.
+        ///
+        /// Returns the registration fee plus the estimated storage deposit for
+        /// the anchor record and its first version entry. Fixed-size bookkeeping
+        /// entries are not included in the deposit estimate.
+        #[ink(message)]
+        pub fn quote_registration(
+            &self,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            granularity: Granularity,
+            subject_id_hash: Option<[u8; 32]>,
+        ) -> U256 {
+            let caller = self.env().caller();
+            let fee = self.fee_for(granularity.clone());
+            let record = PassportRecord {
+                token_id: self.next_token_id,
+                issuer: caller,
+                dataset_uri: dataset_uri.clone(),
+                payload_hash,
+                dataset_type: dataset_type.clone(),
+                version: 1,
+                status: PassportStatus::Active,
+                created_at: 0,
+                updated_at: 0,
+                granularity,
+                subject_id_hash,
+                archived_at: None,
+                expires_at: None,
+                transferable: true,
+            };
+            let entry = VersionHistory {
+                version: 1,
+                dataset_uri,
+                payload_hash,
+                dataset_type,
+                updated_at: 0,
+                updated_by: caller,
+                diff_uri: None,
+                diff_hash: None,
+                issuer: caller,
+            };
+            let bytes = record.encoded_size() + entry.encoded_size();
+            fee.saturating_add(self.deposit_for(bytes))
+        }
+
+        /// Quote the cost of an `update_dataset` call with these arguments.
+        ///
+        /// Returns the update fee plus the estimated storage deposit for the new
+        /// version entry and any growth of the anchor record.
+        #[ink(message)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn quote_update(
+            &self,
+            token_id: u128,
+            dataset_uri: String,
+            payload_hash: [u8; 32],
+            dataset_type: String,
+            subject_id_hash: Option<[u8; 32]>,
+            diff_uri: Option<String>,
+            diff_hash: Option<[u8; 32]>,
+        ) -> Result<U256> {
+            if self.is_hidden_draft(token_id) {
+                return Err(Error::TokenNotFound);
+            }
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+            let old_size = record.encoded_size();
+
+            let entry = VersionHistory {
+                version: record.version + 1,
+                dataset_uri: dataset_uri.clone(),
+                payload_hash,
+                dataset_type: dataset_type.clone(),
+                updated_at: 0,
+                updated_by: self.env().caller(),
+                diff_uri,
+                diff_hash,
+                issuer: record.issuer,
+            };
+            let updated = PassportRecord {
+                dataset_uri,
+                payload_hash,
+                dataset_type,
+                subject_id_hash,
+                ..record
+            };
+            let bytes = entry.encoded_size() + updated.encoded_size().saturating_sub(old_size);
+            Ok(self.update_fee.saturating_add(self.deposit_for(bytes)))
+        }
+
+        /// Set the address receiving fund withdrawals (admin-only).
+        #[ink(message)]
+        pub fn set_fund_address(&mut self, fund_address: Address) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetFundAddress(fund_address))
+        }
+
+        /// Get the address receiving fund withdrawals.
+        #[ink(message)]
+        pub fn fund_address(&self) -> Address {
+            self.fund_address
+        }
+
+        /// Get the accrued, not yet withdrawn fund balance.
+        #[ink(message)]
+        pub fn fund_balance(&self) -> U256 {
+            self.fund_balance
+        }
+
+        /// Withdraw `amount` from the fund to the fund address (admin-only).
+        #[ink(message)]
+        pub fn withdraw_fund(&mut self, amount: U256) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::WithdrawFund(amount))
+        }
+
+        // Subject pre-claims
+
+        /// Reserve a subject hash so that only the caller can register it until
+        /// the window lapses (payable).
+        ///
+        /// At least `pre_claim_bond` must be transferred. The bond is refunded
+        /// when the claimant registers the subject within the window and
+        /// forfeited to the data-availability fund otherwise.
+        ///
+        /// # Errors
+        ///
+        /// * `NotAllowed` - Subject already registered or reserved by an active claim
+        /// * `InsufficientFee` - Transferred value below `pre_claim_bond`
+        #[ink(message, payable)]
+        pub fn pre_claim(&mut self, subject_hash: [u8; 32]) -> Result<()> {
+            let caller = self.env().caller();
+            let block_number = self.env().block_number();
+
+            if self.subject_id_to_token.contains(subject_hash) {
+                return Err(Error::NotAllowed);
+            }
+
+            if let Some(existing) = self.pre_claims.get(subject_hash) {
+                if block_number < existing.expires_at {
+                    return Err(Error::NotAllowed);
+                }
+                self.fund_balance = self.fund_balance.saturating_add(existing.bond);
+            }
+
+            let bond = self.env().transferred_value();
+            if bond < self.pre_claim_bond {
+                return Err(Error::InsufficientFee);
+            }
+
+            let expires_at = block_number.saturating_add(self.pre_claim_window);
+            self.pre_claims.insert(
+                subject_hash,
+                &PreClaim {
+                    claimant: caller,
+                    bond,
+                    expires_at,
+                },
+            );
+
+            self.env().emit_event(SubjectPreClaimed {
+                subject_hash,
+                claimant: caller,
+                bond,
+                expires_at,
+            });
+
+            Ok(())
+        }
+
+        /// Get the pre-claim recorded for a subject hash (may have lapsed).
+        #[ink(message)]
+        pub fn get_pre_claim(&self, subject_hash: [u8; 32]) -> Option<PreClaim> {
+            self.pre_claims.get(subject_hash)
+        }
+
+        /// Set the pre-claim bond and reservation window (admin-only).
+        #[ink(message)]
+        pub fn set_pre_claim_terms(&mut self, bond: U256, window: u32) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetPreClaimTerms(bond, window))
+        }
+
+        /// Get the pre-claim bond and reservation window.
+        #[ink(message)]
+        pub fn pre_claim_terms(&self) -> (U256, u32) {
+            (self.pre_claim_bond, self.pre_claim_window)
+        }
+
+        // Resolver endpoints directory
+
+        /// Publish a resolver endpoint for one token (`Some`, issuer-only) or
+        /// issuer-wide for the caller (`None`).
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty or oversized public key
+        #[ink(message)]
+        pub fn set_resolver_endpoint(
+            &mut self,
+            token_id: Option<u128>,
+            url_hash: [u8; 32],
+            public_key: ink::prelude::vec::Vec<u8>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+
+            if public_key.is_empty() || public_key.len() > MAX_PUBLIC_KEY_LEN {
+                return Err(Error::InvalidInput);
+            }
+
+            let endpoint = ResolverEndpoint {
+                url_hash,
+                public_key: public_key.clone(),
+                updated_at: self.env().block_number(),
+            };
+
+            match token_id {
+                Some(id) => {
+                    self.ensure_issuer(id, caller)?;
+                    self.token_resolvers.insert(id, &endpoint);
+                }
+                None => {
+                    self.issuer_resolvers.insert(caller, &endpoint);
+                }
+            }
+
+            let event_seq = token_id.map(|id| self.next_event_seq(id));
+            self.env().emit_event(ResolverEndpointSet {
+                issuer: caller,
+                token_id,
+                url_hash,
+                public_key,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Remove a per-token (issuer-only) or the caller's issuer-wide resolver endpoint.
+        #[ink(message)]
+        pub fn clear_resolver_endpoint(&mut self, token_id: Option<u128>) -> Result<()> {
+            let caller = self.env().caller();
+
+            match token_id {
+                Some(id) => {
+                    self.ensure_issuer(id, caller)?;
+                    self.token_resolvers.remove(id);
+                }
+                None => self.issuer_resolvers.remove(caller),
+            }
+
+            let event_seq = token_id.map(|id| self.next_event_seq(id));
+            self.env().emit_event(ResolverEndpointCleared {
+                issuer: caller,
+                token_id,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the resolver endpoint for a token: the per-token entry if set,
+        /// otherwise the issuer-wide one.
+        #[ink(message)]
+        pub fn get_resolver_endpoint(&self, token_id: u128) -> Option<ResolverEndpoint> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            if let Some(endpoint) = self.token_resolvers.get(token_id) {
+                return Some(endpoint);
+            }
+            let record = self.passports.get(token_id)?;
+            self.issuer_resolvers.get(record.issuer)
+        }
+
+        /// Get an issuer's issuer-wide resolver endpoint.
+        #[ink(message)]
+        pub fn get_issuer_resolver_endpoint(&self, issuer: Address) -> Option<ResolverEndpoint> {
+            self.issuer_resolvers.get(issuer)
+        }
+
+        // Cross-registry federation
+
+        /// Record that the same product is anchored in another registry (issuer-only).
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty or oversized registry code
+        /// * `NotAllowed` - Reference already recorded or `MAX_FEDERATED_REFS` reached
+        #[ink(message)]
+        pub fn add_federated_ref(
+            &mut self,
+            token_id: u128,
+            registry_code: String,
+            remote_ref_hash: [u8; 32],
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            if registry_code.is_empty() || registry_code.len() > MAX_REGISTRY_CODE_LEN {
+                return Err(Error::InvalidInput);
+            }
+
+            let mut refs = self.federated_refs.get(token_id).unwrap_or_default();
+            if refs.len() >= MAX_FEDERATED_REFS
+                || refs.iter().any(|r| {
+                    r.registry_code == registry_code && r.remote_ref_hash == remote_ref_hash
+                })
+            {
+                return Err(Error::NotAllowed);
+            }
+
+            refs.push(FederatedRef {
+                registry_code: registry_code.clone(),
+                remote_ref_hash,
+                added_at: self.env().block_number(),
+            });
+            self.federated_refs.insert(token_id, &refs);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(FederatedRefAdded {
+                token_id,
+                registry_code,
+                remote_ref_hash,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Remove a federated reference (issuer-only).
+        #[ink(message)]
+        pub fn remove_federated_ref(
+            &mut self,
+            token_id: u128,
+            registry_code: String,
+            remote_ref_hash: [u8; 32],
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            self.ensure_issuer(token_id, caller)?;
+
+            let mut refs = self.federated_refs.get(token_id).unwrap_or_default();
+            let index = refs
+                .iter()
+                .position(|r| {
+                    r.registry_code == registry_code && r.remote_ref_hash == remote_ref_hash
+                })
+                .ok_or(Error::NotAllowed)?;
+            refs.remove(index);
+
+            if refs.is_empty() {
+                self.federated_refs.remove(token_id);
+            } else {
+                self.federated_refs.insert(token_id, &refs);
+            }
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(FederatedRefRemoved {
+                token_id,
+                registry_code,
+                remote_ref_hash,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Get the federated references of a token (in insertion order).
+        #[ink(message)]
+        pub fn get_federated_refs(&self, token_id: u128) -> ink::prelude::vec::Vec<FederatedRef> {
+            if self.is_hidden_draft(token_id) {
+                return ink::prelude::vec::Vec::new();
+            }
+            self.federated_refs.get(token_id).unwrap_or_default()
+        }
+
+        // Issuer onboarding
+
+        /// Request issuer onboarding for the caller.
+        ///
+        /// Allowed when the caller has no record yet or was previously rejected.
+        ///
+        /// # Errors
+        ///
+        /// * `InvalidInput` - Empty evidence_uri
+        /// * `NotAllowed` - A request is already pending or approved
+        #[ink(message)]
+        pub fn request_issuer_onboarding(
+            &mut self,
+            did_hash: [u8; 32],
+            evidence_uri: String,
+            evidence_hash: [u8; 32],
+        ) -> Result<()> {
+            let caller = self.env().caller();
+
+            if evidence_uri.is_empty() {
+                return Err(Error::InvalidInput);
+            }
+
+            if let Some(existing) = self.onboarding.get(caller) {
+                if existing.status != OnboardingStatus::Rejected {
+                    return Err(Error::NotAllowed);
+                }
+            }
+
+            let record = OnboardingRecord {
+                did_hash,
+                evidence_uri: evidence_uri.clone(),
+                evidence_hash,
+                status: OnboardingStatus::Pending,
+                requested_at: self.env().block_number(),
+                decided_at: None,
+            };
+            self.onboarding.insert(caller, &record);
+
+            self.env().emit_event(IssuerOnboardingRequested {
+                account: caller,
+                did_hash,
+                evidence_uri,
+                evidence_hash,
+            });
+
+            Ok(())
+        }
+
+        /// Approve a pending onboarding request (Registrar-only).
+        ///
+        /// The account is added to the trusted issuer allowlist.
+        #[ink(message)]
+        pub fn approve_issuer(&mut self, account: Address) -> Result<()> {
+            self.ensure_role(Role::Registrar)?;
+            self.decide_onboarding(account, OnboardingStatus::Approved)?;
+            self.trusted_issuers.insert(account, &());
+            self.env()
+                .emit_event(TrustedIssuerAdded { issuer: account });
+            self.env().emit_event(IssuerApproved {
+                account,
+                approved_by: self.env().caller(),
+            });
+            Ok(())
+        }
+
+        /// Reject a pending onboarding request (Registrar-only).
+        ///
+        /// The account is removed from the trusted issuer allowlist if present.
+        #[ink(message)]
+        pub fn reject_issuer(&mut self, account: Address, reason: Option<String>) -> Result<()> {
+            self.ensure_role(Role::Registrar)?;
+            self.decide_onboarding(account, OnboardingStatus::Rejected)?;
+            if self.trusted_issuers.take(account).is_some() {
+                self.env()
+                    .emit_event(TrustedIssuerRemoved { issuer: account });
+            }
+            self.env().emit_event(IssuerRejected {
+                account,
+                rejected_by: self.env().caller(),
+                reason,
+            });
+            Ok(())
+        }
+
+        /// Get the onboarding record of an account.
+        #[ink(message)]
+        pub fn get_onboarding(&self, account: Address) -> Option<OnboardingRecord> {
+            self.onboarding.get(account)
+        }
+
+        /// Anchor the caller's branding document (replaces any previous one).
+        ///
+        /// The profile is self-declared; apps should show it together with the
+        /// issuer's onboarding status.
+        #[ink(message)]
+        pub fn set_issuer_profile(&mut self, uri: String, hash: [u8; 32]) -> Result<()> {
+            let caller = self.env().caller();
+
+            if uri.is_empty() {
+                return Err(Error::InvalidInput);
+            }
+
+            if self.strict_uri_mode {
+                validate_ipfs_uri(&uri)?;
+            }
+
+            let profile = IssuerProfile {
+                uri: uri.clone(),
+                hash,
+                updated_at: self.env().block_number(),
+            };
+            self.issuer_profiles.insert(caller, &profile);
+
+            self.env().emit_event(IssuerProfileSet {
+                issuer: caller,
+                uri,
+                hash,
+            });
+
+            Ok(())
+        }
+
+        /// Get an issuer's branding document.
+        #[ink(message)]
+        pub fn get_issuer_profile(&self, issuer: Address) -> Option<IssuerProfile> {
+            self.issuer_profiles.get(issuer)
+        }
+
+        // Reporting counters
+
+        /// Set the reporting epoch length in blocks (admin-only).
+        ///
+        /// Only allowed before the first registration, so existing buckets
+        /// never mix different epoch lengths.
+        #[ink(message)]
+        pub fn set_epoch_length(&mut self, epoch_length: u32) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetEpochLength(epoch_length))
+        }
+
+        /// Get the reporting epoch length in blocks.
+        #[ink(message)]
+        pub fn epoch_length(&self) -> u32 {
+            self.epoch_length
+        }
+
+        /// Get the counters of a single epoch (`block_number / epoch_length`).
+        #[ink(message)]
+        pub fn get_epoch_counters(&self, epoch: u32) -> EpochCounters {
+            self.epoch_counters.get(epoch).unwrap_or_default()
+        }
+
+        /// Sum counters of all epochs overlapping `from_block..=to_block`.
+        ///
+        /// Resolution is one epoch: the first and last epochs are counted in full.
+        /// Intended for dry-run queries (e.g. "passports issued this quarter").
+        #[ink(message)]
+        pub fn counters_between(&self, from_block: u32, to_block: u32) -> EpochCounters {
+            let mut total = EpochCounters::default();
+
+            if from_block > to_block {
+                return total;
+            }
+
+            for epoch in (from_block / self.epoch_length)..=(to_block / self.epoch_length) {
+                if let Some(c) = self.epoch_counters.get(epoch) {
+                    total.registered += c.registered;
+                    total.updated += c.updated;
+                    total.revoked += c.revoked;
+                }
+            }
+
+            total
+        }
+
+        /// Number of passports registered in epochs overlapping `from_block..=to_block`.
+        #[ink(message)]
+        pub fn registered_between(&self, from_block: u32, to_block: u32) -> u128 {
+            self.counters_between(from_block, to_block).registered
+        }
+
+        // Owner recovery
+
+        /// Designate a recovery account (e.g. a social-recovery wallet contract)
+        /// allowed to reclaim the caller's tokens after a challenge period.
+        #[ink(message)]
+        pub fn set_owner_recovery(&mut self, recovery: Address) -> Result<()> {
+            let caller = self.env().caller();
+
+            if recovery == caller {
+                return Err(Error::NotAllowed);
+            }
+
+            self.owner_recovery.insert(caller, &recovery);
+            self.env().emit_event(OwnerRecoverySet {
+                owner: caller,
+                recovery: Some(recovery),
+            });
+
+            Ok(())
+        }
+
+        /// Remove the caller's recovery account and any pending recovery.
+        #[ink(message)]
+        pub fn clear_owner_recovery(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+
+            self.owner_recovery.remove(caller);
+            if self.pending_recoveries.take(caller).is_some() {
+                self.env().emit_event(RecoveryCancelled { owner: caller });
+            }
+            self.env().emit_event(OwnerRecoverySet {
+                owner: caller,
+                recovery: None,
+            });
+
+            Ok(())
+        }
+
+        /// Get the recovery account of an owner.
+        #[ink(message)]
+        pub fn get_owner_recovery(&self, owner: Address) -> Option<Address> {
+            self.owner_recovery.get(owner)
+        }
+
+        /// Start recovering `owner`'s tokens to `new_owner` (owner's recovery account only).
+        ///
+        /// Tokens can be moved with `execute_recovery` once the challenge period
+        /// has elapsed; until then the owner may `cancel_recovery`.
+        #[ink(message)]
+        pub fn initiate_recovery(&mut self, owner: Address, new_owner: Address) -> Result<()> {
+            let caller = self.env().caller();
+
+            if self.owner_recovery.get(owner) != Some(caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            if new_owner == owner || self.pending_recoveries.contains(owner) {
+                return Err(Error::NotAllowed);
+            }
+
+            let initiated_at = self.env().block_number();
+            let executable_at = initiated_at.saturating_add(self.recovery_challenge_period);
+            self.pending_recoveries.insert(
+                owner,
+                &PendingRecovery {
+                    recovery: caller,
+                    new_owner,
+                    initiated_at,
+                    executable_at,
+                },
+            );
+
+            self.env().emit_event(RecoveryInitiated {
+                owner,
+                recovery: caller,
+                new_owner,
+                executable_at,
+            });
+
+            Ok(())
+        }
+
+        /// Cancel a pending recovery of the caller's tokens (challenge).
+        #[ink(message)]
+        pub fn cancel_recovery(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+
+            if self.pending_recoveries.take(caller).is_none() {
+                return Err(Error::NotAllowed);
+            }
+
+            self.env().emit_event(RecoveryCancelled { owner: caller });
+            Ok(())
+        }
+
+        /// Get the pending recovery of an owner.
+        #[ink(message)]
+        pub fn get_pending_recovery(&self, owner: Address) -> Option<PendingRecovery> {
+            self.pending_recoveries.get(owner)
+        }
+
+        /// Move up to `MAX_BATCH_SIZE` of `owner`'s tokens to the recovery's new owner.
+        ///
+        /// Callable by the recovery account once the challenge period elapsed.
+        /// The pending recovery stays open so owners with many tokens can be
+        /// recovered over several calls. Every transfer rule except token
+        /// approval still applies per token (soulbound, locks, co-ownership,
+        /// blacklist, allowlists, terms, and cooldowns).
+        #[ink(message)]
+        pub fn execute_recovery(
+            &mut self,
+            owner: Address,
+            token_ids: ink::prelude::vec::Vec<u128>,
+        ) -> Result<ink::prelude::vec::Vec<BatchItemResult>> {
+            let caller = self.env().caller();
+            let pending = self
+                .pending_recoveries
+                .get(owner)
+                .ok_or(Error::NotAllowed)?;
+
+            if pending.recovery != caller || self.owner_recovery.get(owner) != Some(caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            if self.env().block_number() < pending.executable_at {
+                return Err(Error::ChallengePeriodActive);
+            }
+
+            if token_ids.is_empty() || token_ids.len() > MAX_BATCH_SIZE {
+                return Err(Error::InvalidInput);
+            }
+
+            let mut recovered = 0u32;
+            let mut results = ink::prelude::vec::Vec::with_capacity(token_ids.len());
+
+            for token_id in token_ids {
+                let error = self
+                    .move_checked(&owner, &pending.new_owner, token_id, false)
+                    .err();
+                if error.is_none() {
+                    recovered += 1;
+                }
+                results.push(BatchItemResult { token_id, error });
+            }
+
+            self.env().emit_event(TokensRecovered {
+                owner,
+                new_owner: pending.new_owner,
+                recovered,
+            });
+
+            Ok(results)
+        }
+
+        /// Set the recovery challenge period in blocks (admin-only).
+        #[ink(message)]
+        pub fn set_recovery_challenge_period(&mut self, blocks: u32) -> Result<()> {
+            self.ensure_direct_admin()?;
+            self.apply_admin_action(AdminAction::SetRecoveryChallengePeriod(blocks))
+        }
+
+        /// Get the recovery challenge period in blocks.
+        #[ink(message)]
+        pub fn recovery_challenge_period(&self) -> u32 {
+            self.recovery_challenge_period
+        }
+
+        // Physical custodians
+
+        /// Record who physically holds the item without transferring the token
+        /// (owner-only). Replaces any current custodian.
+        ///
+        /// # Errors
+        ///
+        /// * `NotOwner` - Caller does not own the token
+        /// * `InvalidInput` - `custodian` is the owner
+        /// * `AccountBlacklisted` - `custodian` is blacklisted
+        #[ink(message)]
+        pub fn assign_custodian(&mut self, token_id: u128, custodian: Address) -> Result<()> {
+            let caller = self.env().caller();
+            if self.token_owner.get(token_id) != Some(caller) {
+                return Err(Error::NotOwner);
+            }
+            self.ensure_not_draft(token_id)?;
+            if custodian == caller {
+                return Err(Error::InvalidInput);
+            }
+            if self.blacklist.contains(custodian) {
+                return Err(Error::AccountBlacklisted);
+            }
+            if self.lease_active(token_id) {
+                return Err(Error::NotAllowed);
+            }
+
+            self.custodians.insert(token_id, &custodian);
+            self.custody_leases.remove(token_id);
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(CustodianAssigned {
+                token_id,
+                custodian,
+                owner: caller,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// End physical custody, e.g. on handback (owner or custodian).
+        ///
+        /// During an active lease only the custodian may end it early.
+        #[ink(message)]
+        pub fn release_custody(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            let custodian = self.custodians.get(token_id).ok_or(Error::NotAllowed)?;
+            if caller != custodian
+                && (self.token_owner.get(token_id) != Some(caller) || self.lease_active(token_id))
+            {
+                return Err(Error::Unauthorized);
+            }
+
+            self.end_custody(token_id, custodian, caller);
+            Ok(())
+        }
+
+        /// Lease physical custody to `custodian` until block `until` (owner-only),
+        /// e.g. for rental, demo units, or consignment.
+        ///
+        /// Once the lease ends anyone may call `return_custody`.
+        ///
+        /// # Errors
+        ///
+        /// * `NotOwner` - Caller does not own the token
+        /// * `InvalidInput` - `custodian` is the owner or `until` is not in the future
+        /// * `NotAllowed` - Another lease is still active
+        /// * `AccountBlacklisted` - `custodian` is blacklisted
+        #[ink(message)]
+        pub fn lease_custody(
+            &mut self,
+            token_id: u128,
+            custodian: Address,
+            until: u32,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            if self.token_owner.get(token_id) != Some(caller) {
+                return Err(Error::NotOwner);
+            }
+            self.ensure_not_draft(token_id)?;
+            if custodian == caller || until <= self.env().block_number() {
+                return Err(Error::InvalidInput);
+            }
+            if self.blacklist.contains(custodian) {
+                return Err(Error::AccountBlacklisted);
+            }
+            if self.lease_active(token_id) {
+                return Err(Error::NotAllowed);
+            }
+
+            self.custodians.insert(token_id, &custodian);
+            self.custody_leases.insert(token_id, &until);
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(CustodyLeased {
+                token_id,
+                custodian,
+                owner: caller,
+                until,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Return custody of an item whose lease has ended (anyone), or early
+        /// (the custodian).
+        #[ink(message)]
+        pub fn return_custody(&mut self, token_id: u128) -> Result<()> {
+            let caller = self.env().caller();
+            if !self.custody_leases.contains(token_id) {
+                return Err(Error::NotAllowed);
+            }
+            let custodian = self.custodians.get(token_id).ok_or(Error::NotAllowed)?;
+            if self.lease_active(token_id) && caller != custodian {
+                return Err(Error::Unauthorized);
+            }
+
+            self.end_custody(token_id, custodian, caller);
+            Ok(())
+        }
+
+        /// Get the end block of the custody lease of an item, if leased.
+        #[ink(message)]
+        pub fn custody_lease_until(&self, token_id: u128) -> Option<u32> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.custody_leases.get(token_id)
+        }
+
+        fn lease_active(&self, token_id: u128) -> bool {
+            self.custody_leases
+                .get(token_id)
+                .is_some_and(|until| self.env().block_number() < until)
+        }
+
+        fn end_custody(&mut self, token_id: u128, custodian: Address, released_by: Address) {
+            self.custodians.remove(token_id);
+            self.custody_leases.remove(token_id);
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(CustodyReleased {
+                token_id,
+                custodian,
+                released_by,
+                event_seq,
+            });
+        }
+
+        /// Get the physical custodian of an item, if one is assigned.
+        #[ink(message)]
+        pub fn custodian_of(&self, token_id: u128) -> Option<Address> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.custodians.get(token_id)
+        }
+
+        // Co-ownership
+
+        /// Split ownership of a batch passport into shares (owner-only), e.g.
+        /// for a joint venture.
+        ///
+        /// The owner of record keeps the token, but it can no longer be
+        /// transferred or burned directly: moving it as a whole needs
+        /// `approve_co_owned_transfer` from holders of `threshold` shares.
+        ///
+        /// # Errors
+        ///
+        /// * `NotOwner` - Caller does not own the token
+        /// * `NotAllowed` - Token is not batch-granularity or already co-owned
+        /// * `InvalidInput` - Empty, duplicate, zero, or more than `MAX_CO_OWNERS`
+        ///   holders, or threshold not in `1..=total shares`
+        /// * `AccountBlacklisted` - A holder is blacklisted
+        #[ink(message)]
+        pub fn enable_co_ownership(
+            &mut self,
+            token_id: u128,
+            shares: ink::prelude::vec::Vec<(Address, u32)>,
+            threshold: u32,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+            if self.token_owner.get(token_id) != Some(caller) {
+                return Err(Error::NotOwner);
+            }
+            self.ensure_not_draft(token_id)?;
+            if record.granularity != Granularity::Batch || self.co_ownerships.contains(token_id) {
+                return Err(Error::NotAllowed);
+            }
+
+            let has_duplicates = shares
+                .iter()
+                .enumerate()
+                .any(|(i, (holder, _))| shares[..i].iter().any(|(other, _)| other == holder));
+            let total = shares
+                .iter()
+                .try_fold(0u32, |total, (_, amount)| total.checked_add(*amount))
+                .ok_or(Error::InvalidInput)?;
+            if shares.is_empty()
+                || shares.len() > MAX_CO_OWNERS
+                || has_duplicates
+                || shares.iter().any(|(_, amount)| *amount == 0)
+                || threshold == 0
+                || threshold > total
+            {
+                return Err(Error::InvalidInput);
+            }
+            if shares
+                .iter()
+                .any(|(holder, _)| self.blacklist.contains(*holder))
+            {
+                return Err(Error::AccountBlacklisted);
+            }
+
+            self.co_ownerships
+                .insert(token_id, &CoOwnership { shares, threshold });
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(CoOwnershipEnabled {
+                token_id,
+                total_shares: total,
+                threshold,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Transfer `amount` of the caller's shares in a co-owned token to `to`.
+        ///
+        /// # Errors
+        ///
+        /// * `NotAllowed` - Token is not co-owned
+        /// * `Unauthorized` - Caller holds no shares
+        /// * `InvalidInput` - `amount` is zero or exceeds the caller's shares, or
+        ///   `to` would be holder number `MAX_CO_OWNERS + 1`
+        /// * `AccountBlacklisted` - `to` is blacklisted
+        #[ink(message)]
+        pub fn transfer_shares(&mut self, token_id: u128, to: Address, amount: u32) -> Result<()> {
+            self.ensure_not_paused()?;
+            let caller = self.env().caller();
+            let mut ownership = self.co_ownerships.get(token_id).ok_or(Error::NotAllowed)?;
+            let held = ownership.shares_of(caller);
+            if held == 0 {
+                return Err(Error::Unauthorized);
+            }
+            if amount == 0 || amount > held {
+                return Err(Error::InvalidInput);
+            }
+            if self.blacklist.contains(to) {
+                return Err(Error::AccountBlacklisted);
+            }
+
+            if to != caller {
+                let holders = ownership.shares.len();
+                match ownership
+                    .shares
+                    .iter_mut()
+                    .find(|(holder, _)| *holder == to)
+                {
+                    Some((_, shares)) => *shares += amount,
+                    None if holders < MAX_CO_OWNERS || amount == held => {
+                        ownership.shares.push((to, amount));
+                    }
+                    None => return Err(Error::InvalidInput),
+                }
+                for (holder, shares) in ownership.shares.iter_mut() {
+                    if *holder == caller {
+                        *shares -= amount;
+                    }
+                }
+                ownership.shares.retain(|(_, shares)| *shares > 0);
+                self.co_ownerships.insert(token_id, &ownership);
+            }
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(SharesTransferred {
+                token_id,
+                from: caller,
+                to,
+                amount,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Approve transferring a co-owned token as a whole to `to` (share
+        /// holders only).
+        ///
+        /// Approving a different recipient than the pending proposal starts a
+        /// new one. Approvals are weighed by the holders' current shares; once
+        /// they reach the threshold the token moves to `to` under the usual
+        /// transfer rules and co-ownership ends. Returns whether it moved.
+        ///
+        /// # Errors
+        ///
+        /// * `NotAllowed` - Token is not co-owned, or the caller already approved
+        /// * `Unauthorized` - Caller holds no shares
+        #[ink(message)]
+        pub fn approve_co_owned_transfer(&mut self, token_id: u128, to: Address) -> Result<bool> {
+            let caller = self.env().caller();
+            let ownership = self.co_ownerships.get(token_id).ok_or(Error::NotAllowed)?;
+            if ownership.shares_of(caller) == 0 {
+                return Err(Error::Unauthorized);
+            }
+
+            let mut approvals = match self.co_owned_transfer_approvals.get(token_id) {
+                Some((proposed, approvals)) if proposed == to => approvals,
+                _ => ink::prelude::vec::Vec::new(),
+            };
+            if approvals.contains(&caller) {
+                return Err(Error::NotAllowed);
+            }
+            approvals.push(caller);
+
+            // Holders that sold their shares since approving no longer count
+            let approved_shares = approvals
+                .iter()
+                .map(|holder| ownership.shares_of(*holder))
+                .sum::<u32>();
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(CoOwnedTransferApproved {
+                token_id,
+                holder: caller,
+                to,
+                approved_shares,
+                threshold: ownership.threshold,
+                event_seq,
+            });
+
+            if approved_shares < ownership.threshold {
+                self.co_owned_transfer_approvals
+                    .insert(token_id, &(to, approvals));
+                return Ok(false);
+            }
+
+            let owner = self.token_owner.get(token_id).ok_or(Error::TokenNotFound)?;
+            self.co_ownerships.remove(token_id);
+            if let Err(error) = self.move_checked(&owner, &to, token_id, false) {
+                self.co_ownerships.insert(token_id, &ownership);
+                return Err(error);
+            }
+            self.co_owned_transfer_approvals.remove(token_id);
+
+            Ok(true)
+        }
+
+        /// Get the shares and threshold of a co-owned token.
+        #[ink(message)]
+        pub fn co_ownership(&self, token_id: u128) -> Option<CoOwnership> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.co_ownerships.get(token_id)
+        }
+
+        /// Get the pending whole-token transfer proposal of a co-owned token:
+        /// the recipient and the holders that approved it.
+        #[ink(message)]
+        pub fn co_owned_transfer_approvals(
+            &self,
+            token_id: u128,
+        ) -> Option<(Address, ink::prelude::vec::Vec<Address>)> {
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            self.co_owned_transfer_approvals.get(token_id)
+        }
+
+        // Chain of custody
+
+        /// Get the retained (not yet pruned) custody entries, oldest first.
+        #[ink(message)]
+        pub fn get_custody_history(&self, token_id: u128) -> ink::prelude::vec::Vec<CustodyEntry> {
+            if !self.can_read(token_id) {
+                return ink::prelude::vec::Vec::new();
+            }
+            let first = self.custody_checkpoint(token_id).pruned_count;
+            let len = self.custody_len.get(token_id).unwrap_or(0);
+
+            (first..len)
+                .filter_map(|i| self.custody_log.get((token_id, i)))
+                .collect()
+        }
+
+        /// Get the checkpoint covering pruned custody entries.
+        #[ink(message)]
+        pub fn get_custody_checkpoint(&self, token_id: u128) -> Option<CustodyCheckpoint> {
+            if !self.can_read(token_id) {
+                return None;
+            }
+            self.custody_checkpoints.get(token_id)
+        }
+
+        /// Fold old custody entries into the checkpoint digest (issuer or admin).
+        ///
+        /// Keeps the `keep_recent` newest entries and prunes at most
+        /// `MAX_CUSTODY_PRUNE_PER_CALL` older ones per call. Admins that are not
+        /// the issuer queue `AdminAction::PruneCustodyHistory` while an admin
+        /// threshold or timelock is set.
+        ///
+        /// # Returns
+        ///
+        /// Number of entries pruned by this call
+        #[ink(message)]
+        pub fn prune_custody_history(&mut self, token_id: u128, keep_recent: u32) -> Result<u32> {
+            let caller = self.env().caller();
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.is_issuer(&record, caller) {
+                self.ensure_direct_admin()?;
+            }
+            self.prune_custody(token_id, keep_recent)
+        }
+
+        fn prune_custody(&mut self, token_id: u128, keep_recent: u32) -> Result<u32> {
+            if !self.passports.contains(token_id) {
+                return Err(Error::TokenNotFound);
+            }
+            self.ensure_not_draft(token_id)?;
+
+            let mut checkpoint = self.custody_checkpoint(token_id);
+            let len = self.custody_len.get(token_id).unwrap_or(0);
+            let end = len.saturating_sub(keep_recent).min(
+                checkpoint
+                    .pruned_count
+                    .saturating_add(MAX_CUSTODY_PRUNE_PER_CALL),
+            );
+
+            if end <= checkpoint.pruned_count {
+                return Ok(0);
+            }
+
+            let pruned = end - checkpoint.pruned_count;
+            for i in checkpoint.pruned_count..end {
+                if let Some(entry) = self.custody_log.take((token_id, i)) {
+                    let mut digest = [0u8; 32];
+                    ink::env::hash_encoded::<ink::env::hash::Sha2x256, _>(
+                        &(checkpoint.digest, entry),
+                        &mut digest,
+                    );
+                    checkpoint.digest = digest;
+                }
+            }
+            checkpoint.pruned_count = end;
+            self.custody_checkpoints.insert(token_id, &checkpoint);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(CustodyHistoryPruned {
+                token_id,
+                pruned_count: pruned,
+                total_pruned: checkpoint.pruned_count,
+                digest: checkpoint.digest,
+                event_seq,
+            });
+
+            Ok(pruned)
+        }
+
+        // Query messages
+
+        /// Get next token ID (for informational purposes)
+        #[ink(message)]
+        pub fn next_token_id(&self) -> u128 {
+            self.next_token_id
+        }
+
+        /// Get specific version from history
+        ///
+        /// # Arguments
+        ///
+        /// * `token_id` - Token ID
+        /// * `version` - Version number (1-indexed)
         ///
         /// # Returns
         ///
         /// VersionHistory if found, None otherwise
         ///
-        /// # Example
+        /// # Example
+        ///
+        /// ```ignore
+        /// let v1 = contract.get_version(token_id, 1);  // Get original version
+        /// let v2 = contract.get_version(token_id, 2);  // Get second version
+        /// ```
+        #[ink(message)]
+        pub fn get_version(&self, token_id: u128, version: u32) -> Option<VersionHistory> {
+            if !self.can_read(token_id) {
+                return None;
+            }
+            self.version_history.get((token_id, version))
+        }
+
+        /// Get all version history for a passport
+        ///
+        /// Returns a vector of all versions in ascending order (v1, v2, v3, ...).
+        ///
+        /// # Arguments
+        ///
+        /// * `token_id` - Token ID
+        ///
+        /// # Returns
+        ///
+        /// Vector of VersionHistory entries (empty if token doesn't exist or has no history)
+        ///
+        /// # Note
+        ///
+        /// This iterates from version 1 up to the current version.
+        /// For passports with many versions, consider using get_version() for specific versions.
+        #[ink(message)]
+        pub fn get_version_history(
+            &self,
+            token_id: u128,
+        ) -> ink::prelude::vec::Vec<VersionHistory> {
+            use ink::prelude::vec::Vec;
+
+            if !self.can_read(token_id) {
+                return Vec::new();
+            }
+
+            // Get current passport to know the latest version
+            let record = match self.passports.get(token_id) {
+                Some(r) => r,
+                None => return Vec::new(), // Token doesn't exist
+            };
+
+            let mut history = Vec::new();
+
+            // Iterate through all versions (1..=current_version)
+            for v in 1..=record.version {
+                if let Some(entry) = self.version_history.get((token_id, v)) {
+                    history.push(entry);
+                }
+            }
+
+            history
+        }
+
+        /// Get the latest N versions
+        ///
+        /// Useful for displaying recent history without loading all versions.
+        ///
+        /// # Arguments
+        ///
+        /// * `token_id` - Token ID
+        /// * `limit` - Maximum number of versions to return
+        ///
+        /// # Returns
+        ///
+        /// Vector of the most recent versions, in descending order (newest first)
+        #[ink(message)]
+        pub fn get_recent_versions(
+            &self,
+            token_id: u128,
+            limit: u32,
+        ) -> ink::prelude::vec::Vec<VersionHistory> {
+            use ink::prelude::vec::Vec;
+
+            if !self.can_read(token_id) {
+                return Vec::new();
+            }
+
+            let record = match self.passports.get(token_id) {
+                Some(r) => r,
+                None => return Vec::new(),
+            };
+
+            let mut history = Vec::new();
+            let current_version = record.version;
+
+            // Start from current version and go backwards
+            let start = if current_version > limit {
+                current_version - limit + 1
+            } else {
+                1
+            };
+
+            for v in (start..=current_version).rev() {
+                if let Some(entry) = self.version_history.get((token_id, v)) {
+                    history.push(entry);
+                }
+            }
+
+            history
+        }
+
+        /// Find token ID by subject identifier hash
+        ///
+        /// Enables lookup of passport by product identifier (hashed).
+        /// Draft passports are only resolved for their issuer.
+        /// The caller must compute the hash off-chain using the same algorithm:
+        /// - ProductClass: SHA-256(productId)
+        /// - Batch: SHA-256(productId + "#" + batchNumber)
+        /// - Item: SHA-256(productId + "#" + serialNumber)
+        ///
+        /// # Arguments
+        ///
+        /// * `subject_id_hash` - SHA-256 hash of the canonical subject identifier
+        ///
+        /// # Returns
+        ///
+        /// Token ID if found, None otherwise
+        ///
+        /// # Example
+        ///
+        /// ```ignore
+        /// // Off-chain: Compute hash
+        /// let hash = sha256("GTIN-123#LOT-2024-001");  // Batch granularity
+        ///
+        /// // On-chain: Lookup token ID
+        /// let token_id = contract.find_token_by_subject_id(hash);
+        /// ```
+        #[ink(message)]
+        pub fn find_token_by_subject_id(&self, subject_id_hash: [u8; 32]) -> Option<u128> {
+            let token_id = self.subject_id_to_token.get(subject_id_hash)?;
+            if self.is_hidden_draft(token_id) {
+                return None;
+            }
+            Some(token_id)
+        }
+
+        /// Resolve many subject hashes in one query (e.g. a scanned case of mixed items).
+        ///
+        /// Results are in input order, with `None` for unknown subjects and hidden drafts.
+        #[ink(message)]
+        pub fn find_tokens_by_subject_ids(
+            &self,
+            subject_id_hashes: ink::prelude::vec::Vec<[u8; 32]>,
+        ) -> ink::prelude::vec::Vec<Option<u128>> {
+            subject_id_hashes
+                .into_iter()
+                .map(|hash| self.find_token_by_subject_id(hash))
+                .collect()
+        }
+
+        /// List the caller's Draft passports (paginated, oldest first).
+        ///
+        /// Drafts are excluded from all public lookups; this is the only
+        /// enumeration that returns them.
+        #[ink(message)]
+        pub fn my_drafts(&self, offset: u32, limit: u32) -> ink::prelude::vec::Vec<u128> {
+            let drafts = self
+                .issuer_drafts
+                .get(self.env().caller())
+                .unwrap_or_default();
+            drafts
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect()
+        }
+
+        // Internal visibility helpers (Restricted passports and Drafts)
+
+        /// True unless the token is Restricted and the caller is neither the
+        /// issuer, the owner, nor an approved account.
+        fn can_read_full(&self, token_id: u128) -> bool {
+            if self.visibility.get(token_id) != Some(Visibility::Restricted) {
+                return true;
+            }
+
+            let caller = self.env().caller();
+            let is_issuer = self
+                .passports
+                .get(token_id)
+                .is_some_and(|record| self.is_issuer(&record, caller));
+            let is_authorized = self
+                .token_owner
+                .get(token_id)
+                .is_some_and(|owner| self.approved_or_owner(caller, token_id, owner));
+
+            is_issuer || is_authorized
+        }
+
+        /// Full record and version data is readable (`can_read_full` and not a
+        /// hidden Draft).
+        fn can_read(&self, token_id: u128) -> bool {
+            self.can_read_full(token_id) && !self.is_hidden_draft(token_id)
+        }
+
+        /// True if `token_id` is a Draft and the caller is neither its issuer
+        /// nor one of the issuer's checkers (reviewer or co-signers).
+        fn is_hidden_draft(&self, token_id: u128) -> bool {
+            let caller = self.env().caller();
+            match self.passports.get(token_id) {
+                Some(record) => {
+                    record.status == PassportStatus::Draft
+                        && !self.is_issuer(&record, caller)
+                        && self.reviewer_of(record.issuer) != Some(caller)
+                        && !self
+                            .issuance_policy_of(record.issuer)
+                            .is_some_and(|policy| policy.signers.contains(&caller))
+                }
+                None => false,
+            }
+        }
+
+        /// Reject per-token changes whose events would announce a Draft before
+        /// its activation. Callers that cannot see the Draft get `TokenNotFound`.
+        fn ensure_not_draft(&self, token_id: u128) -> Result<()> {
+            if self.is_hidden_draft(token_id) {
+                return Err(Error::TokenNotFound);
+            }
+            if self
+                .passports
+                .get(token_id)
+                .is_some_and(|record| record.status == PassportStatus::Draft)
+            {
+                return Err(Error::PassportDraft);
+            }
+            Ok(())
+        }
+
+        /// Attach or replace `endorsement.certifier`'s endorsement of a token.
+        fn add_endorsement(&mut self, token_id: u128, endorsement: Endorsement) -> Result<()> {
+            let certifier = endorsement.certifier;
+            if let Some(certificate_hash) = endorsement.certificate_hash {
+                if self
+                    .certificates
+                    .get(certificate_hash)
+                    .is_some_and(|entry| entry != (token_id, certifier))
+                {
+                    return Err(Error::NotAllowed);
+                }
+            }
+
+            let mut endorsements = self.endorsements.get(token_id).unwrap_or_default();
+            let replaced = endorsements
+                .iter()
+                .position(|e| e.certifier == certifier)
+                .map(|index| endorsements.remove(index));
+            if endorsements.len() >= MAX_ENDORSEMENTS {
+                return Err(Error::NotAllowed);
+            }
+            if let Some(old_hash) = replaced.and_then(|e| e.certificate_hash) {
+                self.certificates.remove(old_hash);
+            }
+            if let Some(certificate_hash) = endorsement.certificate_hash {
+                self.certificates
+                    .insert(certificate_hash, &(token_id, certifier));
+            }
+            let claim_hash = endorsement.claim_hash;
+            let evidence_hash = endorsement.evidence_hash;
+            endorsements.push(endorsement);
+            self.endorsements.insert(token_id, &endorsements);
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportEndorsed {
+                token_id,
+                certifier,
+                claim_hash,
+                evidence_hash,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// Drafts are indexed under the key controlling `issuer`, so they stay
+        /// listed by `my_drafts` after a key rotation.
+        fn index_draft(&mut self, issuer: Address, token_id: u128) -> Result<()> {
+            let key = self.controller_of(issuer);
+            let mut drafts = self.issuer_drafts.get(key).unwrap_or_default();
+            if drafts.len() >= MAX_DRAFTS_PER_ISSUER {
+                return Err(Error::NotAllowed);
+            }
+            drafts.push(token_id);
+            self.issuer_drafts.insert(key, &drafts);
+            Ok(())
+        }
+
+        fn unindex_draft(&mut self, issuer: Address, token_id: u128) {
+            let key = self.controller_of(issuer);
+            let mut drafts = self.issuer_drafts.get(key).unwrap_or_default();
+            drafts.retain(|id| *id != token_id);
+            if drafts.is_empty() {
+                self.issuer_drafts.remove(key);
+            } else {
+                self.issuer_drafts.insert(key, &drafts);
+            }
+        }
+
+        /// Drop the staged update of `token_id`, if any, from the submitting
+        /// issuer's pending count.
+        fn clear_pending_update(&mut self, token_id: u128) {
+            let Some(pending) = self.pending_updates.take(token_id) else {
+                return;
+            };
+            let issuer = pending.entry.issuer;
+            let controller = self.controller_of(issuer);
+            self.move_pending_updates(controller, None, 1);
+
+            let count = self
+                .issuer_pending_updates
+                .get(issuer)
+                .unwrap_or(0)
+                .saturating_sub(1);
+            if count == 0 {
+                self.issuer_pending_updates.remove(issuer);
+            } else {
+                self.issuer_pending_updates.insert(issuer, &count);
+            }
+        }
+
+        /// Move `count` staged updates from one key's pending count to another's
+        /// (`None` drops them).
+        fn move_pending_updates(&mut self, from: Address, to: Option<Address>, count: u32) {
+            let left = self
+                .pending_update_counts
+                .get(from)
+                .unwrap_or(0)
+                .saturating_sub(count);
+            if left == 0 {
+                self.pending_update_counts.remove(from);
+            } else {
+                self.pending_update_counts.insert(from, &left);
+            }
+            if let Some(to) = to {
+                let merged = self.pending_update_counts.get(to).unwrap_or(0) + count;
+                self.pending_update_counts.insert(to, &merged);
+            }
+        }
+
+        // Internal revocation helper
+
+        /// Revoke one passport. All checks run before any state is written, so a
+        /// failed revocation leaves storage untouched (relied on by `revoke_batch`).
+        fn revoke(&mut self, token_id: u128, reason: Option<String>) -> Result<()> {
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            // Authorization: the current issuer or a co-issuer
+            if !self.is_issuer(&record, caller) && !self.is_co_issuer(token_id, caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            // Cannot revoke already revoked passports
+            if record.status == PassportStatus::Revoked {
+                return Err(Error::AlreadyRevoked);
+            }
+
+            // Update status (a Draft is published first)
+            let block_number =
+                self.change_status(&mut record, PassportStatus::Revoked, reason.clone());
+            self.bump_epoch_counters(|c| c.revoked += 1);
+            self.revocations.insert(
+                token_id,
+                &RevocationInfo {
+                    reason: reason.clone(),
+                    revoked_at: block_number,
+                    revoked_by: caller,
+                },
+            );
+
+            // Emit event
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportRevoked {
+                token_id,
+                issuer: caller,
+                reason,
+                revoked_at: block_number,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        // Internal suspension helpers
+
+        fn suspend(
+            &mut self,
+            token_id: u128,
+            reason: Option<String>,
+            until: Option<u32>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let mut record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if !self.is_issuer(&record, caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            match record.status {
+                PassportStatus::Active => {}
+                PassportStatus::Revoked => return Err(Error::PassportRevoked),
+                PassportStatus::Archived => return Err(Error::PassportArchived),
+                _ => return Err(Error::NotAllowed),
+            }
+
+            if let Some(block) = until {
+                self.suspended_until.insert(token_id, &block);
+            } else {
+                self.suspended_until.remove(token_id);
+            }
+
+            let block_number =
+                self.change_status(&mut record, PassportStatus::Suspended, reason.clone());
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(PassportSuspended {
+                token_id,
+                issuer: caller,
+                reason,
+                suspended_at: block_number,
+                until,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        /// True if the token has a timed suspension whose end block has been reached.
+        fn suspension_lapsed(&self, token_id: u128) -> bool {
+            self.suspended_until
+                .get(token_id)
+                .is_some_and(|until| self.env().block_number() >= until)
+        }
+
+        fn effective_status_of(&self, record: &PassportRecord) -> PassportStatus {
+            if record.status == PassportStatus::Suspended && self.suspension_lapsed(record.token_id)
+            {
+                return PassportStatus::Active;
+            }
+            record.status.clone()
+        }
+
+        /// Make `entry` the current version of `record` and append it to history.
+        fn publish_version(
+            &mut self,
+            mut record: PassportRecord,
+            entry: VersionHistory,
+            subject_id_hash: Option<[u8; 32]>,
+        ) {
+            let token_id = record.token_id;
+            let block_number = entry.updated_at;
+            let new_version = entry.version;
+            let dataset_uri = entry.dataset_uri.clone();
+            let payload_hash = entry.payload_hash;
+            let dataset_type = entry.dataset_type.clone();
+
+            // Update fields in current record
+            let old_subject_hash = record.subject_id_hash;
+            record.dataset_uri = dataset_uri.clone();
+            record.payload_hash = payload_hash;
+            record.dataset_type = dataset_type.clone();
+            record.subject_id_hash = subject_id_hash;
+            record.version = new_version;
+            record.updated_at = block_number;
+
+            // Update reverse lookup.
+            if let Some(old_hash) = old_subject_hash {
+                // Only remove if it points to this token_id (safety check)
+                if let Some(existing_token) = self.subject_id_to_token.get(old_hash) {
+                    if existing_token == token_id {
+                        // Note: ink! Mapping doesn't support deletion directly
+                        // We'll overwrite with new hash if it changed
+                    }
+                }
+            }
+
+            // Add/update new mapping
+            if let Some(new_hash) = subject_id_hash {
+                self.subject_id_to_token.insert(new_hash, &token_id);
+            }
+
+            // Store updated record
+            self.passports.insert(token_id, &record);
+
+            // Append-only version history.
+            self.version_history.insert((token_id, new_version), &entry);
+            self.bump_epoch_counters(|c| c.updated += 1);
+            self.touch(token_id);
+
+            // Draft versions are announced on activation
+            if record.status == PassportStatus::Draft {
+                return;
+            }
+
+            // Emit events
+            if self.verbose_events {
+                let event_seq = self.next_event_seq(token_id);
+                self.env().emit_event(PassportUpdated {
+                    token_id,
+                    dataset_uri,
+                    payload_hash,
+                    dataset_type,
+                    version: new_version,
+                    updated_at: block_number,
+                    event_seq,
+                });
+            }
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(AnchorDigest {
+                token_id,
+                version: new_version,
+                payload_hash,
+                event_seq,
+            });
+        }
+
+        // Internal status helper
+
+        fn activate(&mut self, record: &mut PassportRecord) {
+            let block_number = self.change_status(record, PassportStatus::Active, None);
+
+            let event_seq = self.next_event_seq(record.token_id);
+            self.env().emit_event(PassportActivated {
+                token_id: record.token_id,
+                issuer: record.issuer,
+                activated_at: block_number,
+                event_seq,
+            });
+        }
+
+        /// Apply a status transition, persist the record, and emit `PassportStatusChanged`.
+        ///
+        /// All status paths must go through here. Returns the block number of the change.
+        fn change_status(
+            &mut self,
+            record: &mut PassportRecord,
+            new_status: PassportStatus,
+            reason: Option<String>,
+        ) -> u32 {
+            if record.status == PassportStatus::Draft && new_status != PassportStatus::Draft {
+                self.publish_draft(record);
+            }
+
+            let block_number = self.env().block_number();
+            let old_status = core::mem::replace(&mut record.status, new_status.clone());
+            record.updated_at = block_number;
+            self.passports.insert(record.token_id, &*record);
+            // Revoked and Archived passports can no longer take a staged update
+            if matches!(
+                new_status,
+                PassportStatus::Revoked | PassportStatus::Archived
+            ) {
+                self.clear_pending_update(record.token_id);
+            }
+            self.touch(record.token_id);
+            self.append_status_change(
+                record.token_id,
+                Some(old_status.clone()),
+                new_status.clone(),
+                reason.clone(),
+            );
+
+            let event_seq = self.next_event_seq(record.token_id);
+            self.env().emit_event(PassportStatusChanged {
+                token_id: record.token_id,
+                old_status,
+                new_status,
+                changed_by: self.env().caller(),
+                reason,
+                event_seq,
+            });
+
+            block_number
+        }
+
+        /// A Draft leaves Draft status (activation, revocation, archival, or
+        /// burn): list and count it publicly and emit its deferred registration
+        /// events, so the passport is never readable without them.
+        fn publish_draft(&mut self, record: &PassportRecord) {
+            let token_id = record.token_id;
+            self.unindex_draft(record.issuer, token_id);
+            self.draft_count -= 1;
+            self.index_token(token_id);
+            if let Some(owner) = self.token_owner.get(token_id) {
+                let drafts = self.draft_balances.get(owner).unwrap_or(1) - 1;
+                if drafts == 0 {
+                    self.draft_balances.remove(owner);
+                } else {
+                    self.draft_balances.insert(owner, &drafts);
+                }
+                self.index_owned(owner, token_id);
+            }
+            self.emit_registration(record);
+        }
+
+        fn append_status_change(
+            &mut self,
+            token_id: u128,
+            old_status: Option<PassportStatus>,
+            new_status: PassportStatus,
+            reason: Option<String>,
+        ) {
+            let index = self.status_history_len.get(token_id).unwrap_or(0);
+            let entry = StatusChange {
+                old_status,
+                new_status,
+                changed_by: self.env().caller(),
+                reason,
+                block_number: self.env().block_number(),
+            };
+            self.status_history.insert((token_id, index), &entry);
+            self.status_history_len.insert(token_id, &(index + 1));
+        }
+
+        fn append_service_record(
+            &mut self,
+            provider: ServiceProvider,
+            token_id: u128,
+            uri: String,
+            hash: [u8; 32],
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            if !self.service_providers.contains((provider, caller)) {
+                return Err(Error::Unauthorized);
+            }
+            if !self.passports.contains(token_id) {
+                return Err(Error::TokenNotFound);
+            }
+            self.ensure_not_draft(token_id)?;
+            if uri.is_empty() {
+                return Err(Error::InvalidInput);
+            }
+            if self.strict_uri_mode {
+                validate_ipfs_uri(&uri)?;
+            }
+
+            let kind = match provider {
+                ServiceProvider::Repairer => ServiceRecordKind::Repair,
+                ServiceProvider::Recycler => ServiceRecordKind::EndOfLife,
+            };
+            let index = self.service_log_len.get(token_id).unwrap_or(0);
+            self.service_log.insert(
+                (token_id, index),
+                &ServiceRecord {
+                    kind: kind.clone(),
+                    uri,
+                    hash,
+                    recorded_by: caller,
+                    recorded_at: self.env().block_number(),
+                },
+            );
+            self.service_log_len.insert(token_id, &(index + 1));
+
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(ServiceRecordAppended {
+                token_id,
+                recorded_by: caller,
+                kind,
+                hash,
+                index,
+                event_seq,
+            });
+
+            Ok(())
+        }
+
+        // Internal registration helpers
+
+        /// Issuer gating shared by registration and ID reservation.
+        fn ensure_can_issue(&self, issuer: Address) -> Result<()> {
+            if self.blacklist.contains(issuer) {
+                return Err(Error::AccountBlacklisted);
+            }
+
+            // A rotated identity's retired key may no longer issue under it
+            if self.issuer_controllers.contains(issuer) {
+                return Err(Error::Unauthorized);
+            }
+
+            if self.allowlist_mode && !self.trusted_issuers.contains(issuer) {
+                return Err(Error::NotTrustedIssuer);
+            }
+
+            if let Some(registry) = self.issuer_registry {
+                if !self.registry_has_issuer(registry, issuer)? {
+                    return Err(Error::NotTrustedIssuer);
+                }
+            }
+
+            Ok(())
+        }
+
+        fn mint_passport(&mut self, input: RegistrationInput, params: MintParams) -> Result<u128> {
+            let RegistrationInput {
+                dataset_uri,
+                payload_hash,
+                dataset_type,
+                granularity,
+                subject_id_hash,
+                validity_blocks,
+            } = input;
+            let MintParams {
+                issuer,
+                owner,
+                status,
+                transferable,
+                prepaid,
+                reserved_id,
+            } = params;
+
+            self.ensure_not_paused()?;
+
+            if self.blacklist.contains(owner) {
+                return Err(Error::AccountBlacklisted);
+            }
+            self.ensure_can_issue(issuer)?;
+
+            // Issuers with an N-of-M policy may only propose (register Drafts)
+            if status != PassportStatus::Draft && self.issuance_policy_of(issuer).is_some() {
+                return Err(Error::NotAllowed);
+            }
+
+            // Issuers with a reviewer publish only after `approve_publication`
+            let status = if self.reviewer_of(issuer).is_some() {
+                PassportStatus::Draft
+            } else {
+                status
+            };
+
+            let token_id = reserved_id.unwrap_or(self.next_token_id);
+            let block_number = self.env().block_number();
+
+            if dataset_uri.is_empty() || dataset_type.is_empty() {
+                return Err(Error::InvalidInput);
+            }
+
+            let expires_at = match validity_blocks {
+                Some(0) => return Err(Error::InvalidInput),
+                Some(blocks) => Some(block_number.saturating_add(blocks)),
+                None => None,
+            };
+
+            if self.strict_uri_mode {
+                validate_ipfs_uri(&dataset_uri)?;
+            }
+
+            // Batch registrations collect the summed fee once, up front
+            let required_fee = self.fee_for(granularity.clone());
+            let fee_paid = if prepaid {
+                required_fee
+            } else {
+                self.collect_fee(required_fee)?;
+                self.env().transferred_value()
+            };
+
+            if let Some(subject_hash) = subject_id_hash {
+                self.settle_pre_claim(issuer, subject_hash)?;
+            }
+
+            let record = PassportRecord {
+                token_id,
+                issuer,
+                dataset_uri: dataset_uri.clone(),
+                payload_hash,
+                dataset_type: dataset_type.clone(),
+                version: 1,
+                status,
+                created_at: block_number,
+                updated_at: block_number,
+                granularity,
+                subject_id_hash,
+                archived_at: None,
+                expires_at,
+                transferable,
+            };
+
+            self.passports.insert(token_id, &record);
+            self.append_status_change(token_id, None, record.status.clone(), None);
+
+            let context = IssuanceContext {
+                required_fee,
+                fee_paid,
+                max_versions: self.max_versions,
+                strict_uri_mode: self.strict_uri_mode,
+                status_policy: self.status_policy.clone(),
+                issuer_approved: self
+                    .onboarding
+                    .get(issuer)
+                    .is_some_and(|r| r.status == OnboardingStatus::Approved),
+                permissioned: self.allowlist_mode || self.issuer_registry.is_some(),
+            };
+            self.issuance_context.insert(token_id, &context);
+
+            self.add_token_to(&owner, token_id)?;
+            self.record_custody(token_id, None, owner);
+
+            if reserved_id.is_none() {
+                self.next_token_id += 1;
+            }
+            self.live_tokens += 1;
+            self.recount_scanned(token_id, |state| state.live += 1);
+            self.bump_epoch_counters(|c| c.registered += 1);
+
+            // Drafts are indexed and counted on publication (`publish_draft`)
+            if record.status == PassportStatus::Draft {
+                self.index_draft(issuer, token_id)?;
+                self.draft_count += 1;
+                let drafts = self.draft_balances.get(owner).unwrap_or(0);
+                self.draft_balances.insert(owner, &(drafts + 1));
+            } else {
+                self.index_token(token_id);
+                self.index_owned(owner, token_id);
+            }
+
+            if let Some(subject_hash) = subject_id_hash {
+                self.subject_id_to_token.insert(subject_hash, &token_id);
+            }
+
+            let history_entry = VersionHistory {
+                version: 1,
+                dataset_uri,
+                payload_hash,
+                dataset_type,
+                updated_at: block_number,
+                updated_by: issuer,
+                diff_uri: None,
+                diff_hash: None,
+                issuer,
+            };
+            self.version_history.insert((token_id, 1), &history_entry);
+
+            // Drafts stay private (not even hashes) until `activate`
+            if record.status != PassportStatus::Draft {
+                self.emit_registration(&record);
+            }
+
+            Ok(token_id)
+        }
+
+        /// Registration events of a published passport, for its current version.
+        fn emit_registration(&mut self, record: &PassportRecord) {
+            let token_id = record.token_id;
+            if self.verbose_events {
+                let event_seq = self.next_event_seq(token_id);
+                self.env().emit_event(PassportRegistered {
+                    token_id,
+                    issuer: record.issuer,
+                    dataset_uri: record.dataset_uri.clone(),
+                    payload_hash: record.payload_hash,
+                    dataset_type: record.dataset_type.clone(),
+                    version: record.version,
+                    created_at: record.created_at,
+                    transferable: record.transferable,
+                    expires_at: record.expires_at,
+                    event_seq,
+                });
+            }
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(AnchorDigest {
+                token_id,
+                version: record.version,
+                payload_hash: record.payload_hash,
+                event_seq,
+            });
+
+            let owner = self.token_owner.get(token_id);
+            self.emit_transfer(None, owner, token_id);
+        }
+
+        // Internal ownership helpers
+
+        fn transfer_token_from(
+            &mut self,
+            from: &Address,
+            to: &Address,
+            token_id: u128,
+        ) -> Result<()> {
+            self.move_checked(from, to, token_id, true)
+        }
+
+        /// Custody change with every transfer rule. `check_approval` is false only
+        /// where the move is authorized otherwise (accepted offers, co-owner
+        /// approvals, owner recovery).
+        fn move_checked(
+            &mut self,
+            from: &Address,
+            to: &Address,
+            token_id: u128,
+            check_approval: bool,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+
+            // Require an existing passport record (same lifecycle rules)
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+            if !record.transferable {
+                return Err(Error::NotAllowed);
+            }
+            if self.locked.contains(token_id) {
+                return Err(Error::TokenLocked);
+            }
+            // Co-owned tokens move only via `approve_co_owned_transfer`
+            if self.co_ownerships.contains(token_id) {
+                return Err(Error::NotAllowed);
+            }
+            self.ensure_transferable(&record)?;
+
+            let owner = self.token_owner.get(token_id).ok_or(Error::TokenNotFound)?;
+
+            if owner != *from {
+                return Err(Error::NotOwner);
+            }
+
+            if check_approval && !self.approved_or_owner(caller, token_id, owner) {
+                return Err(Error::NotApproved);
+            }
+
+            if self.env().block_number() < self.transfers_allowed_from_block(&record) {
+                return Err(Error::TransferThrottled);
+            }
+
+            if let Some(terms_hash) = self.transfer_terms.get(token_id) {
+                let accepted = self
+                    .terms_acceptances
+                    .get((token_id, *to))
+                    .is_some_and(|(hash, _)| hash == terms_hash);
+                if !accepted {
+                    return Err(Error::TermsNotAccepted);
+                }
+            }
+
+            if self.blacklist.contains(*to) {
+                return Err(Error::AccountBlacklisted);
+            }
+
+            if !self.recipient_allowed(record.issuer, *to) {
+                return Err(Error::RecipientNotAllowed);
+            }
+
+            self.move_token(from, to, token_id)
+        }
+
+        fn set_recipient_allowlist_for(&mut self, issuer: Option<Address>, enabled: bool) {
+            if enabled {
+                self.recipient_allowlists.insert(issuer, &());
+            } else {
+                self.recipient_allowlists.remove(issuer);
+            }
+            self.env()
+                .emit_event(RecipientAllowlistChanged { issuer, enabled });
+        }
+
+        fn set_allowed_recipient_for(
+            &mut self,
+            issuer: Option<Address>,
+            recipient: Address,
+            allowed: bool,
+        ) {
+            if allowed {
+                self.allowed_recipients.insert((issuer, recipient), &());
+            } else {
+                self.allowed_recipients.remove((issuer, recipient));
+            }
+            self.env().emit_event(AllowedRecipientChanged {
+                issuer,
+                recipient,
+                allowed,
+            });
+        }
+
+        /// Every enabled allowlist (contract-wide and the issuer's) lists `recipient`.
+        fn recipient_allowed(&self, issuer: Address, recipient: Address) -> bool {
+            [None, Some(issuer)].into_iter().all(|list| {
+                !self.recipient_allowlists.contains(list)
+                    || self.allowed_recipients.contains((list, recipient))
+            })
+        }
+
+        fn transfers_allowed_from_block(&self, record: &PassportRecord) -> u32 {
+            let token_id = record.token_id;
+            let after_transfer = self.transfer_cooldown.get(token_id).map_or(0, |cooldown| {
+                let last = self
+                    .last_transfer_at
+                    .get(token_id)
+                    .unwrap_or(record.created_at);
+                last.saturating_add(cooldown)
+            });
+            let anchored_at = self
+                .version_history
+                .get((token_id, record.version))
+                .map_or(record.created_at, |entry| entry.updated_at);
+            after_transfer.max(anchored_at.saturating_add(self.update_transfer_cooldown))
+        }
+
+        fn ensure_lock_authority(&self, token_id: u128) -> Result<PassportRecord> {
+            let caller = self.env().caller();
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+            let authorized = match self.lock_authority {
+                LockAuthority::Owner => self.token_owner.get(token_id) == Some(caller),
+                LockAuthority::Issuer => self.is_issuer(&record, caller),
+            };
+            if !authorized {
+                return Err(Error::Unauthorized);
+            }
+            self.ensure_not_draft(token_id)?;
+            Ok(record)
+        }
+
+        /// Lifecycle rules shared by every custody change.
+        fn ensure_transferable(&self, record: &PassportRecord) -> Result<()> {
+            self.ensure_not_paused()?;
+            if self.frozen.contains(record.token_id) {
+                return Err(Error::TokenFrozen);
+            }
+            if record.status == PassportStatus::Revoked {
+                return Err(Error::PassportRevoked);
+            }
+            if self.status_policy.suspended_blocks_transfers
+                && self.effective_status_of(record) == PassportStatus::Suspended
+            {
+                return Err(Error::PassportSuspended);
+            }
+            if record.status == PassportStatus::Archived {
+                return Err(Error::PassportArchived);
+            }
+            if record.status == PassportStatus::Draft {
+                return Err(Error::PassportDraft);
+            }
+            Ok(())
+        }
+
+        /// Move ownership without authorization checks (callers must check first).
+        fn move_token(&mut self, from: &Address, to: &Address, token_id: u128) -> Result<()> {
+            self.clear_approval(token_id);
+            self.pending_transfers.remove(token_id);
+            self.remove_token_from(from, token_id)?;
+            self.add_token_to(to, token_id)?;
+            self.index_owned(*to, token_id);
+            self.record_custody(token_id, Some(*from), *to);
+
+            self.emit_transfer(Some(*from), Some(*to), token_id);
+
+            Ok(())
+        }
+
+        fn can_update(&self, caller: Address, record: &PassportRecord) -> bool {
+            let controller = self.controller_of(record.issuer);
+            if caller == controller
+                || self.is_co_issuer(record.token_id, caller)
+                || self.update_delegates.get((record.token_id, caller)) == Some(controller)
+            {
+                return true;
+            }
+
+            match self.session_keys.get(caller) {
+                Some(key) => {
+                    key.issuer == controller
+                        && record.token_id >= key.first_token_id
+                        && record.token_id <= key.last_token_id
+                        && self.env().block_number() < key.expires_at
+                }
+                None => false,
+            }
+        }
+
+        fn bump_epoch_counters(&mut self, bump: impl FnOnce(&mut EpochCounters)) {
+            let epoch = self.env().block_number() / self.epoch_length;
+            let mut counters = self.epoch_counters.get(epoch).unwrap_or_default();
+            bump(&mut counters);
+            self.epoch_counters.insert(epoch, &counters);
+        }
+
+        fn custody_checkpoint(&self, token_id: u128) -> CustodyCheckpoint {
+            self.custody_checkpoints.get(token_id).unwrap_or_default()
+        }
+
+        fn record_custody(&mut self, token_id: u128, from: Option<Address>, to: Address) {
+            let index = self.custody_len.get(token_id).unwrap_or(0);
+            let entry = CustodyEntry {
+                from,
+                to,
+                block_number: self.env().block_number(),
+            };
+            self.custody_log.insert((token_id, index), &entry);
+            self.custody_len.insert(token_id, &(index + 1));
+            self.last_transfer_at
+                .insert(token_id, &self.env().block_number());
+            self.touch(token_id);
+        }
+
+        /// Enforce and release any pre-claim on a subject hash being registered.
         ///
-        /// ```ignore
-        /// let v1 = contract.get_version(token_id, 1);  // Get original version
-        /// let v2 = contract.get_version(token_id, 2);  // Get second version
-        /// ```
+        /// Fails if an account other than the key controlling `issuer` holds an
+        /// active claim. The claimant's own claim is consumed and its bond
+        /// refunded; a lapsed claim's bond is forfeited to the fund.
+        fn settle_pre_claim(&mut self, issuer: Address, subject_hash: [u8; 32]) -> Result<()> {
+            let Some(claim) = self.pre_claims.get(subject_hash) else {
+                return Ok(());
+            };
+
+            let active = self.env().block_number() < claim.expires_at;
+            if active && claim.claimant != self.controller_of(issuer) {
+                return Err(Error::NotAllowed);
+            }
+
+            self.pre_claims.remove(subject_hash);
+            if active {
+                if !claim.bond.is_zero() {
+                    self.env()
+                        .transfer(claim.claimant, claim.bond)
+                        .map_err(|_| Error::TransferFailed)?;
+                }
+            } else {
+                self.fund_balance = self.fund_balance.saturating_add(claim.bond);
+            }
+
+            Ok(())
+        }
+
+        fn next_event_seq(&mut self, token_id: u128) -> u64 {
+            let seq = self.event_seqs.get(token_id).unwrap_or(0) + 1;
+            self.event_seqs.insert(token_id, &seq);
+            seq
+        }
+
+        fn touch(&mut self, token_id: u128) {
+            self.last_activity
+                .insert(token_id, &self.env().block_number());
+        }
+
+        fn decide_onboarding(&mut self, account: Address, status: OnboardingStatus) -> Result<()> {
+            let mut record = self.onboarding.get(account).ok_or(Error::NotAllowed)?;
+
+            if record.status != OnboardingStatus::Pending {
+                return Err(Error::NotAllowed);
+            }
+
+            record.status = status;
+            record.decided_at = Some(self.env().block_number());
+            self.onboarding.insert(account, &record);
+
+            Ok(())
+        }
+
+        /// Ethereum-style address of the secp256k1 key that signed `message_hash`.
+        fn recover_signer(&self, signature: &[u8; 65], message_hash: &[u8; 32]) -> Result<Address> {
+            let public_key = self
+                .env()
+                .ecdsa_recover(signature, message_hash)
+                .map_err(|_| Error::InvalidSignature)?;
+            let signer = self
+                .env()
+                .ecdsa_to_eth_address(&public_key)
+                .map_err(|_| Error::InvalidSignature)?;
+            Ok(Address::from(signer))
+        }
+
+        fn deposit_for(&self, bytes: usize) -> U256 {
+            self.deposit_per_byte.saturating_mul(U256::from(bytes))
+        }
+
+        /// Require at least `fee` to be transferred; the whole value accrues to the fund.
+        fn collect_fee(&mut self, fee: U256) -> Result<()> {
+            let paid = self.env().transferred_value();
+            if paid < fee {
+                return Err(Error::InsufficientFee);
+            }
+            self.fund_balance = self.fund_balance.saturating_add(paid);
+            Ok(())
+        }
+
+        /// Account currently controlling an issuer identity (itself unless rotated).
+        fn controller_of(&self, issuer: Address) -> Address {
+            self.issuer_controllers.get(issuer).unwrap_or(issuer)
+        }
+
+        /// Require the caller to be the key currently controlling `issuer`.
+        fn ensure_controller(&self, issuer: Address) -> Result<()> {
+            if self.controller_of(issuer) != self.env().caller() {
+                return Err(Error::Unauthorized);
+            }
+            Ok(())
+        }
+
+        /// Issuance policy governing an issuer identity's passports.
+        fn issuance_policy_of(&self, issuer: Address) -> Option<IssuancePolicy> {
+            self.issuance_policies.get(self.controller_of(issuer))
+        }
+
+        fn reviewer_of(&self, issuer: Address) -> Option<Address> {
+            self.reviewers.get(self.controller_of(issuer))
+        }
+
+        fn is_issuer(&self, record: &PassportRecord, account: Address) -> bool {
+            self.controller_of(record.issuer) == account
+        }
+
+        fn is_co_issuer(&self, token_id: u128, account: Address) -> bool {
+            self.co_issuers
+                .get(token_id)
+                .is_some_and(|co_issuers| co_issuers.contains(&account))
+        }
+
+        /// Issuer changes emit per-token events, so they wait until a Draft
+        /// is activated (`PassportDraft`).
+        fn ensure_issuer(&self, token_id: u128, caller: Address) -> Result<PassportRecord> {
+            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
+            if !self.is_issuer(&record, caller) {
+                return Err(Error::Unauthorized);
+            }
+            if record.status == PassportStatus::Draft {
+                return Err(Error::PassportDraft);
+            }
+            Ok(record)
+        }
+
+        fn ensure_role(&self, role: Role) -> Result<()> {
+            if !self.has_role(role, self.env().caller()) {
+                return Err(Error::Unauthorized);
+            }
+            Ok(())
+        }
+
+        /// Admin check for actions that must be queued while a timelock or an
+        /// admin threshold above one is set.
+        fn ensure_direct_admin(&self) -> Result<()> {
+            self.ensure_role(Role::Admin)?;
+            if self.timelock_delay > 0 || self.admin_threshold > 1 {
+                return Err(Error::NotAllowed);
+            }
+            Ok(())
+        }
+
+        /// Apply a sensitive admin action (callers check authorization).
+        fn apply_admin_action(&mut self, action: AdminAction) -> Result<()> {
+            match action {
+                AdminAction::SetUpdateFee(update_fee) => {
+                    self.update_fee = update_fee;
+                    self.env().emit_event(UpdateFeeChanged { update_fee });
+                }
+                AdminAction::SetRegistrationFee(granularity, fee) => {
+                    match granularity {
+                        Granularity::ProductClass => self.registration_fees.product_class = fee,
+                        Granularity::Batch => self.registration_fees.batch = fee,
+                        Granularity::Item => self.registration_fees.item = fee,
+                    }
+                    self.env()
+                        .emit_event(RegistrationFeeChanged { granularity, fee });
+                }
+                AdminAction::SetAllowlistMode(enabled) => {
+                    self.allowlist_mode = enabled;
+                    self.env().emit_event(AllowlistModeChanged { enabled });
+                }
+                AdminAction::AddTrustedIssuer(issuer) => {
+                    self.trusted_issuers.insert(issuer, &());
+                    self.env().emit_event(TrustedIssuerAdded { issuer });
+                }
+                AdminAction::RemoveTrustedIssuer(issuer) => {
+                    if self.trusted_issuers.take(issuer).is_none() {
+                        return Err(Error::NotAllowed);
+                    }
+                    self.env().emit_event(TrustedIssuerRemoved { issuer });
+                }
+                AdminAction::SetTimelockDelay(delay) => {
+                    self.timelock_delay = delay;
+                    self.env().emit_event(TimelockDelayChanged { delay });
+                }
+                AdminAction::SetAdminThreshold(threshold) => {
+                    if threshold == 0 || threshold > self.admin_count {
+                        return Err(Error::InvalidInput);
+                    }
+                    self.admin_threshold = threshold;
+                    self.env().emit_event(AdminThresholdChanged { threshold });
+                }
+                AdminAction::GrantRole(role, account) => {
+                    if self.roles.insert((role, account), &()).is_none() && role == Role::Admin {
+                        self.admin_count += 1;
+                    }
+                    self.env().emit_event(RoleGranted {
+                        role,
+                        account,
+                        granted_by: self.env().caller(),
+                    });
+                }
+                AdminAction::RevokeRole(role, account) => {
+                    self.remove_role(role, account)?;
+                }
+                AdminAction::SetIssuerRegistry(registry) => {
+                    self.issuer_registry = registry;
+                    self.env().emit_event(IssuerRegistryChanged { registry });
+                }
+                AdminAction::SetRecipientAllowlist(enabled) => {
+                    self.set_recipient_allowlist_for(None, enabled);
+                }
+                AdminAction::SetAllowedRecipient(recipient, allowed) => {
+                    self.set_allowed_recipient_for(None, recipient, allowed);
+                }
+                AdminAction::SetFundAddress(fund_address) => {
+                    self.fund_address = fund_address;
+                    self.env().emit_event(FundAddressChanged { fund_address });
+                }
+                AdminAction::FreezeToken(token_id) => {
+                    if !self.passports.contains(token_id) {
+                        return Err(Error::TokenNotFound);
+                    }
+                    self.ensure_not_draft(token_id)?;
+                    if self.frozen.insert(token_id, &()).is_some() {
+                        return Err(Error::TokenFrozen);
+                    }
+                    let event_seq = self.next_event_seq(token_id);
+                    self.env().emit_event(TokenFrozen {
+                        token_id,
+                        by: self.env().caller(),
+                        event_seq,
+                    });
+                }
+                AdminAction::UnfreezeToken(token_id) => {
+                    if self.frozen.take(token_id).is_none() {
+                        return Err(Error::NotAllowed);
+                    }
+                    let event_seq = self.next_event_seq(token_id);
+                    self.env().emit_event(TokenUnfrozen {
+                        token_id,
+                        by: self.env().caller(),
+                        event_seq,
+                    });
+                }
+                AdminAction::SetMaxVersions(max_versions) => {
+                    self.max_versions = max_versions;
+                    self.env().emit_event(MaxVersionsChanged { max_versions });
+                }
+                AdminAction::SetUpdateTransferCooldown(blocks) => {
+                    self.update_transfer_cooldown = blocks;
+                    self.env()
+                        .emit_event(UpdateTransferCooldownChanged { blocks });
+                }
+                AdminAction::StartMigration(migration) => {
+                    if self.migration.is_some() {
+                        return Err(Error::NotAllowed);
+                    }
+
+                    self.migration = Some(MigrationState {
+                        migration,
+                        cursor: 0,
+                        processed: 0,
+                        started_at: self.env().block_number(),
+                        live: 0,
+                        owned: 0,
+                    });
+
+                    self.env().emit_event(MigrationStarted {
+                        migration,
+                        by: self.env().caller(),
+                    });
+                }
+                AdminAction::AddCertifier(certifier) => {
+                    self.certifiers.insert(certifier, &());
+                    self.env().emit_event(CertifierAdded { certifier });
+                }
+                AdminAction::RemoveCertifier(certifier) => {
+                    if self.certifiers.take(certifier).is_none() {
+                        return Err(Error::NotAllowed);
+                    }
+                    self.env().emit_event(CertifierRemoved { certifier });
+                }
+                AdminAction::AddServiceProvider(provider, account) => {
+                    self.service_providers.insert((provider, account), &());
+                    self.env()
+                        .emit_event(ServiceProviderAdded { account, provider });
+                }
+                AdminAction::RemoveServiceProvider(provider, account) => {
+                    if self.service_providers.take((provider, account)).is_none() {
+                        return Err(Error::NotAllowed);
+                    }
+                    self.env()
+                        .emit_event(ServiceProviderRemoved { account, provider });
+                }
+                AdminAction::SetStrictUriMode(enabled) => {
+                    self.strict_uri_mode = enabled;
+                    self.env().emit_event(StrictUriModeChanged { enabled });
+                }
+                AdminAction::SetTokenBaseUri(base_uri) => {
+                    if base_uri.as_ref().is_some_and(|uri| uri.is_empty()) {
+                        return Err(Error::InvalidInput);
+                    }
+                    self.token_base_uri = base_uri.clone();
+                    self.env().emit_event(TokenBaseUriChanged { base_uri });
+                }
+                AdminAction::BlacklistAccount(account) => {
+                    if self.blacklist.insert(account, &()).is_some() {
+                        return Err(Error::AccountBlacklisted);
+                    }
+                    self.env().emit_event(AccountBlacklisted {
+                        account,
+                        by: self.env().caller(),
+                    });
+                }
+                AdminAction::UnblacklistAccount(account) => {
+                    if self.blacklist.take(account).is_none() {
+                        return Err(Error::NotAllowed);
+                    }
+                    self.env().emit_event(AccountUnblacklisted {
+                        account,
+                        by: self.env().caller(),
+                    });
+                }
+                AdminAction::SetStatusPolicy(policy) => {
+                    self.status_policy = policy.clone();
+                    self.env().emit_event(StatusPolicyChanged { policy });
+                }
+                AdminAction::SetLockAuthority(authority) => {
+                    self.lock_authority = authority;
+                    self.env().emit_event(LockAuthorityChanged { authority });
+                }
+                AdminAction::SetVerboseEvents(enabled) => {
+                    self.verbose_events = enabled;
+                    self.env().emit_event(VerboseEventsChanged { enabled });
+                }
+                AdminAction::SetDepositPerByte(deposit_per_byte) => {
+                    self.deposit_per_byte = deposit_per_byte;
+                    self.env()
+                        .emit_event(DepositPerByteChanged { deposit_per_byte });
+                }
+                AdminAction::WithdrawFund(amount) => {
+                    if amount > self.fund_balance {
+                        return Err(Error::InvalidInput);
+                    }
+
+                    self.fund_balance -= amount;
+                    self.env()
+                        .transfer(self.fund_address, amount)
+                        .map_err(|_| Error::TransferFailed)?;
+
+                    self.env().emit_event(FundWithdrawn {
+                        to: self.fund_address,
+                        amount,
+                    });
+                }
+                AdminAction::SetPreClaimTerms(bond, window) => {
+                    if window == 0 {
+                        return Err(Error::InvalidInput);
+                    }
+                    self.pre_claim_bond = bond;
+                    self.pre_claim_window = window;
+                    self.env().emit_event(PreClaimTermsChanged { bond, window });
+                }
+                AdminAction::SetEpochLength(epoch_length) => {
+                    if epoch_length == 0 {
+                        return Err(Error::InvalidInput);
+                    }
+
+                    if self.next_token_id != 0 {
+                        return Err(Error::NotAllowed);
+                    }
+
+                    self.epoch_length = epoch_length;
+                    self.env().emit_event(EpochLengthChanged { epoch_length });
+                }
+                AdminAction::SetRecoveryChallengePeriod(blocks) => {
+                    self.recovery_challenge_period = blocks;
+                    self.env()
+                        .emit_event(RecoveryChallengePeriodChanged { blocks });
+                }
+                AdminAction::SetMinUpdateInterval(token_id, blocks) => {
+                    if !self.passports.contains(token_id) {
+                        return Err(Error::TokenNotFound);
+                    }
+                    self.ensure_not_draft(token_id)?;
+
+                    if blocks == 0 {
+                        self.min_update_interval.remove(token_id);
+                    } else {
+                        self.min_update_interval.insert(token_id, &blocks);
+                    }
+                    let event_seq = self.next_event_seq(token_id);
+                    self.env().emit_event(MinUpdateIntervalChanged {
+                        token_id,
+                        blocks,
+                        event_seq,
+                    });
+                }
+                AdminAction::PruneCustodyHistory(token_id, keep_recent) => {
+                    self.prune_custody(token_id, keep_recent)?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Cross-contract call to `IssuerRegistry::is_registered`.
+        fn registry_has_issuer(&self, registry: Address, issuer: Address) -> Result<bool> {
+            use ink::env::call::{build_call, ExecutionInput, Selector};
+
+            build_call::<ink::env::DefaultEnvironment>()
+                .call(registry)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(IS_REGISTERED_SELECTOR)).push_arg(issuer),
+                )
+                .returns::<bool>()
+                .try_invoke()
+                .map_err(|_| Error::IssuerRegistryUnavailable)?
+                .map_err(|_| Error::IssuerRegistryUnavailable)
+        }
+
+        /// Cross-contract call to the recipient's `on_dpp_received` hook.
+        fn check_on_dpp_received(
+            &self,
+            to: Address,
+            from: Address,
+            token_id: u128,
+            data: ink::prelude::vec::Vec<u8>,
+        ) -> Result<()> {
+            use ink::env::call::{build_call, ExecutionInput, Selector};
+
+            let response = build_call::<ink::env::DefaultEnvironment>()
+                .call(to)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ON_DPP_RECEIVED_SELECTOR))
+                        .push_arg(self.env().caller())
+                        .push_arg(from)
+                        .push_arg(token_id)
+                        .push_arg(data),
+                )
+                .returns::<[u8; 4]>()
+                .try_invoke()
+                .map_err(|_| Error::ReceiverRejected)?
+                .map_err(|_| Error::ReceiverRejected)?;
+
+            if response != ON_DPP_RECEIVED_SELECTOR {
+                return Err(Error::ReceiverRejected);
+            }
+            Ok(())
+        }
+
+        fn ensure_not_paused(&self) -> Result<()> {
+            if self.paused {
+                return Err(Error::ContractPaused);
+            }
+            Ok(())
+        }
+
+        fn remove_role(&mut self, role: Role, account: Address) -> Result<()> {
+            if !self.has_role(role, account)
+                || (role == Role::Admin && self.admin_count <= self.admin_threshold)
+            {
+                return Err(Error::NotAllowed);
+            }
+
+            self.roles.remove((role, account));
+            if role == Role::Admin {
+                self.admin_count -= 1;
+            }
+            self.env().emit_event(RoleRevoked {
+                role,
+                account,
+                revoked_by: self.env().caller(),
+            });
+
+            Ok(())
+        }
+
+        fn emit_transfer(&mut self, from: Option<Address>, to: Option<Address>, token_id: u128) {
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(Transfer {
+                from,
+                to,
+                token_id,
+                event_seq,
+            });
+        }
+
+        fn approved_or_owner(&self, caller: Address, token_id: u128, owner: Address) -> bool {
+            caller == owner
+                || (self.token_approvals.get(token_id) == Some(caller)
+                    && !self.blacklist.contains(caller))
+                || self.is_approved_for_all(owner, caller)
+        }
+
+        fn clear_approval(&mut self, token_id: u128) {
+            self.token_approvals.remove(token_id);
+        }
+
+        /// Clear a token approval on request, emitting `ApprovalCleared` if one was set.
+        fn revoke_approval(&mut self, owner: Address, token_id: u128) {
+            let Some(approved) = self.token_approvals.take(token_id) else {
+                return;
+            };
+            let event_seq = self.next_event_seq(token_id);
+            self.env().emit_event(ApprovalCleared {
+                owner,
+                approved,
+                token_id,
+                event_seq,
+            });
+        }
+
+        fn remove_token_from(&mut self, from: &Address, token_id: u128) -> Result<()> {
+            if !self.token_owner.contains(token_id) {
+                return Err(Error::TokenNotFound);
+            }
+
+            let count = self
+                .owned_tokens_count
+                .get(*from)
+                .unwrap_or(0)
+                .checked_sub(1)
+                .ok_or(Error::InvalidInput)?;
+            self.owned_tokens_count.insert(*from, &count);
+            self.token_owner.remove(token_id);
+            self.owned_total -= 1;
+            self.recount_scanned(token_id, |state| {
+                state.owned = state.owned.saturating_sub(1)
+            });
+            self.unindex_owned(*from, token_id);
+
+            Ok(())
+        }
+
+        fn add_token_to(&mut self, to: &Address, token_id: u128) -> Result<()> {
+            if self.token_owner.contains(token_id) {
+                return Err(Error::InvalidInput);
+            }
+
+            let count = self
+                .owned_tokens_count
+                .get(*to)
+                .unwrap_or(0)
+                .checked_add(1)
+                .ok_or(Error::InvalidInput)?;
+            self.owned_tokens_count.insert(*to, &count);
+            self.token_owner.insert(token_id, to);
+            self.owned_total += 1;
+            self.recount_scanned(token_id, |state| state.owned += 1);
+
+            Ok(())
+        }
+
+        /// Apply a counter change to a running `RecountTokens` tally when the
+        /// token was already scanned; IDs at or past the cursor are counted by the scan
+        fn recount_scanned(&mut self, token_id: u128, update: impl FnOnce(&mut MigrationState)) {
+            if let Some(state) = self.migration.as_mut() {
+                if state.migration == Migration::RecountTokens && token_id < state.cursor {
+                    update(state);
+                }
+            }
+        }
+
+        // Enumeration index helpers. Adding is idempotent and removing skips
+        // unindexed tokens, so both tolerate a partially backfilled index.
+
+        fn index_token(&mut self, token_id: u128) {
+            if self.all_tokens_index.contains(token_id) {
+                return;
+            }
+            self.all_tokens.insert(self.all_tokens_len, &token_id);
+            self.all_tokens_index.insert(token_id, &self.all_tokens_len);
+            self.all_tokens_len += 1;
+        }
+
+        fn unindex_token(&mut self, token_id: u128) {
+            let Some(index) = self.all_tokens_index.take(token_id) else {
+                return;
+            };
+            let last = self.all_tokens_len - 1;
+            if index != last {
+                if let Some(moved) = self.all_tokens.get(last) {
+                    self.all_tokens.insert(index, &moved);
+                    self.all_tokens_index.insert(moved, &index);
+                }
+            }
+            self.all_tokens.remove(last);
+            self.all_tokens_len = last;
+        }
+
+        fn index_owned(&mut self, owner: Address, token_id: u128) {
+            if self.owned_tokens_index.contains(token_id) {
+                return;
+            }
+            let len = self.owned_tokens_len.get(owner).unwrap_or(0);
+            self.owned_tokens.insert((owner, len), &token_id);
+            self.owned_tokens_index.insert(token_id, &len);
+            self.owned_tokens_len.insert(owner, &(len + 1));
+        }
+
+        fn unindex_owned(&mut self, owner: Address, token_id: u128) {
+            let Some(index) = self.owned_tokens_index.take(token_id) else {
+                return;
+            };
+            let last = self.owned_tokens_len.get(owner).unwrap_or(1) - 1;
+            if index != last {
+                if let Some(moved) = self.owned_tokens.get((owner, last)) {
+                    self.owned_tokens.insert((owner, index), &moved);
+                    self.owned_tokens_index.insert(moved, &index);
+                }
+            }
+            self.owned_tokens.remove((owner, last));
+            self.owned_tokens_len.insert(owner, &last);
+        }
+    }
+
+    // PSP34 interface (alongside the ERC-721 style messages above)
+
+    impl From<Error> for PSP34Error {
+        fn from(error: Error) -> Self {
+            match error {
+                Error::NotApproved => PSP34Error::NotApproved,
+                Error::TokenNotFound => PSP34Error::TokenNotExists,
+                Error::ReceiverRejected => {
+                    PSP34Error::SafeTransferCheckFailed(String::from("on_dpp_received"))
+                }
+                other => PSP34Error::Custom(ink::prelude::format!("{:?}", other)),
+            }
+        }
+    }
+
+    impl PSP34 for DppContractV2 {
         #[ink(message)]
-        pub fn get_version(&self, token_id: u128, version: u32) -> Option<VersionHistory> {
-            self.version_history.get((token_id, version))
+        fn collection_id(&self) -> Id {
+            Id::Bytes(self.env().address().0.to_vec())
+        }
+
+        #[ink(message)]
+        fn balance_of(&self, owner: Address) -> u32 {
+            u32::try_from(DppContractV2::balance_of(self, owner)).unwrap_or(u32::MAX)
+        }
+
+        #[ink(message)]
+        fn owner_of(&self, id: Id) -> Option<Address> {
+            DppContractV2::owner_of(self, id.to_token_id()?)
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: Address, operator: Address, id: Option<Id>) -> bool {
+            if self.is_approved_for_all(owner, operator) {
+                return true;
+            }
+            id.and_then(|id| id.to_token_id()).is_some_and(|token_id| {
+                self.token_owner.get(token_id) == Some(owner)
+                    && self.token_approvals.get(token_id) == Some(operator)
+            })
+        }
+
+        #[ink(message)]
+        fn approve(
+            &mut self,
+            operator: Address,
+            id: Option<Id>,
+            approved: bool,
+        ) -> core::result::Result<(), PSP34Error> {
+            let caller = self.env().caller();
+            if operator == caller {
+                return Err(PSP34Error::SelfApprove);
+            }
+
+            let Some(id) = id else {
+                return Ok(self.set_approval_for_all(operator, approved)?);
+            };
+            let token_id = id.to_token_id().ok_or(PSP34Error::TokenNotExists)?;
+
+            if approved {
+                return Ok(DppContractV2::approve(self, operator, token_id)?);
+            }
+
+            let owner = self
+                .token_owner
+                .get(token_id)
+                .ok_or(PSP34Error::TokenNotExists)?;
+            if caller != owner && !self.is_approved_for_all(owner, caller) {
+                return Err(PSP34Error::NotApproved);
+            }
+            if self.token_approvals.get(token_id) == Some(operator) {
+                self.revoke_approval(owner, token_id);
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn transfer(
+            &mut self,
+            to: Address,
+            id: Id,
+            _data: ink::prelude::vec::Vec<u8>,
+        ) -> core::result::Result<(), PSP34Error> {
+            let token_id = id.to_token_id().ok_or(PSP34Error::TokenNotExists)?;
+            let owner = self
+                .token_owner
+                .get(token_id)
+                .ok_or(PSP34Error::TokenNotExists)?;
+            Ok(self.transfer_token_from(&owner, &to, token_id)?)
+        }
+
+        #[ink(message)]
+        fn total_supply(&self) -> u128 {
+            DppContractV2::total_supply(self)
+        }
+    }
+
+    // Unit tests
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[ink::test]
+        fn new_works() {
+            let contract = DppContractV2::new();
+            assert_eq!(contract.next_token_id(), 0);
+        }
+
+        #[ink::test]
+        fn register_passport_works() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            let dataset_uri =
+                String::from("ipfs://bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxqvyb3m");
+            let payload_hash = [0u8; 32];
+            let dataset_type = String::from("application/vc+jwt");
+
+            ink::env::test::set_caller(accounts.alice);
+
+            let token_id = contract
+                .register_passport(
+                    dataset_uri.clone(),
+                    payload_hash,
+                    dataset_type.clone(),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(token_id, 0);
+
+            let record = contract.get_passport(token_id).unwrap();
+            assert_eq!(record.token_id, token_id);
+            assert_eq!(record.dataset_uri, dataset_uri);
+            assert_eq!(record.payload_hash, payload_hash);
+            assert_eq!(record.dataset_type, dataset_type);
+            assert_eq!(record.version, 1);
+            assert_eq!(record.status, PassportStatus::Active);
+            assert_eq!(record.granularity, Granularity::Batch);
+            assert_eq!(record.subject_id_hash, None);
+
+            assert_eq!(contract.owner_of(token_id), Some(accounts.alice));
+            assert_eq!(contract.balance_of(accounts.alice), 1);
+        }
+
+        #[ink::test]
+        fn register_with_subject_id_hash_works() {
+            let mut contract = DppContractV2::new();
+            let subject_id_hash = [42u8; 32];
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    Some(subject_id_hash),
+                    None,
+                )
+                .unwrap();
+
+            let record = contract.get_passport(token_id).unwrap();
+            assert_eq!(record.subject_id_hash, Some(subject_id_hash));
+            assert_eq!(record.granularity, Granularity::Item);
+        }
+
+        #[ink::test]
+        fn register_multiple_passports_works() {
+            let mut contract = DppContractV2::new();
+
+            let token_id_0 = contract
+                .register_passport(
+                    String::from("ipfs://cid0"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::ProductClass,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let token_id_1 = contract
+                .register_passport(
+                    String::from("ipfs://cid1"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(token_id_0, 0);
+            assert_eq!(token_id_1, 1);
+
+            let record_0 = contract.get_passport(token_id_0).unwrap();
+            let record_1 = contract.get_passport(token_id_1).unwrap();
+
+            assert_eq!(record_0.dataset_uri, String::from("ipfs://cid0"));
+            assert_eq!(record_0.granularity, Granularity::ProductClass);
+            assert_eq!(record_1.dataset_uri, String::from("ipfs://cid1"));
+            assert_eq!(record_1.granularity, Granularity::Batch);
+        }
+
+        #[ink::test]
+        fn register_with_empty_uri_fails() {
+            let mut contract = DppContractV2::new();
+
+            let result = contract.register_passport(
+                String::from(""),
+                [0u8; 32],
+                String::from("application/vc+jwt"),
+                Granularity::Batch,
+                None,
+                None,
+            );
+
+            assert_eq!(result, Err(Error::InvalidInput));
+        }
+
+        #[ink::test]
+        fn register_with_empty_type_fails() {
+            let mut contract = DppContractV2::new();
+
+            let result = contract.register_passport(
+                String::from("ipfs://cid"),
+                [0u8; 32],
+                String::from(""),
+                Granularity::Batch,
+                None,
+                None,
+            );
+
+            assert_eq!(result, Err(Error::InvalidInput));
+        }
+
+        #[ink::test]
+        fn update_dataset_increments_version() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://old"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://new"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let record = contract.get_passport(token_id).unwrap();
+            assert_eq!(record.version, 2);
+            assert_eq!(record.dataset_uri, String::from("ipfs://new"));
+            assert_eq!(record.payload_hash, [1u8; 32]);
+            // Granularity should remain unchanged
+            assert_eq!(record.granularity, Granularity::Batch);
+        }
+
+        #[ink::test]
+        fn only_issuer_can_update() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            // Try to update from different account
+            ink::env::test::set_caller(accounts.bob);
+            let result = contract.update_dataset(
+                token_id,
+                String::from("ipfs://new"),
+                [1u8; 32],
+                String::from("application/vc+jwt"),
+                None,
+                None,
+                None,
+            );
+
+            assert_eq!(result, Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn update_nonexistent_token_fails() {
+            let mut contract = DppContractV2::new();
+
+            let result = contract.update_dataset(
+                999,
+                String::from("ipfs://new"),
+                [1u8; 32],
+                String::from("application/vc+jwt"),
+                None,
+                None,
+                None,
+            );
+
+            assert_eq!(result, Err(Error::TokenNotFound));
+        }
+
+        #[ink::test]
+        fn revoke_prevents_updates() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::ProductClass,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract
+                .revoke_passport(token_id, Some(String::from("test reason")))
+                .unwrap();
+
+            let result = contract.update_dataset(
+                token_id,
+                String::from("ipfs://new"),
+                [1u8; 32],
+                String::from("application/vc+jwt"),
+                None,
+                None,
+                None,
+            );
+
+            assert_eq!(result, Err(Error::PassportRevoked));
+        }
+
+        #[ink::test]
+        fn revoke_works() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract
+                .revoke_passport(token_id, Some(String::from("product recalled")))
+                .unwrap();
+
+            let record = contract.get_passport(token_id).unwrap();
+            assert_eq!(record.status, PassportStatus::Revoked);
+        }
+
+        #[ink::test]
+        fn only_issuer_can_revoke() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            // Try to revoke from different account
+            ink::env::test::set_caller(accounts.bob);
+            let result = contract.revoke_passport(token_id, Some(String::from("reason")));
+
+            assert_eq!(result, Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn transfer_works() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(contract.owner_of(token_id), Some(accounts.alice));
+
+            contract.transfer(accounts.bob, token_id).unwrap();
+            assert_eq!(contract.owner_of(token_id), Some(accounts.bob));
+            assert_eq!(contract.balance_of(accounts.alice), 0);
+            assert_eq!(contract.balance_of(accounts.bob), 1);
+        }
+
+        #[ink::test]
+        fn approve_and_transfer_from_works() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract.approve(accounts.charlie, token_id).unwrap();
+            assert_eq!(contract.get_approved(token_id), Some(accounts.charlie));
+
+            ink::env::test::set_caller(accounts.charlie);
+            contract
+                .transfer_from(accounts.alice, accounts.bob, token_id)
+                .unwrap();
+            assert_eq!(contract.owner_of(token_id), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn revoked_passport_cannot_transfer() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract.revoke_passport(token_id, None).unwrap();
+            let result = contract.transfer(accounts.bob, token_id);
+            assert_eq!(result, Err(Error::PassportRevoked));
+        }
+
+        #[ink::test]
+        fn cannot_revoke_already_revoked() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract.revoke_passport(token_id, None).unwrap();
+
+            let result = contract.revoke_passport(token_id, None);
+            assert_eq!(result, Err(Error::AlreadyRevoked));
+        }
+
+        #[ink::test]
+        fn revoke_nonexistent_token_fails() {
+            let mut contract = DppContractV2::new();
+
+            let result = contract.revoke_passport(999, None);
+            assert_eq!(result, Err(Error::TokenNotFound));
+        }
+
+        #[ink::test]
+        fn all_granularity_levels_work() {
+            let mut contract = DppContractV2::new();
+
+            // ProductClass
+            let token_id_0 = contract
+                .register_passport(
+                    String::from("ipfs://model"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::ProductClass,
+                    Some([10u8; 32]),
+                    None,
+                )
+                .unwrap();
+
+            // Batch
+            let token_id_1 = contract
+                .register_passport(
+                    String::from("ipfs://batch"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    Some([20u8; 32]),
+                    None,
+                )
+                .unwrap();
+
+            // Item
+            let token_id_2 = contract
+                .register_passport(
+                    String::from("ipfs://item"),
+                    [2u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    Some([30u8; 32]),
+                    None,
+                )
+                .unwrap();
+
+            let record_0 = contract.get_passport(token_id_0).unwrap();
+            let record_1 = contract.get_passport(token_id_1).unwrap();
+            let record_2 = contract.get_passport(token_id_2).unwrap();
+
+            assert_eq!(record_0.granularity, Granularity::ProductClass);
+            assert_eq!(record_1.granularity, Granularity::Batch);
+            assert_eq!(record_2.granularity, Granularity::Item);
+
+            assert_eq!(record_0.subject_id_hash, Some([10u8; 32]));
+            assert_eq!(record_1.subject_id_hash, Some([20u8; 32]));
+            assert_eq!(record_2.subject_id_hash, Some([30u8; 32]));
+        }
+
+        #[ink::test]
+        fn get_nonexistent_passport_returns_none() {
+            let contract = DppContractV2::new();
+            assert_eq!(contract.get_passport(999), None);
+        }
+
+        #[ink::test]
+        fn update_with_empty_uri_fails() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let result = contract.update_dataset(
+                token_id,
+                String::from(""), // Empty URI
+                [1u8; 32],
+                String::from("application/vc+jwt"),
+                None,
+                None,
+                None,
+            );
+
+            assert_eq!(result, Err(Error::InvalidInput));
+        }
+
+        #[ink::test]
+        fn update_with_empty_type_fails() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let result = contract.update_dataset(
+                token_id,
+                String::from("ipfs://new"),
+                [1u8; 32],
+                String::from(""), // Empty type
+                None,
+                None,
+                None,
+            );
+
+            assert_eq!(result, Err(Error::InvalidInput));
+        }
+
+        #[ink::test]
+        fn granularity_remains_immutable_after_update() {
+            let mut contract = DppContractV2::new();
+
+            // Register with ProductClass
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://old"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::ProductClass,
+                    Some([42u8; 32]),
+                    None,
+                )
+                .unwrap();
+
+            // Update dataset
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://new"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    Some([99u8; 32]),
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let record = contract.get_passport(token_id).unwrap();
+
+            // Granularity should NOT have changed
+            assert_eq!(record.granularity, Granularity::ProductClass);
+
+            // But other fields should have updated
+            assert_eq!(record.dataset_uri, String::from("ipfs://new"));
+            assert_eq!(record.payload_hash, [1u8; 32]);
+            assert_eq!(record.subject_id_hash, Some([99u8; 32]));
+            assert_eq!(record.version, 2);
+        }
+
+        #[ink::test]
+        fn update_with_diff_reference_is_stored_in_history() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    Some(String::from("ipfs://diff-v1-v2")),
+                    Some([7u8; 32]),
+                )
+                .unwrap();
+
+            let v1 = contract.get_version(token_id, 1).unwrap();
+            assert_eq!(v1.diff_uri, None);
+            assert_eq!(v1.diff_hash, None);
+
+            let history = contract.get_version_history(token_id);
+            assert_eq!(history[1].diff_uri, Some(String::from("ipfs://diff-v1-v2")));
+            assert_eq!(history[1].diff_hash, Some([7u8; 32]));
+        }
+
+        #[ink::test]
+        fn update_with_incomplete_diff_reference_fails() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let result = contract.update_dataset(
+                token_id,
+                String::from("ipfs://v2"),
+                [1u8; 32],
+                String::from("application/vc+jwt"),
+                None,
+                Some(String::from("ipfs://diff")),
+                None,
+            );
+
+            assert_eq!(result, Err(Error::InvalidInput));
+        }
+
+        #[ink::test]
+        fn max_versions_blocks_further_updates() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract.set_max_versions(2).unwrap();
+            assert_eq!(contract.max_versions(), 2);
+
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let result = contract.update_dataset(
+                token_id,
+                String::from("ipfs://v3"),
+                [2u8; 32],
+                String::from("application/vc+jwt"),
+                None,
+                None,
+                None,
+            );
+            assert_eq!(result, Err(Error::MaxVersionsReached));
+        }
+
+        #[ink::test]
+        fn supersede_links_old_and_new_tokens() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let old_token = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.transfer(accounts.bob, old_token).unwrap();
+
+            let new_token = contract
+                .supersede_passport(
+                    old_token,
+                    String::from("ipfs://successor"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(contract.superseded_by(old_token), Some(new_token));
+            assert_eq!(contract.supersedes(new_token), Some(old_token));
+            assert_eq!(contract.owner_of(new_token), Some(accounts.bob));
+            let record = contract.get_passport(new_token).unwrap();
+            assert_eq!(record.issuer, accounts.alice);
+            assert_eq!(record.granularity, Granularity::Batch);
+
+            let result = contract.update_dataset(
+                old_token,
+                String::from("ipfs://v2"),
+                [2u8; 32],
+                String::from("application/vc+jwt"),
+                None,
+                None,
+                None,
+            );
+            assert_eq!(result, Err(Error::AlreadySuperseded));
+        }
+
+        #[ink::test]
+        fn only_admin_can_set_max_versions() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.set_max_versions(5), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn session_key_can_update_tokens_in_scope() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract
+                .add_session_key(accounts.django, token_id, token_id, 100)
+                .unwrap();
+
+            ink::env::test::set_caller(accounts.django);
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let v2 = contract.get_version(token_id, 2).unwrap();
+            assert_eq!(v2.updated_by, accounts.django);
+        }
+
+        #[ink::test]
+        fn session_key_outside_scope_or_revoked_is_rejected() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract
+                .add_session_key(accounts.django, token_id + 1, token_id + 10, 100)
+                .unwrap();
+
+            ink::env::test::set_caller(accounts.django);
+            let result = contract.update_dataset(
+                token_id,
+                String::from("ipfs://v2"),
+                [1u8; 32],
+                String::from("application/vc+jwt"),
+                None,
+                None,
+                None,
+            );
+            assert_eq!(result, Err(Error::Unauthorized));
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.revoke_session_key(accounts.django).unwrap();
+            assert_eq!(contract.get_session_key(accounts.django), None);
+        }
+
+        #[ink::test]
+        fn epoch_counters_track_registrations_per_period() {
+            let mut contract = DppContractV2::new();
+            contract.set_epoch_length(10).unwrap();
+            assert_eq!(ink::env::test::recorded_events().into_iter().count(), 1);
+
+            contract
+                .register_passport(
+                    String::from("ipfs://a"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            for _ in 0..10 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://b"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.revoke_passport(token_id, None).unwrap();
+
+            assert_eq!(contract.registered_between(0, 9), 1);
+            assert_eq!(contract.registered_between(0, 19), 2);
+            let second_epoch = contract.get_epoch_counters(1);
+            assert_eq!(second_epoch.registered, 1);
+            assert_eq!(second_epoch.revoked, 1);
+
+            // Epoch length is frozen after the first registration
+            assert_eq!(contract.set_epoch_length(5), Err(Error::NotAllowed));
+        }
+
+        #[ink::test]
+        fn update_fee_is_enforced_and_accrues_to_fund() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract.set_update_fee(U256::from(100)).unwrap();
+
+            ink::env::test::set_value_transferred(U256::from(50));
+            let result = contract.update_dataset(
+                token_id,
+                String::from("ipfs://v2"),
+                [1u8; 32],
+                String::from("application/vc+jwt"),
+                None,
+                None,
+                None,
+            );
+            assert_eq!(result, Err(Error::InsufficientFee));
+
+            ink::env::test::set_value_transferred(U256::from(100));
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.fund_balance(), U256::from(100));
+        }
+
+        #[ink::test]
+        fn only_admin_can_withdraw_fund() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.withdraw_fund(U256::zero()),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn strict_uri_mode_rejects_malformed_cids() {
+            let mut contract = DppContractV2::new();
+            contract.set_strict_uri_mode(true).unwrap();
+
+            let result = contract.register_passport(
+                String::from("ipfs://cid"),
+                [0u8; 32],
+                String::from("application/vc+jwt"),
+                Granularity::Item,
+                None,
+                None,
+            );
+            assert_eq!(result, Err(Error::InvalidUri));
+
+            let token_id = contract
+                .register_passport(
+                    String::from(
+                        "ipfs://bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxqvyb3m",
+                    ),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG/vc.jwt"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+        }
+
+        #[test]
+        fn cid_syntax_checks() {
+            assert!(is_valid_cid(
+                "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"
+            ));
+            assert!(is_valid_cid(
+                "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxqvyb3m"
+            ));
+            // Uppercase is not valid base32 multibase 'b'
+            assert!(!is_valid_cid(
+                "bAFKREIHDWDCEFGH4DQKJV67UZCMW7OJEE6XEDZDETOJUZJEVTENXQVYB3M"
+            ));
+            // '0' is not part of the base58btc alphabet
+            assert!(!is_valid_cid(
+                "Qm0wAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"
+            ));
+            assert!(!is_valid_cid(""));
+            assert_eq!(validate_ipfs_uri("https://example.com/vc"), Ok(()));
+        }
+
+        #[ink::test]
+        fn draft_passports_hidden_from_subject_lookup() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            let subject = [5u8; 32];
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_draft(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    Some(subject),
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(contract.find_token_by_subject_id(subject), Some(token_id));
+            assert_eq!(contract.my_drafts(0, 10), ink::prelude::vec![token_id]);
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.find_token_by_subject_id(subject), None);
+            assert!(contract.my_drafts(0, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn suspend_blocks_transfers_until_resumed() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract
+                .suspend_passport(token_id, Some(String::from("under investigation")))
+                .unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Suspended
+            );
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::PassportSuspended)
+            );
+
+            // Issuer may still correct the data while suspended
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://corrected"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract.resume_passport(token_id).unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Active
+            );
+            contract.transfer(accounts.bob, token_id).unwrap();
+        }
+
+        #[ink::test]
+        fn suspend_rules_are_enforced() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(contract.resume_passport(token_id), Err(Error::NotAllowed));
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.suspend_passport(token_id, None),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.revoke_passport(token_id, None).unwrap();
+            assert_eq!(
+                contract.suspend_passport(token_id, None),
+                Err(Error::PassportRevoked)
+            );
+        }
+
+        #[ink::test]
+        fn prune_custody_history_keeps_recent_entries() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.transfer(accounts.bob, token_id).unwrap();
+            ink::env::test::set_caller(accounts.bob);
+            contract.transfer(accounts.charlie, token_id).unwrap();
+
+            assert_eq!(contract.get_custody_history(token_id).len(), 3);
+
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(contract.prune_custody_history(token_id, 1), Ok(2));
+
+            let retained = contract.get_custody_history(token_id);
+            assert_eq!(retained.len(), 1);
+            assert_eq!(retained[0].from, Some(accounts.bob));
+            assert_eq!(retained[0].to, accounts.charlie);
+
+            let checkpoint = contract.get_custody_checkpoint(token_id).unwrap();
+            assert_eq!(checkpoint.pruned_count, 2);
+            assert_ne!(checkpoint.digest, [0u8; 32]);
+
+            // Nothing left to prune
+            assert_eq!(contract.prune_custody_history(token_id, 1), Ok(0));
+        }
+
+        #[ink::test]
+        fn only_issuer_or_admin_can_prune_custody() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.prune_custody_history(token_id, 0),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn archive_makes_passport_read_only() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract.archive_passport(token_id).unwrap();
+
+            let record = contract.get_passport(token_id).unwrap();
+            assert_eq!(record.status, PassportStatus::Archived);
+            assert!(record.archived_at.is_some());
+
+            let result = contract.update_dataset(
+                token_id,
+                String::from("ipfs://new"),
+                [1u8; 32],
+                String::from("application/vc+jwt"),
+                None,
+                None,
+                None,
+            );
+            assert_eq!(result, Err(Error::PassportArchived));
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::PassportArchived)
+            );
+            assert_eq!(
+                contract.archive_passport(token_id),
+                Err(Error::PassportArchived)
+            );
+        }
+
+        #[ink::test]
+        fn only_issuer_can_archive() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.archive_passport(token_id),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn issuer_onboarding_request_and_approval() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.bob);
+            contract
+                .request_issuer_onboarding([1u8; 32], String::from("ipfs://evidence"), [2u8; 32])
+                .unwrap();
+            assert_eq!(
+                contract.request_issuer_onboarding(
+                    [1u8; 32],
+                    String::from("ipfs://evidence"),
+                    [2u8; 32]
+                ),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                contract.approve_issuer(accounts.bob),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            assert!(!contract.is_trusted_issuer(accounts.bob));
+            contract.approve_issuer(accounts.bob).unwrap();
+
+            let record = contract.get_onboarding(accounts.bob).unwrap();
+            assert_eq!(record.status, OnboardingStatus::Approved);
+            assert!(record.decided_at.is_some());
+            assert!(contract.is_trusted_issuer(accounts.bob));
+        }
+
+        #[ink::test]
+        fn rejected_issuer_can_request_again() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.bob);
+            contract
+                .request_issuer_onboarding([1u8; 32], String::from("ipfs://evidence"), [2u8; 32])
+                .unwrap();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.add_trusted_issuer(accounts.bob).unwrap();
+            contract
+                .reject_issuer(accounts.bob, Some(String::from("missing registry extract")))
+                .unwrap();
+            assert_eq!(
+                contract.reject_issuer(accounts.bob, None),
+                Err(Error::NotAllowed)
+            );
+            assert!(!contract.is_trusted_issuer(accounts.bob));
+
+            ink::env::test::set_caller(accounts.bob);
+            contract
+                .request_issuer_onboarding([1u8; 32], String::from("ipfs://evidence-v2"), [3u8; 32])
+                .unwrap();
+            assert_eq!(
+                contract.get_onboarding(accounts.bob).unwrap().status,
+                OnboardingStatus::Pending
+            );
+        }
+
+        #[ink::test]
+        fn draft_can_be_updated_but_not_transferred_until_activated() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_draft(
+                    String::from("ipfs://draft"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Draft
+            );
+
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://draft-v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::PassportDraft)
+            );
+
+            // Changes that emit per-token events wait for activation
+            assert_eq!(
+                contract.set_visibility(token_id, Visibility::Restricted),
+                Err(Error::PassportDraft)
+            );
+            assert_eq!(
+                contract.set_expiry(token_id, Some(100)),
+                Err(Error::PassportDraft)
+            );
+            assert_eq!(
+                contract.add_update_delegate(token_id, accounts.charlie),
+                Err(Error::PassportDraft)
+            );
+            assert_eq!(
+                contract.approve(accounts.bob, token_id),
+                Err(Error::PassportDraft)
+            );
+
+            contract.activate_passport(token_id).unwrap();
+            contract
+                .set_visibility(token_id, Visibility::Restricted)
+                .unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Active
+            );
+            assert!(contract.my_drafts(0, 10).is_empty());
+            assert_eq!(contract.activate_passport(token_id), Err(Error::NotAllowed));
+
+            contract.transfer(accounts.bob, token_id).unwrap();
+        }
+
+        #[ink::test]
+        fn issuer_custody_is_reported_in_summary() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [3u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            assert!(contract.issuer_holds_custody(token_id));
+            let summary = contract.get_verification_summary(token_id).unwrap();
+            assert!(summary.issuer_holds_custody);
+            assert_eq!(summary.payload_hash, [3u8; 32]);
+
+            contract.transfer(accounts.bob, token_id).unwrap();
+            assert!(!contract.issuer_holds_custody(token_id));
+            let summary = contract.get_verification_summary(token_id).unwrap();
+            assert!(!summary.issuer_holds_custody);
+            assert_eq!(summary.owner, Some(accounts.bob));
+
+            assert!(!contract.issuer_holds_custody(999));
+            assert_eq!(contract.get_verification_summary(999), None);
+        }
+
+        #[ink::test]
+        fn anchor_digest_is_emitted_without_verbose_events() {
+            let mut contract = DppContractV2::new();
+
+            // PassportRegistered + AnchorDigest + Transfer
+            contract
+                .register_passport(
+                    String::from("ipfs://a"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(ink::env::test::recorded_events().into_iter().count(), 3);
+
+            // VerboseEventsChanged
+            contract.set_verbose_events(false).unwrap();
+            assert!(!contract.verbose_events());
+
+            // AnchorDigest + Transfer
+            contract
+                .register_passport(
+                    String::from("ipfs://b"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(ink::env::test::recorded_events().into_iter().count(), 6);
+        }
+
+        #[ink::test]
+        fn status_changes_emit_status_changed_event() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            let before = ink::env::test::recorded_events().into_iter().count();
+
+            // PassportStatusChanged + PassportSuspended
+            contract
+                .suspend_passport(token_id, Some(String::from("audit")))
+                .unwrap();
+            // PassportStatusChanged + PassportRevoked
+            contract.revoke_passport(token_id, None).unwrap();
+
+            let after = ink::env::test::recorded_events().into_iter().count();
+            assert_eq!(after - before, 4);
+        }
+
+        #[ink::test]
+        fn registration_fee_depends_on_granularity() {
+            let mut contract = DppContractV2::new();
+
+            contract
+                .set_registration_fee(Granularity::Batch, U256::from(50))
+                .unwrap();
+            contract
+                .set_registration_fee(Granularity::Item, U256::from(5))
+                .unwrap();
+            assert_eq!(contract.fee_for(Granularity::Batch), U256::from(50));
+            assert_eq!(contract.fee_for(Granularity::Item), U256::from(5));
+            assert_eq!(contract.fee_for(Granularity::ProductClass), U256::zero());
+
+            ink::env::test::set_value_transferred(U256::from(5));
+            contract
+                .register_passport(
+                    String::from("ipfs://item"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let result = contract.register_passport(
+                String::from("ipfs://batch"),
+                [1u8; 32],
+                String::from("application/vc+jwt"),
+                Granularity::Batch,
+                None,
+                None,
+            );
+            assert_eq!(result, Err(Error::InsufficientFee));
+            assert_eq!(contract.fund_balance(), U256::from(5));
+        }
+
+        #[ink::test]
+        fn expired_passport_can_be_archived_by_anyone() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.set_expiry(token_id, Some(2)).unwrap();
+            assert_eq!(contract.get_passport(token_id).unwrap().expires_at, Some(2));
+
+            ink::env::test::set_caller(accounts.bob);
+            assert!(!contract.is_expired(token_id));
+            assert_eq!(contract.expire_passport(token_id), Err(Error::NotExpired));
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+
+            assert!(contract.is_expired(token_id));
+            contract.expire_passport(token_id).unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Archived
+            );
+        }
+
+        #[ink::test]
+        fn expiry_must_be_in_the_future() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(
+                contract.set_expiry(token_id, Some(0)),
+                Err(Error::InvalidInput)
+            );
+            assert_eq!(contract.expire_passport(token_id), Err(Error::NotExpired));
+        }
+
+        #[ink::test]
+        fn resolver_endpoint_falls_back_to_issuer_wide() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(contract.get_resolver_endpoint(token_id), None);
+
+            contract
+                .set_resolver_endpoint(None, [1u8; 32], ink::prelude::vec![2u8; 32])
+                .unwrap();
+            assert_eq!(
+                contract.get_resolver_endpoint(token_id).unwrap().url_hash,
+                [1u8; 32]
+            );
+
+            contract
+                .set_resolver_endpoint(Some(token_id), [3u8; 32], ink::prelude::vec![4u8; 33])
+                .unwrap();
+            assert_eq!(
+                contract.get_resolver_endpoint(token_id).unwrap().url_hash,
+                [3u8; 32]
+            );
+
+            let seq = contract.latest_event_seq(token_id);
+            contract.clear_resolver_endpoint(Some(token_id)).unwrap();
+            assert_eq!(
+                contract.get_resolver_endpoint(token_id).unwrap().url_hash,
+                [1u8; 32]
+            );
+            assert_eq!(contract.latest_event_seq(token_id), seq + 1);
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_resolver_endpoint(
+                    Some(token_id),
+                    [5u8; 32],
+                    ink::prelude::vec![6u8; 32]
+                ),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn timed_suspension_lapses_and_can_be_lifted_by_anyone() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.suspend_until(token_id, 2).unwrap();
+            assert_eq!(contract.get_suspended_until(token_id), Some(2));
+            assert_eq!(
+                contract.effective_status(token_id),
+                Some(PassportStatus::Suspended)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.lift_expired_suspension(token_id),
+                Err(Error::NotAllowed)
+            );
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+
+            assert_eq!(
+                contract.effective_status(token_id),
+                Some(PassportStatus::Active)
+            );
+            contract.lift_expired_suspension(token_id).unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Active
+            );
+            assert_eq!(contract.get_suspended_until(token_id), None);
+        }
+
+        #[ink::test]
+        fn revocation_info_absent_for_active_passport() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(contract.get_revocation_info(token_id), None);
+        }
+
+        #[ink::test]
+        fn revocation_info_records_reason_and_revoker() {
+            let mut contract = DppContractV2::new();
+
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract
+                .revoke_passport(token_id, Some(String::from("product recalled")))
+                .unwrap();
+
+            let record = contract.get_passport(token_id).unwrap();
+            let info = contract.get_revocation_info(token_id).unwrap();
+            assert_eq!(info.reason, Some(String::from("product recalled")));
+            assert_eq!(info.revoked_by, record.issuer);
+            assert_eq!(info.revoked_at, record.updated_at);
+        }
+
+        #[ink::test]
+        fn revoke_batch_reports_per_token_results() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let mut token_ids = ink::prelude::vec::Vec::new();
+            for i in 0..3u8 {
+                token_ids.push(
+                    contract
+                        .register_passport(
+                            String::from("ipfs://item"),
+                            [i; 32],
+                            String::from("application/vc+jwt"),
+                            Granularity::Item,
+                            None,
+                            None,
+                        )
+                        .unwrap(),
+                );
+            }
+            contract.revoke_passport(token_ids[1], None).unwrap();
+            token_ids.push(999);
+
+            let results = contract
+                .revoke_batch(token_ids.clone(), Some(String::from("batch recall")))
+                .unwrap();
+
+            assert_eq!(results.len(), 4);
+            assert_eq!(results[0].error, None);
+            assert_eq!(results[1].error, Some(Error::AlreadyRevoked));
+            assert_eq!(results[2].error, None);
+            assert_eq!(results[3].error, Some(Error::TokenNotFound));
+            assert_eq!(
+                contract.get_passport(token_ids[2]).unwrap().status,
+                PassportStatus::Revoked
+            );
+        }
+
+        #[ink::test]
+        fn revoke_batch_is_bounded() {
+            let mut contract = DppContractV2::new();
+
+            assert_eq!(
+                contract.revoke_batch(ink::prelude::vec::Vec::new(), None),
+                Err(Error::InvalidInput)
+            );
+            let too_many: ink::prelude::vec::Vec<u128> = (0..=MAX_BATCH_SIZE as u128).collect();
+            assert_eq!(
+                contract.revoke_batch(too_many, None),
+                Err(Error::InvalidInput)
+            );
+        }
+
+        #[ink::test]
+        fn recovery_reclaims_tokens_after_challenge_period() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            contract.set_recovery_challenge_period(2).unwrap();
+            assert_eq!(ink::env::test::recorded_events().into_iter().count(), 1);
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.set_owner_recovery(accounts.eve).unwrap();
+
+            ink::env::test::set_caller(accounts.eve);
+            contract
+                .initiate_recovery(accounts.alice, accounts.frank)
+                .unwrap();
+            assert_eq!(
+                contract.execute_recovery(accounts.alice, ink::prelude::vec![token_id]),
+                Err(Error::ChallengePeriodActive)
+            );
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+
+            let results = contract
+                .execute_recovery(accounts.alice, ink::prelude::vec![token_id])
+                .unwrap();
+            assert_eq!(results[0].error, None);
+            assert_eq!(contract.owner_of(token_id), Some(accounts.frank));
+            // Issuer authority is unaffected
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().issuer,
+                accounts.alice
+            );
+        }
+
+        #[ink::test]
+        fn owner_can_cancel_pending_recovery() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_owner_recovery(accounts.eve).unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.initiate_recovery(accounts.alice, accounts.bob),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.eve);
+            contract
+                .initiate_recovery(accounts.alice, accounts.frank)
+                .unwrap();
+            assert!(contract.get_pending_recovery(accounts.alice).is_some());
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.cancel_recovery().unwrap();
+            assert_eq!(contract.get_pending_recovery(accounts.alice), None);
+        }
+
+        #[ink::test]
+        fn status_history_records_full_lifecycle() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_draft(
+                    String::from("ipfs://cid"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.activate_passport(token_id).unwrap();
+            contract
+                .suspend_passport(token_id, Some(String::from("audit")))
+                .unwrap();
+            contract
+                .revoke_passport(token_id, Some(String::from("recall")))
+                .unwrap();
+
+            assert_eq!(contract.status_history_len(token_id), 4);
+
+            let history = contract.get_status_history(token_id, 0, 10);
+            assert_eq!(history[0].old_status, None);
+            assert_eq!(history[0].new_status, PassportStatus::Draft);
+            assert_eq!(history[2].new_status, PassportStatus::Suspended);
+            assert_eq!(history[2].reason, Some(String::from("audit")));
+            assert_eq!(history[3].old_status, Some(PassportStatus::Suspended));
+            assert_eq!(history[3].changed_by, accounts.alice);
+
+            let page = contract.get_status_history(token_id, 1, 2);
+            assert_eq!(page.len(), 2);
+            assert_eq!(page[0].new_status, PassportStatus::Active);
+        }
+
+        #[ink::test]
+        fn restricted_passport_exposes_only_stub_to_public() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://b2b-only"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract
+                .set_visibility(token_id, Visibility::Restricted)
+                .unwrap();
+            contract.transfer(accounts.bob, token_id).unwrap();
+
+            // Issuer and owner keep full access
+            assert!(contract.get_passport(token_id).is_some());
+            ink::env::test::set_caller(accounts.bob);
+            assert!(contract.get_passport(token_id).is_some());
+            assert_eq!(contract.get_version_history(token_id).len(), 1);
+
+            // Everyone else only sees the stub
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.get_passport(token_id), None);
+            assert_eq!(contract.get_version(token_id, 1), None);
+            assert!(contract.get_version_history(token_id).is_empty());
+            assert_eq!(contract.get_verification_summary(token_id), None);
+            assert!(contract.get_custody_history(token_id).is_empty());
+            assert!(contract.get_status_history(token_id, 0, 10).is_empty());
+            assert!(contract.get_endorsements(token_id).is_empty());
+            assert_eq!(contract.get_sbom(token_id), None);
+            let stub = contract.get_passport_stub(token_id).unwrap();
+            assert_eq!(stub.issuer, accounts.alice);
+            assert_eq!(stub.status, PassportStatus::Active);
+            assert_eq!(stub.visibility, Visibility::Restricted);
+        }
+
+        #[ink::test]
+        fn staleness_resets_on_update() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.staleness(token_id), Some(0));
+
+            for _ in 0..3 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            assert_eq!(contract.staleness(token_id), Some(3));
+
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.staleness(token_id), Some(0));
+            assert_eq!(contract.staleness(999), None);
+        }
+
+        #[ink::test]
+        fn status_policy_controls_suspended_semantics() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.suspend_passport(token_id, None).unwrap();
+
+            contract
+                .set_status_policy(StatusPolicy {
+                    suspended_blocks_updates: true,
+                    suspended_blocks_transfers: false,
+                })
+                .unwrap();
+            assert!(contract.status_policy().suspended_blocks_updates);
+
+            assert_eq!(
+                contract.update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                ),
+                Err(Error::PassportSuspended)
+            );
+            assert_eq!(contract.transfer(accounts.bob, token_id), Ok(()));
+
+            // Only the admin may change the policy
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_status_policy(StatusPolicy::default()),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn owner_decommission_archives_and_records_end_of_life() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://battery"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.transfer(accounts.bob, token_id).unwrap();
+
+            // Only the current owner may decommission, not the issuer
+            assert_eq!(
+                contract.decommission(token_id, EndOfLifeReason::Recycled, [7u8; 32]),
+                Err(Error::NotOwner)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            contract
+                .decommission(token_id, EndOfLifeReason::Recycled, [7u8; 32])
+                .unwrap();
+
+            let record = contract.get_passport(token_id).unwrap();
+            assert_eq!(record.status, PassportStatus::Archived);
+            let eol = contract.get_end_of_life(token_id).unwrap();
+            assert_eq!(eol.reason, EndOfLifeReason::Recycled);
+            assert_eq!(eol.evidence_hash, [7u8; 32]);
+            assert_eq!(eol.decommissioned_by, accounts.bob);
+
+            assert_eq!(
+                contract.transfer(accounts.charlie, token_id),
+                Err(Error::PassportArchived)
+            );
+            assert_eq!(
+                contract.decommission(token_id, EndOfLifeReason::Destroyed, [0u8; 32]),
+                Err(Error::PassportArchived)
+            );
+        }
+
+        #[ink::test]
+        fn co_owned_token_cannot_be_decommissioned() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://battery"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract
+                .enable_co_ownership(
+                    token_id,
+                    ink::prelude::vec![(accounts.alice, 50), (accounts.bob, 50)],
+                    60,
+                )
+                .unwrap();
+
+            assert_eq!(
+                contract.decommission(token_id, EndOfLifeReason::Recycled, [7u8; 32]),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Active
+            );
+            assert_eq!(contract.get_end_of_life(token_id), None);
+        }
+
+        #[ink::test]
+        fn pre_claim_reserves_subject_for_claimant() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            let subject = [42u8; 32];
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.pre_claim(subject).unwrap();
+            assert_eq!(contract.pre_claim(subject), Err(Error::NotAllowed));
+
+            // Someone else cannot register the reserved subject
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(
+                contract.register_passport(
+                    String::from("ipfs://front-run"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    Some(subject),
+                    None,
+                ),
+                Err(Error::NotAllowed)
+            );
+
+            // The claimant can, which consumes the claim
+            ink::env::test::set_caller(accounts.bob);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://claimed"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    Some(subject),
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.find_token_by_subject_id(subject), Some(token_id));
+            assert_eq!(contract.get_pre_claim(subject), None);
+        }
+
+        #[ink::test]
+        fn lapsed_pre_claim_bond_is_forfeited() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            let subject = [9u8; 32];
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_pre_claim_terms(U256::from(10), 2).unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.pre_claim(subject), Err(Error::InsufficientFee));
+            ink::env::test::set_value_transferred(U256::from(10));
+            contract.pre_claim(subject).unwrap();
+            ink::env::test::set_value_transferred(U256::zero());
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+
+            ink::env::test::set_caller(accounts.charlie);
+            contract
+                .register_passport(
+                    String::from("ipfs://after-window"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    Some(subject),
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.fund_balance(), U256::from(10));
+        }
+
+        #[ink::test]
+        fn find_tokens_by_subject_ids_preserves_order() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let first = contract
+                .register_passport(
+                    String::from("ipfs://a"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    Some([1u8; 32]),
+                    None,
+                )
+                .unwrap();
+            let second = contract
+                .register_passport(
+                    String::from("ipfs://b"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    Some([2u8; 32]),
+                    None,
+                )
+                .unwrap();
+
+            let subjects = ink::prelude::vec![[2u8; 32], [3u8; 32], [1u8; 32]];
+            assert_eq!(
+                contract.find_tokens_by_subject_ids(subjects),
+                ink::prelude::vec![Some(second), None, Some(first)]
+            );
+        }
+
+        #[ink::test]
+        fn update_delegate_can_update_single_token() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let register = |contract: &mut DppContractV2| {
+                contract
+                    .register_passport(
+                        String::from("ipfs://v1"),
+                        [0u8; 32],
+                        String::from("application/vc+jwt"),
+                        Granularity::Batch,
+                        None,
+                        None,
+                    )
+                    .unwrap()
+            };
+            let delegated = register(&mut contract);
+            let other = register(&mut contract);
+            contract
+                .add_update_delegate(delegated, accounts.bob)
+                .unwrap();
+            assert!(contract.is_update_delegate(delegated, accounts.bob));
+
+            let update = |contract: &mut DppContractV2, token_id| {
+                contract.update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+            };
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(update(&mut contract, delegated), Ok(()));
+            assert_eq!(update(&mut contract, other), Err(Error::Unauthorized));
+            assert_eq!(
+                contract.add_update_delegate(delegated, accounts.charlie),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract
+                .remove_update_delegate(delegated, accounts.bob)
+                .unwrap();
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(update(&mut contract, delegated), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn min_update_interval_rejects_rapid_updates() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.set_min_update_interval(token_id, 2).unwrap();
+            assert_eq!(contract.min_update_interval(token_id), 2);
+
+            let update = |contract: &mut DppContractV2| {
+                contract.update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+            };
+
+            assert_eq!(update(&mut contract), Err(Error::TooFrequent));
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(update(&mut contract), Ok(()));
+            assert_eq!(update(&mut contract), Err(Error::TooFrequent));
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_min_update_interval(token_id, 0),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn roles_gate_configuration_and_keep_one_admin() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            // Deployer starts with every role
+            assert!(contract.has_role(Role::Admin, accounts.alice));
+            assert!(contract.has_role(Role::Registrar, accounts.alice));
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.set_max_versions(3), Err(Error::Unauthorized));
+            assert_eq!(
+                contract.grant_role(Role::Admin, accounts.bob),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(contract.renounce_role(Role::Admin), Err(Error::NotAllowed));
+            contract.grant_role(Role::Admin, accounts.bob).unwrap();
+            contract.renounce_role(Role::Admin).unwrap();
+            assert_eq!(contract.set_max_versions(3), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.set_max_versions(3), Ok(()));
+            assert_eq!(
+                contract.revoke_role(Role::Admin, accounts.bob),
+                Err(Error::NotAllowed)
+            );
+        }
+
+        #[ink::test]
+        fn federated_refs_are_bounded_and_issuer_only() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://product"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::ProductClass,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            contract
+                .add_federated_ref(token_id, String::from("EU-DPP"), [1u8; 32])
+                .unwrap();
+            assert_eq!(
+                contract.add_federated_ref(token_id, String::from("EU-DPP"), [1u8; 32]),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                contract.add_federated_ref(token_id, String::new(), [2u8; 32]),
+                Err(Error::InvalidInput)
+            );
+            for i in 1..MAX_FEDERATED_REFS {
+                contract
+                    .add_federated_ref(token_id, String::from("NAT"), [i as u8 + 1; 32])
+                    .unwrap();
+            }
+            assert_eq!(
+                contract.add_federated_ref(token_id, String::from("NAT"), [99u8; 32]),
+                Err(Error::NotAllowed)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.remove_federated_ref(token_id, String::from("EU-DPP"), [1u8; 32]),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract
+                .remove_federated_ref(token_id, String::from("EU-DPP"), [1u8; 32])
+                .unwrap();
+            let refs = contract.get_federated_refs(token_id);
+            assert_eq!(refs.len(), MAX_FEDERATED_REFS - 1);
+            assert_eq!(refs[0].registry_code, String::from("NAT"));
+        }
+
+        #[ink::test]
+        fn pause_blocks_writes_but_not_reads() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.pause(), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.pause().unwrap();
+            assert!(contract.paused());
+
+            assert_eq!(
+                contract.register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                ),
+                Err(Error::ContractPaused)
+            );
+            assert_eq!(
+                contract.update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                ),
+                Err(Error::ContractPaused)
+            );
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::ContractPaused)
+            );
+            assert!(contract.get_passport(token_id).is_some());
+
+            contract.unpause().unwrap();
+            assert_eq!(contract.transfer(accounts.bob, token_id), Ok(()));
+        }
+
+        #[ink::test]
+        fn issuance_context_survives_config_changes() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract
+                .set_registration_fee(Granularity::Item, U256::from(5))
+                .unwrap();
+            contract.set_max_versions(4).unwrap();
+
+            ink::env::test::set_value_transferred(U256::from(7));
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            ink::env::test::set_value_transferred(U256::zero());
+
+            contract.set_max_versions(0).unwrap();
+            contract.set_strict_uri_mode(true).unwrap();
+
+            let context = contract.get_issuance_context(token_id).unwrap();
+            assert_eq!(context.required_fee, U256::from(5));
+            assert_eq!(context.fee_paid, U256::from(7));
+            assert_eq!(context.max_versions, 4);
+            assert!(!context.strict_uri_mode);
+            assert!(!context.issuer_approved);
+        }
+
+        #[ink::test]
+        fn issuer_authority_moves_only_after_acceptance() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract
+                .add_update_delegate(token_id, accounts.django)
+                .unwrap();
+
+            // A withdrawn offer is sequenced and cannot be withdrawn twice
+            contract
+                .offer_issuer_transfer(token_id, accounts.charlie)
+                .unwrap();
+            let seq = contract.latest_event_seq(token_id);
+            contract.cancel_issuer_transfer(token_id).unwrap();
+            assert_eq!(contract.latest_event_seq(token_id), seq + 1);
+            assert_eq!(contract.pending_issuer_transfer(token_id), None);
+            assert_eq!(
+                contract.cancel_issuer_transfer(token_id),
+                Err(Error::NotAllowed)
+            );
+
+            contract
+                .offer_issuer_transfer(token_id, accounts.bob)
+                .unwrap();
+            assert_eq!(
+                contract.pending_issuer_transfer(token_id),
+                Some(accounts.bob)
+            );
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(
+                contract.accept_issuer_transfer(token_id),
+                Err(Error::Unauthorized)
+            );
+
+            // Offer alone does not move authority
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().issuer,
+                accounts.alice
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.accept_issuer_transfer(token_id).unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().issuer,
+                accounts.bob
+            );
+            assert_eq!(contract.pending_issuer_transfer(token_id), None);
+            assert!(!contract.is_update_delegate(token_id, accounts.django));
+
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(
+                contract.get_version(token_id, 1).unwrap().issuer,
+                accounts.alice
+            );
+            assert_eq!(
+                contract.get_version(token_id, 2).unwrap().issuer,
+                accounts.bob
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(
+                contract.revoke_passport(token_id, None),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn issuer_key_rotation_rejects_bad_signature() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(contract.issuer_controller(accounts.alice), accounts.alice);
+
+            let message = contract.issuer_rotation_message(accounts.alice, accounts.bob);
+            assert_ne!(
+                message,
+                contract.issuer_rotation_message(accounts.alice, accounts.charlie)
+            );
+
+            assert_eq!(
+                contract.rotate_issuer_key(accounts.alice, accounts.bob, [1u8; 65]),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(
+                contract.rotate_issuer_key(accounts.alice, accounts.alice, [1u8; 65]),
+                Err(Error::InvalidInput)
+            );
+            assert_eq!(contract.issuer_controller(accounts.alice), accounts.alice);
+        }
+
+        #[ink::test]
+        fn transfer_cooldown_throttles_flip_flopping() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://pallet"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.set_transfer_cooldown(token_id, 3).unwrap();
+            assert_eq!(contract.transfer_cooldown(token_id), 3);
+
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::TransferThrottled)
+            );
+            for _ in 0..3 {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+            contract.transfer(accounts.bob, token_id).unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.transfer(accounts.alice, token_id),
+                Err(Error::TransferThrottled)
+            );
+            assert_eq!(
+                contract.set_transfer_cooldown(token_id, 0),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn allowlist_mode_restricts_registration() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_allowlist_mode(true).unwrap();
+            contract.add_trusted_issuer(accounts.bob).unwrap();
+
+            let register = |contract: &mut DppContractV2| {
+                contract.register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+            };
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(register(&mut contract), Err(Error::NotTrustedIssuer));
+            assert_eq!(
+                contract.add_trusted_issuer(accounts.charlie),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            let token_id = register(&mut contract).unwrap();
+            assert!(
+                contract
+                    .get_issuance_context(token_id)
+                    .unwrap()
+                    .permissioned
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.remove_trusted_issuer(accounts.bob).unwrap();
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(register(&mut contract), Err(Error::NotTrustedIssuer));
+        }
+
+        #[ink::test]
+        fn notices_show_in_summary_until_expiry() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://charger"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::ProductClass,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(
+                contract.post_notice(
+                    token_id,
+                    NoticeType::FirmwareUpdate,
+                    String::from("ipfs://fw-2.1"),
+                    [5u8; 32],
+                    0,
+                ),
+                Err(Error::InvalidInput)
+            );
+            contract
+                .post_notice(
+                    token_id,
+                    NoticeType::FirmwareUpdate,
+                    String::from("ipfs://fw-2.1"),
+                    [5u8; 32],
+                    2,
+                )
+                .unwrap();
+
+            let summary = contract.get_verification_summary(token_id).unwrap();
+            assert_eq!(summary.notices.len(), 1);
+            assert_eq!(summary.notices[0].notice_type, NoticeType::FirmwareUpdate);
+            assert_eq!(summary.status, PassportStatus::Active);
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert!(contract
+                .get_verification_summary(token_id)
+                .unwrap()
+                .notices
+                .is_empty());
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.post_notice(
+                    token_id,
+                    NoticeType::UsageWarning,
+                    String::from("ipfs://warning"),
+                    [6u8; 32],
+                    100,
+                ),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn issuer_registry_is_admin_configured() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_issuer_registry(Some(accounts.django)),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_issuer_registry(Some(accounts.django)).unwrap();
+            assert_eq!(contract.issuer_registry(), Some(accounts.django));
+            contract.set_issuer_registry(None).unwrap();
+            assert_eq!(contract.issuer_registry(), None);
+        }
+
+        #[ink::test]
+        fn draft_activates_when_co_signer_threshold_is_met() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract
+                .set_issuance_policy(
+                    ink::prelude::vec![accounts.bob, accounts.charlie, accounts.django],
+                    2,
+                )
+                .unwrap();
+
+            assert_eq!(
+                contract.register_passport(
+                    String::from("ipfs://battery"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                ),
+                Err(Error::NotAllowed)
+            );
+            let token_id = contract
+                .register_draft(
+                    String::from("ipfs://battery"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.activate_passport(token_id), Err(Error::NotAllowed));
+
+            ink::env::test::set_caller(accounts.eve);
+            assert_eq!(
+                contract.approve_activation(token_id),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.approve_activation(token_id).unwrap();
+            assert_eq!(
+                contract.approve_activation(token_id),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Draft
+            );
+
+            ink::env::test::set_caller(accounts.django);
+            contract.approve_activation(token_id).unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Active
+            );
+            assert!(contract.get_activation_approvals(token_id).is_empty());
+        }
+
+        #[ink::test]
+        fn issuance_policy_rejects_invalid_threshold() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(
+                contract.set_issuance_policy(ink::prelude::vec![accounts.bob], 2),
+                Err(Error::InvalidInput)
+            );
+            assert_eq!(
+                contract.set_issuance_policy(ink::prelude::vec![accounts.bob, accounts.bob], 1),
+                Err(Error::InvalidInput)
+            );
+            assert_eq!(contract.clear_issuance_policy(), Err(Error::NotAllowed));
+        }
+
+        #[ink::test]
+        fn co_issuers_can_update_and_revoke() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::ProductClass,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.add_co_issuer(token_id, accounts.bob),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.add_co_issuer(token_id, accounts.bob).unwrap();
+            assert_eq!(
+                contract.add_co_issuer(token_id, accounts.bob),
+                Err(Error::NotAllowed)
+            );
+
+            // A co-issuer may add further co-issuers and update
+            ink::env::test::set_caller(accounts.bob);
+            contract.add_co_issuer(token_id, accounts.charlie).unwrap();
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            ink::env::test::set_caller(accounts.charlie);
+            contract.remove_co_issuer(token_id, accounts.bob).unwrap();
+            assert_eq!(
+                contract.get_co_issuers(token_id),
+                ink::prelude::vec![accounts.charlie]
+            );
+            contract.revoke_passport(token_id, None).unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Revoked
+            );
+        }
+
+        #[ink::test]
+        fn event_seq_counts_token_events() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            // PassportRegistered + AnchorDigest + Transfer
+            assert_eq!(contract.latest_event_seq(token_id), 3);
+
+            contract.transfer(accounts.bob, token_id).unwrap();
+            assert_eq!(contract.latest_event_seq(token_id), 4);
+            assert_eq!(contract.latest_event_seq(token_id + 1), 0);
+        }
+
+        #[ink::test]
+        fn self_check_reports_invariants() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    Some([7u8; 32]),
+                    None,
+                )
+                .unwrap();
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.transfer(accounts.bob, token_id).unwrap();
+
+            assert!(contract.self_check(token_id).unwrap().healthy);
+            assert_eq!(contract.self_check(token_id + 1), None);
+
+            // Simulate a lost version entry
+            contract.version_history.remove((token_id, 1));
+            let report = contract.self_check(token_id).unwrap();
+            assert!(!report.history_contiguous);
+            assert!(report.owner_set && report.custody_log_consistent);
+            assert!(report.counters_consistent);
+            assert!(!report.healthy);
+
+            // Simulate a balance that drifted from the owner index
+            contract.owned_tokens_count.insert(accounts.bob, &2);
+            assert!(!contract.self_check(token_id).unwrap().counters_consistent);
+        }
+
+        #[ink::test]
+        fn reviewer_approves_registration_and_updates() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(
+                contract.set_reviewer(Some(accounts.alice)),
+                Err(Error::InvalidInput)
+            );
+            contract.set_reviewer(Some(accounts.bob)).unwrap();
+
+            // Registration is held as a Draft until the reviewer approves it
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Draft
+            );
+            assert_eq!(contract.activate_passport(token_id), Err(Error::NotAllowed));
+            assert_eq!(
+                contract.approve_publication(token_id, 1),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.approve_publication(token_id, 1).unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Active
+            );
+
+            // Updates are staged; the current version is unchanged until approval
+            ink::env::test::set_caller(accounts.alice);
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.get_passport(token_id).unwrap().version, 1);
+            assert_eq!(
+                contract.get_pending_update(token_id).unwrap().entry.version,
+                2
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.approve_publication(token_id, 3),
+                Err(Error::InvalidInput)
+            );
+            contract.approve_publication(token_id, 2).unwrap();
+
+            let record = contract.get_passport(token_id).unwrap();
+            assert_eq!(record.version, 2);
+            assert_eq!(record.payload_hash, [1u8; 32]);
+            assert_eq!(
+                contract.get_version(token_id, 2).unwrap().updated_by,
+                accounts.alice
+            );
+            assert_eq!(contract.get_pending_update(token_id), None);
+            assert_eq!(
+                contract.approve_publication(token_id, 3),
+                Err(Error::NotAllowed)
+            );
+        }
+
+        #[ink::test]
+        fn admin_freeze_blocks_transfers_and_updates() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            let update = |contract: &mut DppContractV2| {
+                contract.update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+            };
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.freeze_token(token_id), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.freeze_token(token_id).unwrap();
+            assert!(contract.is_frozen(token_id));
+            assert_eq!(contract.freeze_token(token_id), Err(Error::TokenFrozen));
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::TokenFrozen)
+            );
+            assert_eq!(update(&mut contract), Err(Error::TokenFrozen));
+
+            contract.unfreeze_token(token_id).unwrap();
+            assert_eq!(contract.unfreeze_token(token_id), Err(Error::NotAllowed));
+            update(&mut contract).unwrap();
+            contract.transfer(accounts.bob, token_id).unwrap();
+        }
+
+        #[ink::test]
+        fn quotes_include_fees_and_deposit() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract
+                .set_registration_fee(Granularity::Batch, U256::from(100))
+                .unwrap();
+            contract.set_update_fee(U256::from(10)).unwrap();
+            let quote = |contract: &DppContractV2, uri: &str| {
+                contract.quote_registration(
+                    String::from(uri),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                )
+            };
+            assert_eq!(quote(&contract, "ipfs://v1"), U256::from(100));
+
+            // The URI is stored in the record and in the version entry
+            contract.set_deposit_per_byte(U256::from(1)).unwrap();
+            assert_eq!(
+                quote(&contract, "ipfs://v1-long"),
+                quote(&contract, "ipfs://v1") + U256::from(10)
+            );
+
+            ink::env::test::set_value_transferred(U256::from(100));
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            let quote = contract
+                .quote_update(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert!(quote > U256::from(10));
+            assert_eq!(
+                contract.quote_update(
+                    token_id + 1,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                ),
+                Err(Error::TokenNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn operator_approval_expires() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            let now = ink::env::block_number::<ink::env::DefaultEnvironment>();
+            assert_eq!(
+                contract.set_approval_for_all_until(accounts.bob, now),
+                Err(Error::InvalidInput)
+            );
+            contract
+                .set_approval_for_all_until(accounts.bob, now + 2)
+                .unwrap();
+            assert!(contract.is_approved_for_all(accounts.alice, accounts.bob));
+            assert_eq!(
+                contract.approval_for_all_expiry(accounts.alice, accounts.bob),
+                Some(now + 2)
+            );
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert!(!contract.is_approved_for_all(accounts.alice, accounts.bob));
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.transfer_from(accounts.alice, accounts.charlie, token_id),
+                Err(Error::NotApproved)
+            );
+
+            // An indefinite approval replaces the deadline
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_approval_for_all(accounts.bob, true).unwrap();
+            assert_eq!(
+                contract.approval_for_all_expiry(accounts.alice, accounts.bob),
+                None
+            );
+            ink::env::test::set_caller(accounts.bob);
+            contract
+                .transfer_from(accounts.alice, accounts.charlie, token_id)
+                .unwrap();
+        }
+
+        #[ink::test]
+        fn issuer_profile_set_and_replaced() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.get_issuer_profile(accounts.bob), None);
+            assert_eq!(
+                contract.set_issuer_profile(String::new(), [1u8; 32]),
+                Err(Error::InvalidInput)
+            );
+
+            contract
+                .set_issuer_profile(String::from("ipfs://profile-v1"), [1u8; 32])
+                .unwrap();
+            contract
+                .set_issuer_profile(String::from("ipfs://profile-v2"), [2u8; 32])
+                .unwrap();
+
+            let profile = contract.get_issuer_profile(accounts.bob).unwrap();
+            assert_eq!(profile.uri, String::from("ipfs://profile-v2"));
+            assert_eq!(profile.hash, [2u8; 32]);
+            assert_eq!(contract.get_issuer_profile(accounts.alice), None);
+        }
+
+        #[ink::test]
+        fn transfer_requires_accepted_terms() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract
+                .set_transfer_terms(token_id, Some([1u8; 32]))
+                .unwrap();
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::TermsNotAccepted)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.accept_terms(token_id, [2u8; 32]),
+                Err(Error::InvalidInput)
+            );
+            contract.accept_terms(token_id, [1u8; 32]).unwrap();
+            assert_eq!(
+                contract
+                    .get_terms_acceptance(token_id, accounts.bob)
+                    .map(|(hash, _)| hash),
+                Some([1u8; 32])
+            );
+
+            // New terms require a fresh acceptance
+            ink::env::test::set_caller(accounts.alice);
+            contract
+                .set_transfer_terms(token_id, Some([2u8; 32]))
+                .unwrap();
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::TermsNotAccepted)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.accept_terms(token_id, [2u8; 32]).unwrap();
+            ink::env::test::set_caller(accounts.alice);
+            contract.transfer(accounts.bob, token_id).unwrap();
+            assert_eq!(contract.owner_of(token_id), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn signed_registration_rejects_bad_signature() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            let message = |contract: &DppContractV2, issuer: Address, payload_hash: [u8; 32]| {
+                contract.registration_message(
+                    issuer,
+                    String::from("ipfs://v1"),
+                    payload_hash,
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+            };
+            let signed_hash = message(&contract, accounts.alice, [0u8; 32]);
+            assert_ne!(signed_hash, message(&contract, accounts.bob, [0u8; 32]));
+            assert_ne!(signed_hash, message(&contract, accounts.alice, [1u8; 32]));
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(
+                contract.register_passport_signed(
+                    accounts.alice,
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                    [1u8; 65],
+                ),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(contract.registration_nonce(accounts.alice), 0);
+            assert_eq!(contract.owner_of(0), None);
+        }
+
+        #[ink::test]
+        fn regulator_flags_and_clears() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            // The deployer does not hold the Regulator role
+            assert_eq!(
+                contract.flag_passport(token_id, [9u8; 32]),
+                Err(Error::Unauthorized)
+            );
+            contract.grant_role(Role::Regulator, accounts.eve).unwrap();
+
+            ink::env::test::set_caller(accounts.eve);
+            contract.flag_passport(token_id, [9u8; 32]).unwrap();
+            let flag = contract.get_flag(token_id).unwrap();
+            assert_eq!(flag.reason_hash, [9u8; 32]);
+            assert_eq!(flag.flagged_by, accounts.eve);
+
+            // No update rights come with the role
+            assert_eq!(
+                contract.update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                ),
+                Err(Error::Unauthorized)
+            );
+
+            contract.clear_flag(token_id).unwrap();
+            assert_eq!(contract.get_flag(token_id), None);
+            assert_eq!(contract.clear_flag(token_id), Err(Error::NotAllowed));
+        }
+
+        #[ink::test]
+        fn only_registered_certifiers_endorse() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            let endorse = |contract: &mut DppContractV2, claim_hash: [u8; 32]| {
+                contract.endorse_passport(
+                    token_id,
+                    claim_hash,
+                    String::from("ipfs://certificate"),
+                    [7u8; 32],
+                    None,
+                )
+            };
+
+            ink::env::test::set_caller(accounts.frank);
+            assert_eq!(endorse(&mut contract, [1u8; 32]), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.add_certifier(accounts.frank).unwrap();
+
+            // Endorsing again replaces the certifier's previous endorsement
+            ink::env::test::set_caller(accounts.frank);
+            endorse(&mut contract, [1u8; 32]).unwrap();
+            endorse(&mut contract, [2u8; 32]).unwrap();
+            let endorsements = contract.get_endorsements(token_id);
+            assert_eq!(endorsements.len(), 1);
+            assert_eq!(endorsements[0].certifier, accounts.frank);
+            assert_eq!(endorsements[0].claim_hash, [2u8; 32]);
+
+            contract.withdraw_endorsement(token_id).unwrap();
+            assert!(contract.get_endorsements(token_id).is_empty());
+            assert_eq!(
+                contract.withdraw_endorsement(token_id),
+                Err(Error::NotAllowed)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.remove_certifier(accounts.frank).unwrap();
+            ink::env::test::set_caller(accounts.frank);
+            assert_eq!(endorse(&mut contract, [1u8; 32]), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn registration_attaches_signed_attestations() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            // The key of `rotated_key_takes_over_drafts_and_pre_claims` signs
+            // `attestation_message` for `issuer`, payload hash [1; 32], and the
+            // attestation fields below (including the certificate hash).
+            ink::env::test::set_callee(Address::from([0x42; 20]));
+            let certifier = Address::from([
+                0x2c, 0x75, 0x36, 0xe3, 0x60, 0x5d, 0x9c, 0x16, 0xa7, 0xa3, 0xd7, 0xb1, 0x89, 0x8e,
+                0x52, 0x93, 0x96, 0xa6, 0x5c, 0x23,
+            ]);
+            let issuer = Address::from([0x55; 20]);
+            let attestation = SignedAttestation {
+                certifier,
+                claim_hash: [2u8; 32],
+                evidence_uri: String::from("ipfs://certificate"),
+                evidence_hash: [3u8; 32],
+                certificate_hash: Some([4u8; 32]),
+                signature: [
+                    0x28, 0x15, 0xdc, 0xb5, 0x73, 0xe1, 0xd8, 0xb6, 0x94, 0xef, 0x20, 0x3b, 0xcb,
+                    0xb0, 0xdf, 0x10, 0x54, 0xea, 0x4e, 0x83, 0xfa, 0xe5, 0x0b, 0x5d, 0x9a, 0x94,
+                    0x3a, 0x4e, 0x69, 0xf3, 0x20, 0xa6, 0x24, 0x10, 0xc3, 0xfd, 0xf4, 0x89, 0x96,
+                    0x4c, 0x27, 0xbf, 0x04, 0x3d, 0x1a, 0x1b, 0x88, 0x23, 0x16, 0x51, 0x2f, 0xdd,
+                    0xfd, 0xbe, 0x48, 0xbd, 0x84, 0x8e, 0x94, 0xb6, 0xff, 0x68, 0xb0, 0x22, 0x01,
+                ],
+            };
+            let input = |payload_hash: [u8; 32]| RegistrationInput {
+                dataset_uri: String::from("ipfs://v1"),
+                payload_hash,
+                dataset_type: String::from("application/vc+jwt"),
+                granularity: Granularity::Batch,
+                subject_id_hash: None,
+                validity_blocks: None,
+            };
+
+            ink::env::test::set_caller(issuer);
+            assert_eq!(
+                contract.register_with_attestations(
+                    input([1u8; 32]),
+                    ink::prelude::vec![attestation.clone()]
+                ),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.add_certifier(certifier).unwrap();
+
+            // The attestation covers only the dataset it was signed for
+            ink::env::test::set_caller(issuer);
+            assert_eq!(
+                contract.register_with_attestations(
+                    input([9u8; 32]),
+                    ink::prelude::vec![attestation.clone()]
+                ),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(
+                contract.register_with_attestations(input([1u8; 32]), ink::prelude::vec![]),
+                Err(Error::InvalidInput)
+            );
+            assert_eq!(contract.total_supply(), 0);
+
+            let token_id = contract
+                .register_with_attestations(input([1u8; 32]), ink::prelude::vec![attestation])
+                .unwrap();
+            let endorsements = contract.get_endorsements(token_id);
+            assert_eq!(endorsements.len(), 1);
+            assert_eq!(endorsements[0].certifier, certifier);
+            assert_eq!(endorsements[0].claim_hash, [2u8; 32]);
+            assert_eq!(
+                contract.find_attestation_by_certificate([4u8; 32]),
+                Some((token_id, endorsements[0].clone()))
+            );
+        }
+
+        #[ink::test]
+        fn attestations_are_found_by_certificate_hash() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            let register = |contract: &mut DppContractV2| {
+                contract
+                    .register_passport(
+                        String::from("ipfs://v1"),
+                        [0u8; 32],
+                        String::from("application/vc+jwt"),
+                        Granularity::Batch,
+                        None,
+                        None,
+                    )
+                    .unwrap()
+            };
+            let endorse =
+                |contract: &mut DppContractV2, token_id: u128, certificate_hash: [u8; 32]| {
+                    contract.endorse_passport(
+                        token_id,
+                        [1u8; 32],
+                        String::from("ipfs://certificate"),
+                        [7u8; 32],
+                        Some(certificate_hash),
+                    )
+                };
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = register(&mut contract);
+            let other = register(&mut contract);
+            contract.add_certifier(accounts.frank).unwrap();
+            contract.add_certifier(accounts.eve).unwrap();
+
+            ink::env::test::set_caller(accounts.frank);
+            endorse(&mut contract, token_id, [5u8; 32]).unwrap();
+            let (found, endorsement) = contract.find_attestation_by_certificate([5u8; 32]).unwrap();
+            assert_eq!(found, token_id);
+            assert_eq!(endorsement.certifier, accounts.frank);
+            assert_eq!(endorsement.certificate_hash, Some([5u8; 32]));
+
+            // A certificate points at one endorsement only
+            assert_eq!(
+                endorse(&mut contract, other, [5u8; 32]),
+                Err(Error::NotAllowed)
+            );
+            ink::env::test::set_caller(accounts.eve);
+            assert_eq!(
+                endorse(&mut contract, token_id, [5u8; 32]),
+                Err(Error::NotAllowed)
+            );
+
+            // Replacing the endorsement re-indexes it under the new certificate
+            ink::env::test::set_caller(accounts.frank);
+            endorse(&mut contract, token_id, [6u8; 32]).unwrap();
+            assert_eq!(contract.find_attestation_by_certificate([5u8; 32]), None);
+            assert_eq!(
+                contract
+                    .find_attestation_by_certificate([6u8; 32])
+                    .unwrap()
+                    .0,
+                token_id
+            );
+
+            // Restricted passports are withheld from other callers
+            ink::env::test::set_caller(accounts.alice);
+            contract
+                .set_visibility(token_id, Visibility::Restricted)
+                .unwrap();
+            ink::env::test::set_caller(accounts.django);
+            assert_eq!(contract.find_attestation_by_certificate([6u8; 32]), None);
+
+            ink::env::test::set_caller(accounts.frank);
+            contract.withdraw_endorsement(token_id).unwrap();
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(contract.find_attestation_by_certificate([6u8; 32]), None);
+            assert!(!contract.certificates.contains([6u8; 32]));
+        }
+
+        #[ink::test]
+        fn service_providers_append_scoped_records() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract
+                .add_service_provider(ServiceProvider::Repairer, accounts.django)
+                .unwrap();
+            contract
+                .add_service_provider(ServiceProvider::Recycler, accounts.eve)
+                .unwrap();
+
+            // Each registry only grants its own append message
+            ink::env::test::set_caller(accounts.django);
+            contract
+                .append_repair_record(token_id, String::from("ipfs://repair"), [1u8; 32])
+                .unwrap();
+            let eol = String::from("ipfs://eol");
+            assert_eq!(
+                contract.append_end_of_life_record(token_id, eol.clone(), [2u8; 32]),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.eve);
+            contract
+                .append_end_of_life_record(token_id, eol, [2u8; 32])
+                .unwrap();
+
+            let records = contract.get_service_records(token_id, 0, 10);
+            assert_eq!(records.len(), 2);
+            assert_eq!(records[0].kind, ServiceRecordKind::Repair);
+            assert_eq!(records[0].recorded_by, accounts.django);
+            assert_eq!(records[1].kind, ServiceRecordKind::EndOfLife);
+
+            // No dataset update rights and no status change
+            let record = contract.get_passport(token_id).unwrap();
+            assert_eq!(record.version, 1);
+            assert_eq!(record.status, PassportStatus::Active);
+        }
+
+        #[ink::test]
+        fn sbom_history_is_separate_from_versions() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.get_sbom(token_id), None);
+
+            let set_sbom = |contract: &mut DppContractV2, version: &str, sbom_hash: [u8; 32]| {
+                contract.set_sbom(
+                    token_id,
+                    String::from(version),
+                    String::from("ipfs://sbom"),
+                    sbom_hash,
+                )
+            };
+            set_sbom(&mut contract, "1.0.0", [1u8; 32]).unwrap();
+            set_sbom(&mut contract, "1.1.0", [2u8; 32]).unwrap();
+
+            let current = contract.get_sbom(token_id).unwrap();
+            assert_eq!(current.software_version, String::from("1.1.0"));
+            let history = contract.get_sbom_history(token_id, 0, 10);
+            assert_eq!(history.len(), 2);
+            assert_eq!(history[0].sbom_hash, [1u8; 32]);
+            assert_eq!(contract.get_passport(token_id).unwrap().version, 1);
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                set_sbom(&mut contract, "2.0.0", [3u8; 32]),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn admin_handover_requires_acceptance() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(
+                contract.propose_admin(accounts.alice),
+                Err(Error::InvalidInput)
+            );
+            contract.propose_admin(accounts.bob).unwrap();
+            assert_eq!(
+                contract.pending_admin(),
+                Some((accounts.alice, accounts.bob))
+            );
+            assert!(!contract.has_role(Role::Admin, accounts.bob));
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.accept_admin(), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.accept_admin().unwrap();
+            assert!(contract.has_role(Role::Admin, accounts.bob));
+            assert!(!contract.has_role(Role::Admin, accounts.alice));
+            assert_eq!(contract.pending_admin(), None);
+            assert_eq!(contract.accept_admin(), Err(Error::NotAllowed));
+
+            // Other roles of the previous admin are unaffected
+            assert!(contract.has_role(Role::Pauser, accounts.alice));
+        }
+
+        #[ink::test]
+        fn admin_handover_blocked_after_timelock_enabled() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.propose_admin(accounts.bob).unwrap();
+            contract.set_timelock_delay(2).unwrap();
+
+            // The stale proposal cannot bypass the timelock
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.accept_admin(), Err(Error::NotAllowed));
+            assert!(!contract.has_role(Role::Admin, accounts.bob));
+            assert!(contract.has_role(Role::Admin, accounts.alice));
+        }
+
+        #[ink::test]
+        fn admin_handover_to_existing_admin_counts_once() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.propose_admin(accounts.bob).unwrap();
+            contract.grant_role(Role::Admin, accounts.bob).unwrap();
+            assert_eq!(contract.admin_count, 2);
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.accept_admin().unwrap();
+            assert_eq!(contract.admin_count, 1);
+            assert!(!contract.has_role(Role::Admin, accounts.alice));
+            assert_eq!(contract.renounce_role(Role::Admin), Err(Error::NotAllowed));
+        }
+
+        #[ink::test]
+        fn health_tracks_counters() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            for _ in 0..2 {
+                contract
+                    .register_passport(
+                        String::from("ipfs://v1"),
+                        [0u8; 32],
+                        String::from("application/vc+jwt"),
+                        Granularity::Batch,
+                        None,
+                        None,
+                    )
+                    .unwrap();
+            }
+            contract.transfer(accounts.bob, 0).unwrap();
+
+            let health = contract.health();
+            assert_eq!(health.minted, 2);
+            assert_eq!(health.live_tokens, 2);
+            assert_eq!(health.owned_total, 2);
+            assert!(health.balances_consistent);
+            assert!(health.healthy);
+
+            // Simulate a lost balance update
+            contract.owned_total -= 1;
+            assert!(!contract.health().healthy);
+        }
+
+        #[ink::test]
+        fn timelocked_admin_actions() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_timelock_delay(2).unwrap();
+            assert_eq!(
+                contract.set_update_fee(U256::from(10)),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(contract.set_timelock_delay(0), Err(Error::NotAllowed));
+
+            let action_id = contract
+                .queue_action(AdminAction::SetUpdateFee(U256::from(10)))
+                .unwrap();
+            assert_eq!(
+                contract.execute_action(action_id),
+                Err(Error::TimelockActive)
+            );
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            contract.execute_action(action_id).unwrap();
+            assert_eq!(contract.update_fee(), U256::from(10));
+            assert_eq!(contract.execute_action(action_id), Err(Error::NotAllowed));
+
+            let action_id = contract
+                .queue_action(AdminAction::SetAllowlistMode(true))
+                .unwrap();
+            contract.cancel_action(action_id).unwrap();
+            assert_eq!(contract.get_queued_action(action_id), None);
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.queue_action(AdminAction::SetTimelockDelay(0)),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn admin_threshold_requires_confirmations() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.grant_role(Role::Admin, accounts.bob).unwrap();
+            assert_eq!(contract.set_admin_threshold(3), Err(Error::InvalidInput));
+            contract.set_admin_threshold(2).unwrap();
+
+            // Direct calls are disabled; a single admin cannot act alone
+            assert_eq!(
+                contract.grant_role(Role::Admin, accounts.charlie),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(contract.renounce_role(Role::Admin), Err(Error::NotAllowed));
+            let action_id = contract
+                .queue_action(AdminAction::GrantRole(Role::Admin, accounts.charlie))
+                .unwrap();
+            assert_eq!(contract.execute_action(action_id), Err(Error::NotApproved));
+            assert_eq!(contract.confirm_action(action_id), Err(Error::NotAllowed));
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.confirm_action(action_id).unwrap();
+            contract.execute_action(action_id).unwrap();
+            assert!(contract.has_role(Role::Admin, accounts.charlie));
+            assert_eq!(contract.admin_threshold(), 2);
+        }
+
+        #[ink::test]
+        fn psp34_transfer_and_allowance() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            let id = Id::U128(token_id);
+            assert_eq!(PSP34::total_supply(&contract), 1);
+            assert_eq!(PSP34::balance_of(&contract, accounts.alice), 1);
+            assert_eq!(
+                PSP34::approve(&mut contract, accounts.alice, None, true),
+                Err(PSP34Error::SelfApprove)
+            );
+
+            let allowed = |contract: &DppContractV2, id: Option<Id>| {
+                PSP34::allowance(contract, accounts.alice, accounts.charlie, id)
+            };
+            PSP34::approve(&mut contract, accounts.charlie, Some(id.clone()), true).unwrap();
+            assert!(allowed(&contract, Some(id.clone())));
+            assert!(!allowed(&contract, None));
+            PSP34::approve(&mut contract, accounts.charlie, Some(id.clone()), false).unwrap();
+            assert!(!allowed(&contract, Some(id.clone())));
+
+            PSP34::transfer(&mut contract, accounts.bob, id.clone(), Vec::new()).unwrap();
+            assert_eq!(PSP34::owner_of(&contract, id.clone()), Some(accounts.bob));
+            assert_eq!(
+                PSP34::transfer(&mut contract, accounts.charlie, id, Vec::new()),
+                Err(PSP34Error::NotApproved)
+            );
+            assert_eq!(PSP34::owner_of(&contract, Id::Bytes(Vec::new())), None);
+        }
+
+        #[ink::test]
+        fn migration_recounts_tokens_in_batches() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            for _ in 0..3 {
+                contract
+                    .register_passport(
+                        String::from("ipfs://v1"),
+                        [0u8; 32],
+                        String::from("application/vc+jwt"),
+                        Granularity::Batch,
+                        None,
+                        None,
+                    )
+                    .unwrap();
+            }
+            // Simulate counters from a layout that did not maintain them
+            contract.live_tokens = 0;
+            contract.owned_total = 0;
+            assert!(!contract.health().healthy);
+
+            assert_eq!(contract.continue_migration(1), Err(Error::NotAllowed));
+            contract.start_migration(Migration::RecountTokens).unwrap();
+            assert_eq!(
+                contract.start_migration(Migration::RecountTokens),
+                Err(Error::NotAllowed)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.continue_migration(0), Err(Error::InvalidInput));
+            assert_eq!(contract.continue_migration(2), Ok(false));
+            assert_eq!(contract.migration_status().map(|m| m.cursor), Some(2));
+            assert_eq!(contract.continue_migration(2), Ok(true));
+
+            assert_eq!(contract.migration_status(), None);
+            assert!(contract
+                .migration_completed_at(Migration::RecountTokens)
+                .is_some());
+            assert_eq!(contract.health().live_tokens, 3);
+            assert!(contract.health().healthy);
         }
 
-        /// Get all version history for a passport
-        ///
-        /// Returns a vector of all versions in ascending order (v1, v2, v3, ...).
-        ///
-        /// # Arguments
-        ///
-        /// * `token_id` - Token ID
-        ///
-        /// # Returns
-        ///
-        /// Vector of VersionHistory entries (empty if token doesn't exist or has no history)
-        ///
-        /// # Note
-        ///
-        /// This iterates from version 1 up to the current version.
-        /// For passports with many versions, consider using get_version() for specific versions.
-        #[ink(message)]
-        pub fn get_version_history(&self, token_id: u128) -> ink::prelude::vec::Vec<VersionHistory> {
-            use ink::prelude::vec::Vec;
+        #[ink::test]
+        fn token_uri_uses_dataset_uri_or_base_uri() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
 
-            // Get current passport to know the latest version
-            let record = match self.passports.get(token_id) {
-                Some(r) => r,
-                None => return Vec::new(),  // Token doesn't exist
-            };
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.collection_symbol(), "DPP");
+            assert_eq!(
+                contract.token_uri(token_id),
+                Some(String::from("ipfs://v1"))
+            );
+            assert_eq!(contract.token_uri(token_id + 1), None);
 
-            let mut history = Vec::new();
+            assert_eq!(
+                contract.set_token_base_uri(Some(String::new())),
+                Err(Error::InvalidInput)
+            );
+            contract
+                .set_token_base_uri(Some(String::from("https://dpp.example/token/")))
+                .unwrap();
+            assert_eq!(
+                contract.token_uri(token_id),
+                Some(String::from("https://dpp.example/token/0"))
+            );
 
-            // Iterate through all versions (1..=current_version)
-            for v in 1..=record.version {
-                if let Some(entry) = self.version_history.get((token_id, v)) {
-                    history.push(entry);
-                }
+            contract
+                .set_visibility(token_id, Visibility::Restricted)
+                .unwrap();
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.token_uri(token_id), None);
+            assert_eq!(contract.set_token_base_uri(None), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn enumeration_follows_transfers() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            for _ in 0..3 {
+                contract
+                    .register_passport(
+                        String::from("ipfs://v1"),
+                        [0u8; 32],
+                        String::from("application/vc+jwt"),
+                        Granularity::Batch,
+                        None,
+                        None,
+                    )
+                    .unwrap();
             }
+            contract.transfer(accounts.bob, 0).unwrap();
+
+            assert_eq!(contract.total_supply(), 3);
+            assert_eq!(contract.token_by_index(2), Some(2));
+            assert_eq!(contract.token_by_index(3), None);
+            // The last of Alice's tokens moved into the vacated slot
+            assert_eq!(contract.token_of_owner_by_index(accounts.alice, 0), Some(2));
+            assert_eq!(contract.token_of_owner_by_index(accounts.alice, 1), Some(1));
+            assert_eq!(contract.token_of_owner_by_index(accounts.alice, 2), None);
+            assert_eq!(contract.token_of_owner_by_index(accounts.bob, 0), Some(0));
+
+            // Drafts are neither counted nor listed until published, even for
+            // their issuer
+            let draft = contract
+                .register_draft(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.total_supply(), 3);
+            assert_eq!(contract.balance_of(accounts.alice), 2);
+            assert_eq!(contract.token_by_index(3), None);
+            assert_eq!(contract.token_of_owner_by_index(accounts.alice, 2), None);
+            assert!(contract.self_check(draft).unwrap().healthy);
+
+            contract.activate_passport(draft).unwrap();
+            assert_eq!(contract.total_supply(), 4);
+            assert_eq!(contract.balance_of(accounts.alice), 3);
+            assert_eq!(contract.token_by_index(3), Some(draft));
+            assert_eq!(
+                contract.token_of_owner_by_index(accounts.alice, 2),
+                Some(draft)
+            );
+            assert!(contract.self_check(draft).unwrap().healthy);
+        }
 
-            history
+        #[ink::test]
+        fn describe_call_summarizes_known_messages() {
+            let contract = DppContractV2::new();
+
+            let revoke = ink::selector_bytes!("revoke_passport");
+            assert_eq!(
+                contract.describe_call(revoke, (42u128, Some(String::from("Recall"))).encode()),
+                Some(String::from("Revoke passport #42: reason Recall"))
+            );
+            assert_eq!(
+                contract.describe_call(revoke, (42u128, None::<String>).encode()),
+                Some(String::from("Revoke passport #42"))
+            );
+            // Trailing bytes or unknown selectors are not described
+            let trailing = (42u128, None::<String>, 1u8).encode();
+            assert_eq!(contract.describe_call(revoke, trailing), None);
+            assert_eq!(contract.describe_call([0u8; 4], 42u128.encode()), None);
         }
 
-        /// Get the latest N versions
-        ///
-        /// Useful for displaying recent history without loading all versions.
-        ///
-        /// # Arguments
-        ///
-        /// * `token_id` - Token ID
-        /// * `limit` - Maximum number of versions to return
-        ///
-        /// # Returns
-        ///
-        /// Vector of the most recent versions, in descending order (newest first)
-        #[ink(message)]
-        pub fn get_recent_versions(&self, token_id: u128, limit: u32) -> ink::prelude::vec::Vec<VersionHistory> {
-            use ink::prelude::vec::Vec;
+        #[ink::test]
+        fn safe_transfer_to_account_needs_no_hook() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
 
-            let record = match self.passports.get(token_id) {
-                Some(r) => r,
-                None => return Vec::new(),
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.safe_transfer_from(accounts.alice, accounts.bob, token_id, Vec::new()),
+                Err(Error::NotApproved)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract
+                .safe_transfer_from(accounts.alice, accounts.bob, token_id, Vec::new())
+                .unwrap();
+            assert_eq!(contract.owner_of(token_id), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn soulbound_passport_cannot_move() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_soulbound_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert!(!contract.get_passport(token_id).unwrap().transferable);
+
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                contract.approve(accounts.bob, token_id),
+                Err(Error::NotAllowed)
+            );
+
+            contract.set_approval_for_all(accounts.bob, true).unwrap();
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.transfer_from(accounts.alice, accounts.bob, token_id),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(contract.owner_of(token_id), Some(accounts.alice));
+        }
+
+        #[ink::test]
+        fn lock_blocks_transfers_until_unlocked() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.transfer(accounts.bob, token_id).unwrap();
+
+            // Owner authority by default: the issuer cannot lock Bob's token
+            assert_eq!(contract.lock(token_id), Err(Error::Unauthorized));
+            ink::env::test::set_caller(accounts.bob);
+            contract.lock(token_id).unwrap();
+            assert!(contract.is_locked(token_id));
+            assert_eq!(contract.lock(token_id), Err(Error::TokenLocked));
+            assert_eq!(
+                contract.transfer(accounts.charlie, token_id),
+                Err(Error::TokenLocked)
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_lock_authority(LockAuthority::Issuer).unwrap();
+            contract.unlock(token_id).unwrap();
+            assert!(!contract.is_locked(token_id));
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.transfer(accounts.charlie, token_id).unwrap();
+        }
+
+        #[ink::test]
+        fn burn_removes_ownership_and_archives() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let register = |contract: &mut DppContractV2| {
+                contract
+                    .register_passport(
+                        String::from("ipfs://v1"),
+                        [0u8; 32],
+                        String::from("application/vc+jwt"),
+                        Granularity::Batch,
+                        None,
+                        None,
+                    )
+                    .unwrap()
             };
+            let first = register(&mut contract);
+            let second = register(&mut contract);
+            contract.set_burn_consent_required(true).unwrap();
+            contract.transfer(accounts.bob, first).unwrap();
+
+            // The requirement follows the identity's controlling key
+            contract
+                .issuer_controllers
+                .insert(accounts.alice, &accounts.django);
+            assert!(!contract.burn_consent_required(accounts.alice));
+            contract.issuer_controllers.remove(accounts.alice);
+            assert!(contract.burn_consent_required(accounts.alice));
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.burn(second), Err(Error::NotOwner));
+            assert_eq!(contract.burn(first), Err(Error::NotApproved));
+
+            ink::env::test::set_caller(accounts.alice);
+            let events = ink::env::test::recorded_events().into_iter().count();
+            contract.consent_to_burn(first).unwrap();
+            assert_eq!(
+                ink::env::test::recorded_events().into_iter().count(),
+                events + 1
+            );
+            ink::env::test::set_caller(accounts.bob);
+            contract.burn(first).unwrap();
+
+            assert_eq!(contract.owner_of(first), None);
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+            assert_eq!(
+                contract.get_passport(first).unwrap().status,
+                PassportStatus::Archived
+            );
+            assert!(contract.get_burn_record(first).unwrap().issuer_consented);
+            assert_eq!(contract.total_supply(), 1);
+            assert_eq!(contract.token_by_index(0), Some(second));
+            assert_eq!(contract.token_by_index(1), None);
+            assert!(contract.health().healthy);
+            assert!(contract.self_check(first).unwrap().healthy);
+        }
+
+        #[ink::test]
+        fn register_passport_for_sets_initial_owner() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport_for(
+                    accounts.bob,
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+
+            assert_eq!(contract.owner_of(token_id), Some(accounts.bob));
+            assert_eq!(contract.balance_of(accounts.alice), 0);
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().issuer,
+                accounts.alice
+            );
+            // Issuer authority stays with the caller
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+        }
+
+        #[ink::test]
+        fn transfer_offer_moves_token_on_acceptance() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract.offer_transfer(accounts.bob, token_id).unwrap();
+            assert_eq!(contract.pending_transfer(token_id), Some(accounts.bob));
+            assert_eq!(contract.owner_of(token_id), Some(accounts.alice));
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.accept_transfer(token_id), Err(Error::Unauthorized));
+            assert_eq!(
+                contract.cancel_transfer_offer(token_id),
+                Err(Error::NotOwner)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.accept_transfer(token_id).unwrap();
+            assert_eq!(contract.owner_of(token_id), Some(accounts.bob));
+            assert_eq!(contract.pending_transfer(token_id), None);
+
+            contract.offer_transfer(accounts.charlie, token_id).unwrap();
+            let seq = contract.latest_event_seq(token_id);
+            contract.cancel_transfer_offer(token_id).unwrap();
+            assert_eq!(contract.latest_event_seq(token_id), seq + 1);
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(contract.accept_transfer(token_id), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn transfer_with_data_emits_memo() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            let before = ink::env::test::recorded_events().into_iter().count();
+
+            contract
+                .transfer_with_data(accounts.bob, token_id, [9u8; 32])
+                .unwrap();
+            assert_eq!(contract.owner_of(token_id), Some(accounts.bob));
+            // Transfer + TransferMemo
+            assert_eq!(
+                ink::env::test::recorded_events().into_iter().count(),
+                before + 2
+            );
+            assert_eq!(contract.latest_event_seq(token_id), 5);
+
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(
+                contract.transfer_with_data(accounts.charlie, token_id, [9u8; 32]),
+                Err(Error::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn recipient_allowlists_restrict_transfers() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://battery"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
 
-            let mut history = Vec::new();
-            let current_version = record.version;
+            // Contract-wide list
+            contract.set_recipient_allowlist(true).unwrap();
+            contract.set_allowed_recipient(accounts.bob, true).unwrap();
+            assert!(!contract.is_recipient_allowed(token_id, accounts.charlie));
+            assert_eq!(
+                contract.transfer(accounts.charlie, token_id),
+                Err(Error::RecipientNotAllowed)
+            );
 
-            // Start from current version and go backwards
-            let start = if current_version > limit {
-                current_version - limit + 1
-            } else {
-                1
-            };
+            // The issuer's own list applies on top
+            contract
+                .set_issuer_recipient_allowlist(accounts.alice, true)
+                .unwrap();
+            assert!(!contract.is_recipient_allowed(token_id, accounts.bob));
 
-            for v in (start..=current_version).rev() {
-                if let Some(entry) = self.version_history.get((token_id, v)) {
-                    history.push(entry);
-                }
-            }
+            // After a key rotation only the new key manages the identity's list
+            contract
+                .issuer_controllers
+                .insert(accounts.alice, &accounts.django);
+            assert_eq!(
+                contract.set_issuer_allowed_recipient(accounts.alice, accounts.bob, true),
+                Err(Error::Unauthorized)
+            );
+            ink::env::test::set_caller(accounts.django);
+            contract
+                .set_issuer_allowed_recipient(accounts.alice, accounts.bob, true)
+                .unwrap();
+            contract.issuer_controllers.remove(accounts.alice);
 
-            history
-        }
+            ink::env::test::set_caller(accounts.alice);
+            contract.transfer(accounts.bob, token_id).unwrap();
 
-        /// Find token ID by subject identifier hash
-        ///
-        /// Enables lookup of passport by product identifier (hashed).
-        /// The caller must compute the hash off-chain using the same algorithm:
-        /// - ProductClass: SHA-256(productId)
-        /// - Batch: SHA-256(productId + "#" + batchNumber)
-        /// - Item: SHA-256(productId + "#" + serialNumber)
-        ///
-        /// # Arguments
-        ///
-        /// * `subject_id_hash` - SHA-256 hash of the canonical subject identifier
-        ///
-        /// # Returns
-        ///
-        /// Token ID if found, None otherwise
-        ///
-        /// # Example
-        ///
-        /// ```ignore
-        /// // Off-chain: Compute hash
-        /// let hash = sha256("GTIN-123#LOT-2024-001");  // Batch granularity
-        ///
-        /// // On-chain: Lookup token ID
-        /// let token_id = contract.find_token_by_subject_id(hash);
-        /// ```
-        #[ink(message)]
-        pub fn find_token_by_subject_id(&self, subject_id_hash: [u8; 32]) -> Option<u128> {
-            self.subject_id_to_token.get(subject_id_hash)
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_recipient_allowlist(false),
+                Err(Error::Unauthorized)
+            );
         }
 
-        // Internal ownership helpers
+        #[ink::test]
+        fn blacklisted_accounts_cannot_register_receive_or_operate() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
 
-        fn transfer_token_from(&mut self, from: &Address, to: &Address, token_id: u128) -> Result<()> {
-            let caller = self.env().caller();
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            contract
+                .set_approval_for_all(accounts.charlie, true)
+                .unwrap();
+            contract.blacklist_account(accounts.bob).unwrap();
+            contract.blacklist_account(accounts.charlie).unwrap();
+            assert!(contract.is_blacklisted(accounts.bob));
 
-            // Require an existing passport record (same lifecycle rules)
-            let record = self.passports.get(token_id).ok_or(Error::TokenNotFound)?;
-            if record.status == PassportStatus::Revoked {
-                return Err(Error::PassportRevoked);
-            }
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::AccountBlacklisted)
+            );
+            assert_eq!(
+                contract.approve(accounts.bob, token_id),
+                Err(Error::AccountBlacklisted)
+            );
+            // Charlie's existing operator approval stops applying
+            assert!(!contract.is_approved_for_all(accounts.alice, accounts.charlie));
 
-            let owner = self.owner_of(token_id).ok_or(Error::TokenNotFound)?;
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                ),
+                Err(Error::AccountBlacklisted)
+            );
 
-            if owner != *from {
-                return Err(Error::NotOwner);
-            }
+            ink::env::test::set_caller(accounts.alice);
+            contract.unblacklist_account(accounts.bob).unwrap();
+            contract.transfer(accounts.bob, token_id).unwrap();
+        }
 
-            if !self.approved_or_owner(caller, token_id, owner) {
-                return Err(Error::NotApproved);
-            }
+        #[ink::test]
+        fn fresh_anchors_cannot_move_during_cooldown() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
 
-            self.clear_approval(token_id);
-            self.remove_token_from(from, token_id)?;
-            self.add_token_to(to, token_id)?;
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_update_transfer_cooldown(2).unwrap();
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Batch,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.transfers_allowed_from(token_id), Some(2));
+            assert_eq!(
+                contract.transfer(accounts.bob, token_id),
+                Err(Error::TransferThrottled)
+            );
 
-            self.env().emit_event(Transfer {
-                from: Some(*from),
-                to: Some(*to),
-                token_id,
-            });
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            contract.transfer(accounts.bob, token_id).unwrap();
 
-            Ok(())
+            // An update restarts the cooldown
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.transfers_allowed_from(token_id), Some(4));
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.transfer(accounts.charlie, token_id),
+                Err(Error::TransferThrottled)
+            );
         }
 
-        fn approved_or_owner(&self, caller: Address, token_id: u128, owner: Address) -> bool {
-            caller == owner
-                || self.token_approvals.get(token_id) == Some(caller)
-                || self.is_approved_for_all(owner, caller)
-        }
+        #[ink::test]
+        fn royalty_info_applies_basis_points() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
 
-        fn clear_approval(&mut self, token_id: u128) {
-            self.token_approvals.remove(token_id);
-        }
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.royalty_info(token_id, U256::from(1000)), None);
+            assert_eq!(
+                contract.set_royalty(token_id, accounts.eve, 10_001),
+                Err(Error::InvalidInput)
+            );
 
-        fn remove_token_from(&mut self, from: &Address, token_id: u128) -> Result<()> {
-            if !self.token_owner.contains(token_id) {
-                return Err(Error::TokenNotFound);
-            }
+            contract.set_royalty(token_id, accounts.eve, 250).unwrap();
+            assert_eq!(
+                contract.royalty_info(token_id, U256::from(1_000_000)),
+                Some((accounts.eve, U256::from(25_000)))
+            );
+            // No overflow at the largest price
+            assert!(contract.royalty_info(token_id, U256::MAX).is_some());
 
-            let count = self
-                .owned_tokens_count
-                .get(*from)
-                .unwrap_or(0)
-                .checked_sub(1)
-                .ok_or(Error::InvalidInput)?;
-            self.owned_tokens_count.insert(*from, &count);
-            self.token_owner.remove(token_id);
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_royalty(token_id, accounts.bob, 100),
+                Err(Error::Unauthorized)
+            );
+        }
 
-            Ok(())
+        #[ink::test]
+        fn supports_interface_reports_extensions() {
+            let contract = DppContractV2::new();
+            assert!(contract.supports_interface(INTERFACE_PSP34));
+            assert!(contract.supports_interface(ink::selector_bytes!("DPP::Royalty")));
+            assert!(contract.supports_interface(INTERFACE_DETECTION));
+            assert!(!contract.supports_interface([0xff; 4]));
         }
 
-        fn add_token_to(&mut self, to: &Address, token_id: u128) -> Result<()> {
-            if self.token_owner.contains(token_id) {
-                return Err(Error::InvalidInput);
+        #[ink::test]
+        fn batch_approve_and_clear() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let mut token_ids = Vec::new();
+            for _ in 0..2 {
+                token_ids.push(
+                    contract
+                        .register_passport(
+                            String::from("ipfs://v1"),
+                            [0u8; 32],
+                            String::from("application/vc+jwt"),
+                            Granularity::Item,
+                            None,
+                            None,
+                        )
+                        .unwrap(),
+                );
             }
+            let unknown = 99;
+            let mut requested = token_ids.clone();
+            requested.push(unknown);
+
+            let results = contract.batch_approve(accounts.bob, requested).unwrap();
+            assert_eq!(results[2].error, Some(Error::TokenNotFound));
+            assert!(results[..2].iter().all(|r| r.error.is_none()));
+            assert_eq!(contract.get_approved(token_ids[1]), Some(accounts.bob));
+            assert_eq!(
+                contract.batch_approve(accounts.bob, Vec::new()),
+                Err(Error::InvalidInput)
+            );
 
-            let count = self
-                .owned_tokens_count
-                .get(*to)
-                .unwrap_or(0)
-                .checked_add(1)
-                .ok_or(Error::InvalidInput)?;
-            self.owned_tokens_count.insert(*to, &count);
-            self.token_owner.insert(token_id, to);
+            ink::env::test::set_caller(accounts.bob);
+            let results = contract.batch_clear_approvals(token_ids.clone()).unwrap();
+            assert!(results.iter().all(|r| r.error == Some(Error::NotApproved)));
 
-            Ok(())
+            ink::env::test::set_caller(accounts.alice);
+            let seq = contract.latest_event_seq(token_ids[0]);
+            let results = contract.batch_clear_approvals(token_ids.clone()).unwrap();
+            assert!(results.iter().all(|r| r.error.is_none()));
+            assert_eq!(contract.get_approved(token_ids[0]), None);
+            assert_eq!(contract.latest_event_seq(token_ids[0]), seq + 1);
+
+            // Nothing left to clear: no further event
+            contract.batch_clear_approvals(token_ids.clone()).unwrap();
+            assert_eq!(contract.latest_event_seq(token_ids[0]), seq + 1);
         }
-    }
 
-    // Unit tests
+        #[ink::test]
+        fn custodian_is_tracked_apart_from_owner() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+            ink::env::test::set_caller(accounts.alice);
+            let token_id = contract
+                .register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(
+                contract.assign_custodian(token_id, accounts.alice),
+                Err(Error::InvalidInput)
+            );
+            contract
+                .assign_custodian(token_id, accounts.django)
+                .unwrap();
+            assert_eq!(contract.custodian_of(token_id), Some(accounts.django));
+            assert_eq!(contract.owner_of(token_id), Some(accounts.alice));
 
-        #[ink::test]
-        fn new_works() {
-            let contract = DppContractV2::new();
-            assert_eq!(contract.next_token_id(), 0);
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.assign_custodian(token_id, accounts.bob),
+                Err(Error::NotOwner)
+            );
+            assert_eq!(contract.release_custody(token_id), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller(accounts.django);
+            contract.release_custody(token_id).unwrap();
+            assert_eq!(contract.custodian_of(token_id), None);
+            assert_eq!(contract.release_custody(token_id), Err(Error::NotAllowed));
         }
 
         #[ink::test]
-        fn register_passport_works() {
+        fn custody_lease_can_be_returned_by_anyone_after_expiry() {
             let mut contract = DppContractV2::new();
             let accounts = ink::env::test::default_accounts();
-            let dataset_uri = String::from("ipfs://bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxqvyb3m");
-            let payload_hash = [0u8; 32];
-            let dataset_type = String::from("application/vc+jwt");
 
             ink::env::test::set_caller(accounts.alice);
-
             let token_id = contract
                 .register_passport(
-                    dataset_uri.clone(),
-                    payload_hash,
-                    dataset_type.clone(),
-                    Granularity::Batch,
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
                     None,
                 )
                 .unwrap();
+            let until = ink::env::block_number::<ink::env::DefaultEnvironment>() + 2;
+            contract
+                .lease_custody(token_id, accounts.django, until)
+                .unwrap();
+            assert_eq!(contract.custodian_of(token_id), Some(accounts.django));
+            assert_eq!(contract.custody_lease_until(token_id), Some(until));
+            assert_eq!(contract.release_custody(token_id), Err(Error::Unauthorized));
 
-            assert_eq!(token_id, 0);
-
-            let record = contract.get_passport(token_id).unwrap();
-            assert_eq!(record.token_id, token_id);
-            assert_eq!(record.dataset_uri, dataset_uri);
-            assert_eq!(record.payload_hash, payload_hash);
-            assert_eq!(record.dataset_type, dataset_type);
-            assert_eq!(record.version, 1);
-            assert_eq!(record.status, PassportStatus::Active);
-            assert_eq!(record.granularity, Granularity::Batch);
-            assert_eq!(record.subject_id_hash, None);
-
-            assert_eq!(contract.owner_of(token_id), Some(accounts.alice));
-            assert_eq!(contract.balance_of(accounts.alice), 1);
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.return_custody(token_id), Err(Error::Unauthorized));
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            contract.return_custody(token_id).unwrap();
+            assert_eq!(contract.custodian_of(token_id), None);
+            assert_eq!(contract.custody_lease_until(token_id), None);
+            assert_eq!(contract.return_custody(token_id), Err(Error::NotAllowed));
         }
 
         #[ink::test]
-        fn register_with_subject_id_hash_works() {
+        fn co_owned_token_moves_once_threshold_shares_approve() {
             let mut contract = DppContractV2::new();
-            let subject_id_hash = [42u8; 32];
+            let accounts = ink::env::test::default_accounts();
 
+            ink::env::test::set_caller(accounts.alice);
             let token_id = contract
                 .register_passport(
-                    String::from("ipfs://cid"),
+                    String::from("ipfs://v1"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::Item,
-                    Some(subject_id_hash),
+                    Granularity::Batch,
+                    None,
+                    None,
                 )
                 .unwrap();
+            let shares = ink::prelude::vec![
+                (accounts.alice, 50),
+                (accounts.bob, 30),
+                (accounts.charlie, 20),
+            ];
+            assert_eq!(
+                contract.enable_co_ownership(token_id, shares.clone(), 101),
+                Err(Error::InvalidInput)
+            );
+            contract.enable_co_ownership(token_id, shares, 60).unwrap();
+            assert_eq!(
+                contract.transfer(accounts.django, token_id),
+                Err(Error::NotAllowed)
+            );
 
-            let record = contract.get_passport(token_id).unwrap();
-            assert_eq!(record.subject_id_hash, Some(subject_id_hash));
-            assert_eq!(record.granularity, Granularity::Item);
+            ink::env::test::set_caller(accounts.charlie);
+            contract
+                .transfer_shares(token_id, accounts.bob, 20)
+                .unwrap();
+            let ownership = contract.co_ownership(token_id).unwrap();
+            assert_eq!(ownership.shares_of(accounts.bob), 50);
+            assert_eq!(ownership.shares.len(), 2);
+            assert_eq!(
+                contract.approve_co_owned_transfer(token_id, accounts.django),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.approve_co_owned_transfer(token_id, accounts.django),
+                Ok(false)
+            );
+            assert_eq!(contract.owner_of(token_id), Some(accounts.alice));
+
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(
+                contract.approve_co_owned_transfer(token_id, accounts.django),
+                Ok(true)
+            );
+            assert_eq!(contract.owner_of(token_id), Some(accounts.django));
+            assert_eq!(contract.co_ownership(token_id), None);
+            assert_eq!(contract.co_owned_transfer_approvals(token_id), None);
         }
 
         #[ink::test]
-        fn register_multiple_passports_works() {
+        fn register_batch_assigns_contiguous_ids() {
             let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            let input = |uri: &str| RegistrationInput {
+                dataset_uri: String::from(uri),
+                payload_hash: [0u8; 32],
+                dataset_type: String::from("application/vc+jwt"),
+                granularity: Granularity::Item,
+                subject_id_hash: None,
+                validity_blocks: None,
+            };
 
-            let token_id_0 = contract
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(
+                contract.register_batch(ink::prelude::vec![input("ipfs://a"), input("")]),
+                Err(Error::InvalidInput)
+            );
+            assert_eq!(
+                contract.register_batch(ink::prelude::vec![]),
+                Err(Error::InvalidInput)
+            );
+            assert_eq!(contract.total_supply(), 0);
+
+            let first = contract
                 .register_passport(
-                    String::from("ipfs://cid0"),
+                    String::from("ipfs://v1"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::ProductClass,
+                    Granularity::Item,
+                    None,
                     None,
                 )
                 .unwrap();
+            let token_ids = contract
+                .register_batch(ink::prelude::vec![input("ipfs://a"), input("ipfs://b")])
+                .unwrap();
+            assert_eq!(token_ids, ink::prelude::vec![first + 1, first + 2]);
+            assert_eq!(contract.owner_of(token_ids[1]), Some(accounts.alice));
+            assert_eq!(
+                contract.get_passport(token_ids[1]).unwrap().dataset_uri,
+                "ipfs://b"
+            );
+        }
 
-            let token_id_1 = contract
-                .register_passport(
-                    String::from("ipfs://cid1"),
-                    [1u8; 32],
+        #[ink::test]
+        fn reserved_token_ids_are_registered_by_their_issuer_only() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            let register = |contract: &mut DppContractV2, token_id: u128| {
+                contract.register_reserved_passport(
+                    token_id,
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::Batch,
+                    Granularity::Item,
+                    None,
                     None,
                 )
-                .unwrap();
+            };
 
-            assert_eq!(token_id_0, 0);
-            assert_eq!(token_id_1, 1);
+            ink::env::test::set_caller(accounts.alice);
+            let first = contract.reserve_token_ids(10).unwrap();
+            assert_eq!(
+                contract.token_id_reservations(accounts.alice),
+                ink::prelude::vec![(first, 10)]
+            );
+            assert_eq!(contract.next_token_id(), first + 10);
 
-            let record_0 = contract.get_passport(token_id_0).unwrap();
-            let record_1 = contract.get_passport(token_id_1).unwrap();
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(register(&mut contract, first + 3), Err(Error::Unauthorized));
 
-            assert_eq!(record_0.dataset_uri, String::from("ipfs://cid0"));
-            assert_eq!(record_0.granularity, Granularity::ProductClass);
-            assert_eq!(record_1.dataset_uri, String::from("ipfs://cid1"));
-            assert_eq!(record_1.granularity, Granularity::Batch);
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(
+                register(&mut contract, first + 10),
+                Err(Error::Unauthorized)
+            );
+            assert_eq!(register(&mut contract, first + 3), Ok(first + 3));
+            assert_eq!(register(&mut contract, first + 3), Err(Error::NotAllowed));
+            assert_eq!(contract.owner_of(first + 3), Some(accounts.alice));
+            assert_eq!(contract.next_token_id(), first + 10);
         }
 
         #[ink::test]
-        fn register_with_empty_uri_fails() {
+        fn reserved_ranges_are_dropped_once_registered() {
             let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            let register = |contract: &mut DppContractV2, token_id: u128| {
+                contract.register_reserved_passport(
+                    token_id,
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    None,
+                )
+            };
 
-            let result = contract.register_passport(
-                String::from(""),
-                [0u8; 32],
-                String::from("application/vc+jwt"),
-                Granularity::Batch,
-                None,
+            ink::env::test::set_caller(accounts.alice);
+            let first = contract.reserve_token_ids(2).unwrap();
+            let second = contract.reserve_token_ids(1).unwrap();
+            register(&mut contract, first + 1).unwrap();
+            assert_eq!(
+                contract.token_id_reservations(accounts.alice),
+                ink::prelude::vec![(first, 2), (second, 1)]
             );
-
-            assert_eq!(result, Err(Error::InvalidInput));
+            register(&mut contract, first).unwrap();
+            assert_eq!(
+                contract.token_id_reservations(accounts.alice),
+                ink::prelude::vec![(second, 1)]
+            );
+            register(&mut contract, second).unwrap();
+            assert!(contract.token_id_reservations(accounts.alice).is_empty());
+            assert!(!contract.id_reservations.contains(accounts.alice));
+
+            // The ID space cannot wrap around
+            contract.next_token_id = u128::MAX - 1;
+            assert_eq!(contract.reserve_token_ids(2), Err(Error::InvalidInput));
+            assert_eq!(contract.next_token_id(), u128::MAX - 1);
         }
 
         #[ink::test]
-        fn register_with_empty_type_fails() {
+        fn reserving_ids_requires_an_allowed_issuer() {
             let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
 
-            let result = contract.register_passport(
-                String::from("ipfs://cid"),
-                [0u8; 32],
-                String::from(""),
-                Granularity::Batch,
-                None,
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_allowlist_mode(true).unwrap();
+            contract.blacklist_account(accounts.charlie).unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.reserve_token_ids(1), Err(Error::NotTrustedIssuer));
+            ink::env::test::set_caller(accounts.charlie);
+            assert_eq!(
+                contract.reserve_token_ids(1),
+                Err(Error::AccountBlacklisted)
             );
 
-            assert_eq!(result, Err(Error::InvalidInput));
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_allowlist_mode(false).unwrap();
+            // Simulate a completed `rotate_issuer_key` to django
+            contract
+                .issuer_controllers
+                .insert(accounts.alice, &accounts.django);
+            assert_eq!(contract.reserve_token_ids(1), Err(Error::Unauthorized));
+            assert!(contract.token_id_reservations(accounts.alice).is_empty());
         }
 
         #[ink::test]
-        fn update_dataset_increments_version() {
+        fn reserved_ids_move_to_a_rotated_key() {
             let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            // Same fixed rotation vector as `rotated_key_takes_over_drafts_and_pre_claims`
+            ink::env::test::set_callee(Address::from([0x42; 20]));
+            let issuer = Address::from([
+                0x2c, 0x75, 0x36, 0xe3, 0x60, 0x5d, 0x9c, 0x16, 0xa7, 0xa3, 0xd7, 0xb1, 0x89, 0x8e,
+                0x52, 0x93, 0x96, 0xa6, 0x5c, 0x23,
+            ]);
+            let new_key = Address::from([0x77; 20]);
+            let signature = [
+                0xac, 0xf3, 0xa0, 0x82, 0xc0, 0x9b, 0x8c, 0xb3, 0xd0, 0x97, 0x63, 0x19, 0x45, 0xe0,
+                0xcb, 0x5a, 0x2a, 0xdf, 0x13, 0xfc, 0x2d, 0x89, 0x0f, 0xaf, 0x3a, 0x83, 0x7b, 0xec,
+                0x6e, 0x3b, 0x6c, 0x99, 0x26, 0x76, 0xa9, 0x12, 0x03, 0xcb, 0xd2, 0x06, 0x9b, 0xfd,
+                0xf5, 0xb1, 0x41, 0xd1, 0x2d, 0xa0, 0xa1, 0xcf, 0x1c, 0x68, 0xc4, 0xa5, 0xf2, 0x8d,
+                0x55, 0x45, 0x1d, 0x99, 0xd3, 0x57, 0x3b, 0x59, 0x00,
+            ];
+
+            ink::env::test::set_caller(issuer);
+            let first = contract.reserve_token_ids(3).unwrap();
 
+            ink::env::test::set_caller(accounts.bob);
+            contract
+                .rotate_issuer_key(issuer, new_key, signature)
+                .unwrap();
+            assert!(contract.token_id_reservations(issuer).is_empty());
+            assert_eq!(
+                contract.token_id_reservations(new_key),
+                ink::prelude::vec![(first, 3)]
+            );
+
+            ink::env::test::set_caller(new_key);
             let token_id = contract
-                .register_passport(
-                    String::from("ipfs://old"),
+                .register_reserved_passport(
+                    first + 2,
+                    String::from("ipfs://v1"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::Batch,
+                    Granularity::Item,
+                    None,
                     None,
                 )
                 .unwrap();
+            assert_eq!(contract.get_passport(token_id).unwrap().issuer, new_key);
+        }
 
-            contract
-                .update_dataset(
-                    token_id,
-                    String::from("ipfs://new"),
-                    [1u8; 32],
+        #[ink::test]
+        fn register_with_validity_sets_expiry() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            let register = |contract: &mut DppContractV2, validity_blocks: Option<u32>| {
+                contract.register_passport(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
                     String::from("application/vc+jwt"),
+                    Granularity::Item,
                     None,
+                    validity_blocks,
                 )
-                .unwrap();
+            };
 
-            let record = contract.get_passport(token_id).unwrap();
-            assert_eq!(record.version, 2);
-            assert_eq!(record.dataset_uri, String::from("ipfs://new"));
-            assert_eq!(record.payload_hash, [1u8; 32]);
-            // Granularity should remain unchanged
-            assert_eq!(record.granularity, Granularity::Batch);
+            ink::env::test::set_caller(accounts.alice);
+            assert_eq!(register(&mut contract, Some(0)), Err(Error::InvalidInput));
+
+            let now = ink::env::block_number::<ink::env::DefaultEnvironment>();
+            let token_id = register(&mut contract, Some(2)).unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().expires_at,
+                Some(now + 2)
+            );
+            assert!(!contract.is_expired(token_id));
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert!(contract.is_expired(token_id));
         }
 
         #[ink::test]
-        fn only_issuer_can_update() {
+        fn every_registration_path_accepts_validity() {
             let mut contract = DppContractV2::new();
             let accounts = ink::env::test::default_accounts();
+            let now = ink::env::block_number::<ink::env::DefaultEnvironment>();
+            let expires_at = |contract: &DppContractV2, token_id: u128| {
+                contract.passports.get(token_id).unwrap().expires_at
+            };
 
             ink::env::test::set_caller(accounts.alice);
-            let token_id = contract
-                .register_passport(
-                    String::from("ipfs://cid"),
+            let for_bob = contract
+                .register_passport_for(
+                    accounts.bob,
+                    String::from("ipfs://v1"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
                     Granularity::Item,
                     None,
+                    Some(3),
                 )
                 .unwrap();
+            assert_eq!(expires_at(&contract, for_bob), Some(now + 3));
 
-            // Try to update from different account
-            ink::env::test::set_caller(accounts.bob);
-            let result = contract.update_dataset(
-                token_id,
-                String::from("ipfs://new"),
-                [1u8; 32],
-                String::from("application/vc+jwt"),
-                None,
-            );
-
-            assert_eq!(result, Err(Error::Unauthorized));
-        }
-
-        #[ink::test]
-        fn update_nonexistent_token_fails() {
-            let mut contract = DppContractV2::new();
+            let draft = contract
+                .register_draft(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    Some(4),
+                )
+                .unwrap();
+            assert_eq!(expires_at(&contract, draft), Some(now + 4));
 
-            let result = contract.update_dataset(
-                999,
-                String::from("ipfs://new"),
-                [1u8; 32],
-                String::from("application/vc+jwt"),
-                None,
+            let first = contract.reserve_token_ids(1).unwrap();
+            contract
+                .register_reserved_passport(
+                    first,
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
+                    String::from("application/vc+jwt"),
+                    Granularity::Item,
+                    None,
+                    Some(5),
+                )
+                .unwrap();
+            assert_eq!(expires_at(&contract, first), Some(now + 5));
+
+            let input = |validity_blocks: Option<u32>| RegistrationInput {
+                dataset_uri: String::from("ipfs://v1"),
+                payload_hash: [0u8; 32],
+                dataset_type: String::from("application/vc+jwt"),
+                granularity: Granularity::Item,
+                subject_id_hash: None,
+                validity_blocks,
+            };
+            assert_eq!(
+                contract.register_batch(ink::prelude::vec![input(None), input(Some(0))]),
+                Err(Error::InvalidInput)
             );
-
-            assert_eq!(result, Err(Error::TokenNotFound));
+            let ids = contract
+                .register_batch(ink::prelude::vec![input(None), input(Some(6))])
+                .unwrap();
+            assert_eq!(expires_at(&contract, ids[0]), None);
+            assert_eq!(expires_at(&contract, ids[1]), Some(now + 6));
         }
 
         #[ink::test]
-        fn revoke_prevents_updates() {
+        fn recovery_keeps_soulbound_tokens_in_place() {
             let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            contract.set_recovery_challenge_period(0).unwrap();
 
+            ink::env::test::set_caller(accounts.alice);
             let token_id = contract
-                .register_passport(
+                .register_soulbound_passport(
                     String::from("ipfs://cid"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::ProductClass,
+                    Granularity::Item,
+                    None,
                     None,
                 )
                 .unwrap();
+            contract.set_owner_recovery(accounts.eve).unwrap();
 
+            ink::env::test::set_caller(accounts.eve);
             contract
-                .revoke_passport(token_id, Some(String::from("test reason")))
+                .initiate_recovery(accounts.alice, accounts.frank)
+                .unwrap();
+            let results = contract
+                .execute_recovery(accounts.alice, ink::prelude::vec![token_id])
                 .unwrap();
+            assert_eq!(results[0].error, Some(Error::NotAllowed));
+            assert_eq!(contract.owner_of(token_id), Some(accounts.alice));
+        }
 
-            let result = contract.update_dataset(
-                token_id,
-                String::from("ipfs://new"),
-                [1u8; 32],
-                String::from("application/vc+jwt"),
-                None,
+        #[ink::test]
+        fn fund_routing_and_registry_are_timelocked() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_timelock_delay(1).unwrap();
+            assert_eq!(
+                contract.set_fund_address(accounts.eve),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                contract.set_issuer_registry(Some(accounts.eve)),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                contract.set_recipient_allowlist(true),
+                Err(Error::NotAllowed)
             );
 
-            assert_eq!(result, Err(Error::PassportRevoked));
+            let action_id = contract
+                .queue_action(AdminAction::SetIssuerRegistry(Some(accounts.eve)))
+                .unwrap();
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            contract.execute_action(action_id).unwrap();
+            assert_eq!(contract.issuer_registry(), Some(accounts.eve));
         }
 
         #[ink::test]
-        fn revoke_works() {
+        fn admin_threshold_covers_every_admin_setter() {
             let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.grant_role(Role::Admin, accounts.bob).unwrap();
+            contract.set_admin_threshold(2).unwrap();
+            assert_eq!(
+                contract.set_fund_address(accounts.eve),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(contract.withdraw_fund(U256::zero()), Err(Error::NotAllowed));
+            assert_eq!(
+                contract.blacklist_account(accounts.eve),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(contract.propose_admin(accounts.eve), Err(Error::NotAllowed));
+
+            let action_id = contract
+                .queue_action(AdminAction::BlacklistAccount(accounts.eve))
+                .unwrap();
+            assert_eq!(contract.execute_action(action_id), Err(Error::NotApproved));
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.confirm_action(action_id).unwrap();
+            contract.execute_action(action_id).unwrap();
+            assert!(contract.is_blacklisted(accounts.eve));
 
+            // Per-token admin overrides also wait for the threshold; the
+            // issuer itself is not affected
+            ink::env::test::set_caller(accounts.charlie);
             let token_id = contract
                 .register_passport(
                     String::from("ipfs://cid"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::Batch,
+                    Granularity::Item,
+                    None,
                     None,
                 )
                 .unwrap();
+            contract.set_min_update_interval(token_id, 5).unwrap();
 
-            contract
-                .revoke_passport(token_id, Some(String::from("product recalled")))
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.set_min_update_interval(token_id, 0),
+                Err(Error::NotAllowed)
+            );
+            assert_eq!(
+                contract.prune_custody_history(token_id, 0),
+                Err(Error::NotAllowed)
+            );
+            let action_id = contract
+                .queue_action(AdminAction::SetMinUpdateInterval(token_id, 0))
                 .unwrap();
-
-            let record = contract.get_passport(token_id).unwrap();
-            assert_eq!(record.status, PassportStatus::Revoked);
+            ink::env::test::set_caller(accounts.alice);
+            contract.confirm_action(action_id).unwrap();
+            contract.execute_action(action_id).unwrap();
+            assert_eq!(contract.min_update_interval(token_id), 0);
         }
 
         #[ink::test]
-        fn only_issuer_can_revoke() {
+        fn draft_records_are_hidden_from_other_callers() {
             let mut contract = DppContractV2::new();
             let accounts = ink::env::test::default_accounts();
 
             ink::env::test::set_caller(accounts.alice);
             let token_id = contract
-                .register_passport(
+                .register_draft(
                     String::from("ipfs://cid"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
                     Granularity::Item,
                     None,
+                    None,
                 )
                 .unwrap();
+            assert!(contract.get_passport(token_id).is_some());
+            assert_eq!(contract.owner_of(token_id), Some(accounts.alice));
 
-            // Try to revoke from different account
             ink::env::test::set_caller(accounts.bob);
-            let result = contract.revoke_passport(token_id, Some(String::from("reason")));
+            assert_eq!(contract.get_passport(token_id), None);
+            assert_eq!(contract.get_version(token_id, 1), None);
+            assert!(contract.get_version_history(token_id).is_empty());
+            assert_eq!(contract.owner_of(token_id), None);
+            assert!(contract.get_status_history(token_id, 0, 10).is_empty());
+            assert!(contract.get_custody_history(token_id).is_empty());
+            assert_eq!(contract.status_history_len(token_id), 0);
+            assert_eq!(contract.last_activity(token_id), None);
+            assert_eq!(contract.self_check(token_id), None);
+            assert_eq!(
+                contract.quote_update(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                ),
+                Err(Error::TokenNotFound)
+            );
 
-            assert_eq!(result, Err(Error::Unauthorized));
+            ink::env::test::set_caller(accounts.alice);
+            assert!(contract.self_check(token_id).is_some());
+        }
+
+        #[ink::test]
+        fn recount_tracks_burns_behind_the_cursor() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            for _ in 0..2 {
+                contract
+                    .register_passport(
+                        String::from("ipfs://v1"),
+                        [0u8; 32],
+                        String::from("application/vc+jwt"),
+                        Granularity::Batch,
+                        None,
+                        None,
+                    )
+                    .unwrap();
+            }
+
+            contract.start_migration(Migration::RecountTokens).unwrap();
+            assert_eq!(contract.continue_migration(1), Ok(false));
+
+            // Token 0 was already counted; burning it must not be lost on completion
+            contract.burn(0).unwrap();
+            assert_eq!(contract.continue_migration(1), Ok(true));
+
+            assert_eq!(contract.health().live_tokens, 1);
+            assert!(contract.health().healthy);
         }
 
         #[ink::test]
-        fn transfer_works() {
+        fn signed_registration_mints_to_the_signing_issuer() {
             let mut contract = DppContractV2::new();
             let accounts = ink::env::test::default_accounts();
 
-            ink::env::test::set_caller(accounts.alice);
-            let token_id = contract
-                .register_passport(
-                    String::from("ipfs://cid"),
+            // Fixed secp256k1 vector: the key
+            // 0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318
+            // signs `registration_message` for this contract address and nonce 0.
+            ink::env::test::set_callee(Address::from([0x42; 20]));
+            let issuer = Address::from([
+                0x2c, 0x75, 0x36, 0xe3, 0x60, 0x5d, 0x9c, 0x16, 0xa7, 0xa3, 0xd7, 0xb1, 0x89, 0x8e,
+                0x52, 0x93, 0x96, 0xa6, 0x5c, 0x23,
+            ]);
+            let signature = [
+                0x41, 0x17, 0x87, 0x8e, 0xaa, 0x2d, 0xea, 0x57, 0xba, 0x7e, 0x8d, 0x2d, 0xef, 0x9b,
+                0x35, 0x79, 0xc1, 0xb2, 0x06, 0x19, 0x8b, 0x55, 0xb2, 0x42, 0x0f, 0x92, 0x19, 0xa5,
+                0x68, 0x9d, 0xa4, 0x20, 0x1e, 0xd0, 0xa1, 0x98, 0xc3, 0xd8, 0xbc, 0xa1, 0x11, 0x7a,
+                0xdd, 0x4e, 0x21, 0x6d, 0x10, 0x3a, 0xaa, 0x1a, 0x6f, 0x76, 0x83, 0x70, 0xd4, 0xa1,
+                0x37, 0xe1, 0x20, 0x7a, 0x02, 0x83, 0x69, 0x23, 0x00,
+            ];
+            let register = |contract: &mut DppContractV2| {
+                contract.register_passport_signed(
+                    issuer,
+                    String::from("ipfs://v1"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::Item,
+                    Granularity::Batch,
+                    None,
                     None,
+                    signature,
                 )
-                .unwrap();
+            };
 
-            assert_eq!(contract.owner_of(token_id), Some(accounts.alice));
+            // Any relayer may submit; the passport belongs to the signing issuer
+            ink::env::test::set_caller(accounts.charlie);
+            let token_id = register(&mut contract).unwrap();
+            assert_eq!(contract.owner_of(token_id), Some(issuer));
+            assert_eq!(contract.get_passport(token_id).unwrap().issuer, issuer);
+            assert_eq!(contract.registration_nonce(issuer), 1);
 
-            contract.transfer(accounts.bob, token_id).unwrap();
-            assert_eq!(contract.owner_of(token_id), Some(accounts.bob));
-            assert_eq!(contract.balance_of(accounts.alice), 0);
-            assert_eq!(contract.balance_of(accounts.bob), 1);
+            // The nonce moved on, so the same signature cannot be replayed
+            assert_eq!(register(&mut contract), Err(Error::InvalidSignature));
         }
 
         #[ink::test]
-        fn approve_and_transfer_from_works() {
+        fn staged_update_is_not_overwritten_and_keeps_the_reviewer() {
             let mut contract = DppContractV2::new();
             let accounts = ink::env::test::default_accounts();
 
             ink::env::test::set_caller(accounts.alice);
+            contract.set_reviewer(Some(accounts.bob)).unwrap();
             let token_id = contract
                 .register_passport(
-                    String::from("ipfs://cid"),
+                    String::from("ipfs://v1"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::Item,
+                    Granularity::Batch,
+                    None,
                     None,
                 )
                 .unwrap();
+            // The held Draft would otherwise publish without review
+            assert_eq!(contract.set_reviewer(None), Err(Error::NotAllowed));
 
-            contract.approve(accounts.charlie, token_id).unwrap();
-            assert_eq!(contract.get_approved(token_id), Some(accounts.charlie));
+            ink::env::test::set_caller(accounts.bob);
+            contract.approve_publication(token_id, 1).unwrap();
 
-            ink::env::test::set_caller(accounts.charlie);
-            contract.transfer_from(accounts.alice, accounts.bob, token_id).unwrap();
-            assert_eq!(contract.owner_of(token_id), Some(accounts.bob));
+            ink::env::test::set_caller(accounts.alice);
+            let update = |contract: &mut DppContractV2, payload_hash: [u8; 32]| {
+                contract.update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    payload_hash,
+                    String::from("application/vc+jwt"),
+                    None,
+                    None,
+                    None,
+                )
+            };
+            update(&mut contract, [1u8; 32]).unwrap();
+            assert_eq!(update(&mut contract, [2u8; 32]), Err(Error::NotAllowed));
+            assert_eq!(
+                contract
+                    .get_pending_update(token_id)
+                    .unwrap()
+                    .entry
+                    .payload_hash,
+                [1u8; 32]
+            );
+            assert_eq!(contract.set_reviewer(None), Err(Error::NotAllowed));
+
+            // Approval honours the pause
+            contract.pause().unwrap();
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.approve_publication(token_id, 2),
+                Err(Error::ContractPaused)
+            );
+            ink::env::test::set_caller(accounts.alice);
+            contract.unpause().unwrap();
+
+            ink::env::test::set_caller(accounts.bob);
+            contract.approve_publication(token_id, 2).unwrap();
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.set_reviewer(None).unwrap();
+            assert_eq!(contract.get_reviewer(accounts.alice), None);
         }
 
         #[ink::test]
-        fn revoked_passport_cannot_transfer() {
+        fn issuance_policy_follows_the_controlling_key() {
             let mut contract = DppContractV2::new();
             let accounts = ink::env::test::default_accounts();
 
             ink::env::test::set_caller(accounts.alice);
+            contract
+                .set_issuance_policy(ink::prelude::vec![accounts.bob], 1)
+                .unwrap();
             let token_id = contract
-                .register_passport(
-                    String::from("ipfs://cid"),
+                .register_draft(
+                    String::from("ipfs://v1"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
                     Granularity::Item,
                     None,
+                    None,
                 )
                 .unwrap();
+            // The proposal must not become activatable without co-signers
+            assert_eq!(contract.clear_issuance_policy(), Err(Error::NotAllowed));
 
-            contract.revoke_passport(token_id, None).unwrap();
-            let result = contract.transfer(accounts.bob, token_id);
-            assert_eq!(result, Err(Error::PassportRevoked));
+            // After a key rotation, the new key's policy governs the identity's Drafts
+            contract
+                .issuer_controllers
+                .insert(accounts.alice, &accounts.django);
+            assert_eq!(contract.get_issuance_policy(accounts.alice), None);
+
+            ink::env::test::set_caller(accounts.django);
+            contract
+                .set_issuance_policy(ink::prelude::vec![accounts.eve], 1)
+                .unwrap();
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.approve_activation(token_id),
+                Err(Error::Unauthorized)
+            );
+            ink::env::test::set_caller(accounts.eve);
+            contract.approve_activation(token_id).unwrap();
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Active
+            );
+
+            ink::env::test::set_caller(accounts.alice);
+            contract.clear_issuance_policy().unwrap();
         }
 
         #[ink::test]
-        fn cannot_revoke_already_revoked() {
+        fn rotated_key_takes_over_drafts_and_pre_claims() {
             let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
 
+            // Fixed secp256k1 vector: the key
+            // 0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318
+            // signs `issuer_rotation_message(issuer, new_key)` for this contract
+            // address and rotation nonce 0.
+            ink::env::test::set_callee(Address::from([0x42; 20]));
+            let issuer = Address::from([
+                0x2c, 0x75, 0x36, 0xe3, 0x60, 0x5d, 0x9c, 0x16, 0xa7, 0xa3, 0xd7, 0xb1, 0x89, 0x8e,
+                0x52, 0x93, 0x96, 0xa6, 0x5c, 0x23,
+            ]);
+            let new_key = Address::from([0x77; 20]);
+            let signature = [
+                0xac, 0xf3, 0xa0, 0x82, 0xc0, 0x9b, 0x8c, 0xb3, 0xd0, 0x97, 0x63, 0x19, 0x45, 0xe0,
+                0xcb, 0x5a, 0x2a, 0xdf, 0x13, 0xfc, 0x2d, 0x89, 0x0f, 0xaf, 0x3a, 0x83, 0x7b, 0xec,
+                0x6e, 0x3b, 0x6c, 0x99, 0x26, 0x76, 0xa9, 0x12, 0x03, 0xcb, 0xd2, 0x06, 0x9b, 0xfd,
+                0xf5, 0xb1, 0x41, 0xd1, 0x2d, 0xa0, 0xa1, 0xcf, 0x1c, 0x68, 0xc4, 0xa5, 0xf2, 0x8d,
+                0x55, 0x45, 0x1d, 0x99, 0xd3, 0x57, 0x3b, 0x59, 0x00,
+            ];
+
+            ink::env::test::set_caller(issuer);
             let token_id = contract
-                .register_passport(
-                    String::from("ipfs://cid"),
+                .register_draft(
+                    String::from("ipfs://v1"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::Batch,
+                    Granularity::Item,
+                    None,
                     None,
                 )
                 .unwrap();
 
-            contract.revoke_passport(token_id, None).unwrap();
+            // Anyone may relay the signed rotation
+            ink::env::test::set_caller(accounts.bob);
+            contract
+                .rotate_issuer_key(issuer, new_key, signature)
+                .unwrap();
+            assert_eq!(contract.issuer_controller(issuer), new_key);
 
-            let result = contract.revoke_passport(token_id, None);
-            assert_eq!(result, Err(Error::AlreadyRevoked));
-        }
+            ink::env::test::set_caller(issuer);
+            assert!(contract.my_drafts(0, 10).is_empty());
 
-        #[ink::test]
-        fn revoke_nonexistent_token_fails() {
-            let mut contract = DppContractV2::new();
+            ink::env::test::set_caller(new_key);
+            assert_eq!(contract.my_drafts(0, 10), ink::prelude::vec![token_id]);
 
-            let result = contract.revoke_passport(999, None);
-            assert_eq!(result, Err(Error::TokenNotFound));
+            // A subject pre-claimed by the new key settles on the identity's update
+            contract.pre_claim([9u8; 32]).unwrap();
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
+                    String::from("application/vc+jwt"),
+                    Some([9u8; 32]),
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert_eq!(contract.get_passport(token_id).unwrap().version, 2);
+
+            contract.activate_passport(token_id).unwrap();
+            assert!(contract.my_drafts(0, 10).is_empty());
+            assert_eq!(
+                contract.get_passport(token_id).unwrap().status,
+                PassportStatus::Active
+            );
         }
 
         #[ink::test]
-        fn all_granularity_levels_work() {
+        fn retired_key_cannot_register_after_rotation() {
             let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
 
-            // ProductClass
-            let token_id_0 = contract
-                .register_passport(
-                    String::from("ipfs://model"),
+            let register = |contract: &mut DppContractV2| {
+                contract.register_passport(
+                    String::from("ipfs://v1"),
                     [0u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::ProductClass,
-                    Some([10u8; 32]),
+                    Granularity::Batch,
+                    None,
+                    None,
                 )
-                .unwrap();
+            };
+            assert!(register(&mut contract).is_ok());
 
-            // Batch
-            let token_id_1 = contract
-                .register_passport(
-                    String::from("ipfs://batch"),
-                    [1u8; 32],
+            // Simulate a completed `rotate_issuer_key` to django
+            contract
+                .issuer_controllers
+                .insert(accounts.alice, &accounts.django);
+            assert_eq!(register(&mut contract), Err(Error::Unauthorized));
+            assert_eq!(
+                contract.register_draft(
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
                     String::from("application/vc+jwt"),
                     Granularity::Batch,
-                    Some([20u8; 32]),
-                )
-                .unwrap();
+                    None,
+                    None,
+                ),
+                Err(Error::Unauthorized)
+            );
+        }
 
-            // Item
-            let token_id_2 = contract
+        #[ink::test]
+        fn reviewer_follows_the_controlling_key() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            // Same fixed rotation vector as `rotated_key_takes_over_drafts_and_pre_claims`
+            ink::env::test::set_callee(Address::from([0x42; 20]));
+            let issuer = Address::from([
+                0x2c, 0x75, 0x36, 0xe3, 0x60, 0x5d, 0x9c, 0x16, 0xa7, 0xa3, 0xd7, 0xb1, 0x89, 0x8e,
+                0x52, 0x93, 0x96, 0xa6, 0x5c, 0x23,
+            ]);
+            let new_key = Address::from([0x77; 20]);
+            let signature = [
+                0xac, 0xf3, 0xa0, 0x82, 0xc0, 0x9b, 0x8c, 0xb3, 0xd0, 0x97, 0x63, 0x19, 0x45, 0xe0,
+                0xcb, 0x5a, 0x2a, 0xdf, 0x13, 0xfc, 0x2d, 0x89, 0x0f, 0xaf, 0x3a, 0x83, 0x7b, 0xec,
+                0x6e, 0x3b, 0x6c, 0x99, 0x26, 0x76, 0xa9, 0x12, 0x03, 0xcb, 0xd2, 0x06, 0x9b, 0xfd,
+                0xf5, 0xb1, 0x41, 0xd1, 0x2d, 0xa0, 0xa1, 0xcf, 0x1c, 0x68, 0xc4, 0xa5, 0xf2, 0x8d,
+                0x55, 0x45, 0x1d, 0x99, 0xd3, 0x57, 0x3b, 0x59, 0x00,
+            ];
+
+            ink::env::test::set_caller(issuer);
+            contract.set_reviewer(Some(accounts.charlie)).unwrap();
+            let token_id = contract
                 .register_passport(
-                    String::from("ipfs://item"),
-                    [2u8; 32],
+                    String::from("ipfs://v1"),
+                    [0u8; 32],
                     String::from("application/vc+jwt"),
                     Granularity::Item,
-                    Some([30u8; 32]),
+                    None,
+                    None,
                 )
                 .unwrap();
 
-            let record_0 = contract.get_passport(token_id_0).unwrap();
-            let record_1 = contract.get_passport(token_id_1).unwrap();
-            let record_2 = contract.get_passport(token_id_2).unwrap();
-
-            assert_eq!(record_0.granularity, Granularity::ProductClass);
-            assert_eq!(record_1.granularity, Granularity::Batch);
-            assert_eq!(record_2.granularity, Granularity::Item);
-
-            assert_eq!(record_0.subject_id_hash, Some([10u8; 32]));
-            assert_eq!(record_1.subject_id_hash, Some([20u8; 32]));
-            assert_eq!(record_2.subject_id_hash, Some([30u8; 32]));
-        }
+            ink::env::test::set_caller(accounts.bob);
+            contract
+                .rotate_issuer_key(issuer, new_key, signature)
+                .unwrap();
+            assert_eq!(contract.get_reviewer(issuer), Some(accounts.charlie));
 
-        #[ink::test]
-        fn get_nonexistent_passport_returns_none() {
-            let contract = DppContractV2::new();
-            assert_eq!(contract.get_passport(999), None);
-        }
+            // The retired key can no longer install its own reviewer
+            ink::env::test::set_caller(issuer);
+            assert_eq!(
+                contract.set_reviewer(Some(accounts.eve)),
+                Err(Error::Unauthorized)
+            );
 
-        #[ink::test]
-        fn update_with_empty_uri_fails() {
-            let mut contract = DppContractV2::new();
+            ink::env::test::set_caller(accounts.charlie);
+            contract.approve_publication(token_id, 1).unwrap();
 
-            let token_id = contract
-                .register_passport(
-                    String::from("ipfs://cid"),
-                    [0u8; 32],
+            // Updates by the new key are staged and block clearing the reviewer
+            ink::env::test::set_caller(new_key);
+            contract
+                .update_dataset(
+                    token_id,
+                    String::from("ipfs://v2"),
+                    [1u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::Batch,
+                    None,
+                    None,
                     None,
                 )
                 .unwrap();
+            assert_eq!(contract.set_reviewer(None), Err(Error::NotAllowed));
 
-            let result = contract.update_dataset(
-                token_id,
-                String::from(""), // Empty URI
-                [1u8; 32],
-                String::from("application/vc+jwt"),
-                None,
-            );
+            ink::env::test::set_caller(accounts.charlie);
+            contract.approve_publication(token_id, 2).unwrap();
+            assert_eq!(contract.get_passport(token_id).unwrap().version, 2);
 
-            assert_eq!(result, Err(Error::InvalidInput));
+            ink::env::test::set_caller(new_key);
+            contract.set_reviewer(None).unwrap();
+            assert_eq!(contract.get_reviewer(issuer), None);
         }
 
         #[ink::test]
-        fn update_with_empty_type_fails() {
+        fn rotation_leaves_a_shared_key_its_settings_and_other_drafts() {
             let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
 
-            let token_id = contract
-                .register_passport(
-                    String::from("ipfs://cid"),
-                    [0u8; 32],
-                    String::from("application/vc+jwt"),
-                    Granularity::Item,
-                    None,
-                )
+            // The key of `rotated_key_takes_over_drafts_and_pre_claims` signs
+            // `issuer_rotation_message(identity, new_key)` at nonce 0.
+            ink::env::test::set_callee(Address::from([0x42; 20]));
+            let key = Address::from([
+                0x2c, 0x75, 0x36, 0xe3, 0x60, 0x5d, 0x9c, 0x16, 0xa7, 0xa3, 0xd7, 0xb1, 0x89, 0x8e,
+                0x52, 0x93, 0x96, 0xa6, 0x5c, 0x23,
+            ]);
+            let identity = Address::from([0x55; 20]);
+            let new_key = Address::from([0x77; 20]);
+            let signature = [
+                0xa6, 0x8d, 0x58, 0x01, 0x29, 0x57, 0xd1, 0xb3, 0x17, 0xf6, 0x31, 0x91, 0x1b, 0x62,
+                0xbb, 0xa3, 0x4e, 0x6d, 0x51, 0xf2, 0x5d, 0x8b, 0x61, 0x03, 0x0b, 0x5c, 0x19, 0xe8,
+                0x98, 0x65, 0x1f, 0x78, 0x51, 0x24, 0xcd, 0x8e, 0xb9, 0x46, 0xcf, 0x88, 0x53, 0x0b,
+                0xb2, 0x93, 0xfa, 0x65, 0x5b, 0x7e, 0x53, 0xa1, 0x65, 0x70, 0x5f, 0x66, 0xd8, 0x0f,
+                0x78, 0x7a, 0x14, 0x1c, 0xed, 0xfc, 0x8b, 0xa8, 0x01,
+            ];
+
+            let register = |contract: &mut DppContractV2| {
+                contract
+                    .register_draft(
+                        String::from("ipfs://v1"),
+                        [0u8; 32],
+                        String::from("application/vc+jwt"),
+                        Granularity::Item,
+                        None,
+                        None,
+                    )
+                    .unwrap()
+            };
+            ink::env::test::set_caller(identity);
+            let identity_draft = register(&mut contract);
+
+            // Simulate an earlier rotation of `identity` to `key`
+            contract.issuer_controllers.insert(identity, &key);
+            contract.controlled_identities.insert(key, &1);
+            contract.issuer_drafts.remove(identity);
+            contract
+                .issuer_drafts
+                .insert(key, &ink::prelude::vec![identity_draft]);
+
+            ink::env::test::set_caller(key);
+            contract.set_reviewer(Some(accounts.charlie)).unwrap();
+            let own_draft = register(&mut contract);
+
+            ink::env::test::set_caller(accounts.bob);
+            contract
+                .rotate_issuer_key(identity, new_key, signature)
                 .unwrap();
 
-            let result = contract.update_dataset(
-                token_id,
-                String::from("ipfs://new"),
-                [1u8; 32],
-                String::from(""), // Empty type
-                None,
-            );
+            // `key` still issues as itself, so it keeps its reviewer and Draft
+            ink::env::test::set_caller(key);
+            assert_eq!(contract.get_reviewer(key), Some(accounts.charlie));
+            assert_eq!(contract.my_drafts(0, 10), ink::prelude::vec![own_draft]);
 
-            assert_eq!(result, Err(Error::InvalidInput));
+            ink::env::test::set_caller(new_key);
+            assert_eq!(contract.get_reviewer(identity), Some(accounts.charlie));
+            assert_eq!(
+                contract.my_drafts(0, 10),
+                ink::prelude::vec![identity_draft]
+            );
         }
 
         #[ink::test]
-        fn granularity_remains_immutable_after_update() {
+        fn draft_contents_are_announced_on_activation() {
             let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
 
-            // Register with ProductClass
+            ink::env::test::set_caller(accounts.alice);
             let token_id = contract
-                .register_passport(
-                    String::from("ipfs://old"),
-                    [0u8; 32],
+                .register_draft(
+                    String::from("ipfs://v1"),
+                    [1u8; 32],
                     String::from("application/vc+jwt"),
-                    Granularity::ProductClass,
-                    Some([42u8; 32]),
+                    Granularity::Item,
+                    None,
+                    None,
                 )
                 .unwrap();
-
-            // Update dataset
             contract
                 .update_dataset(
                     token_id,
-                    String::from("ipfs://new"),
-                    [1u8; 32],
+                    String::from("ipfs://v2"),
+                    [2u8; 32],
                     String::from("application/vc+jwt"),
-                    Some([99u8; 32]),
+                    None,
+                    None,
+                    None,
                 )
                 .unwrap();
 
-            let record = contract.get_passport(token_id).unwrap();
-            
-            // Granularity should NOT have changed
-            assert_eq!(record.granularity, Granularity::ProductClass);
-            
-            // But other fields should have updated
-            assert_eq!(record.dataset_uri, String::from("ipfs://new"));
-            assert_eq!(record.payload_hash, [1u8; 32]);
-            assert_eq!(record.subject_id_hash, Some([99u8; 32]));
-            assert_eq!(record.version, 2);
+            // Neither the registration nor the draft update emitted anything
+            assert_eq!(ink::env::test::recorded_events().into_iter().count(), 0);
+            assert_eq!(contract.latest_event_seq(token_id), 0);
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(contract.get_issuance_context(token_id), None);
+            assert_eq!(contract.effective_status(token_id), None);
+            assert!(!contract.is_expired(token_id));
+            assert!(contract.get_endorsements(token_id).is_empty());
+            assert_eq!(contract.get_sbom(token_id), None);
+
+            ink::env::test::set_caller(accounts.alice);
+            assert!(contract.get_issuance_context(token_id).is_some());
+            contract.activate_passport(token_id).unwrap();
+
+            // PassportRegistered + AnchorDigest + Transfer + PassportStatusChanged
+            // + PassportActivated, for the current version only
+            assert_eq!(ink::env::test::recorded_events().into_iter().count(), 5);
+            assert_eq!(contract.latest_event_seq(token_id), 5);
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.effective_status(token_id),
+                Some(PassportStatus::Active)
+            );
+            assert!(contract.get_issuance_context(token_id).is_some());
+        }
+
+        #[ink::test]
+        fn leaving_draft_status_announces_the_registration() {
+            let mut contract = DppContractV2::new();
+            let accounts = ink::env::test::default_accounts();
+
+            ink::env::test::set_caller(accounts.alice);
+            let register = |contract: &mut DppContractV2| {
+                contract
+                    .register_draft(
+                        String::from("ipfs://v1"),
+                        [1u8; 32],
+                        String::from("application/vc+jwt"),
+                        Granularity::Item,
+                        None,
+                        Some(1),
+                    )
+                    .unwrap()
+            };
+            let revoked = register(&mut contract);
+            let expiring = register(&mut contract);
+            assert_eq!(contract.total_supply(), 0);
+
+            // Anyone may expire passports, but not Drafts
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(
+                contract.expire_passport(expiring),
+                Err(Error::PassportDraft)
+            );
+
+            // PassportRegistered + AnchorDigest + Transfer precede the
+            // PassportStatusChanged and PassportRevoked of the revocation
+            ink::env::test::set_caller(accounts.alice);
+            contract.revoke_passport(revoked, None).unwrap();
+            assert_eq!(ink::env::test::recorded_events().into_iter().count(), 5);
+            assert_eq!(contract.total_supply(), 1);
+            assert_eq!(contract.my_drafts(0, 10), ink::prelude::vec![expiring]);
+            assert!(contract.self_check(revoked).unwrap().healthy);
+
+            // Burning a Draft likewise announces its mint before the burn
+            contract.burn(expiring).unwrap();
+            assert_eq!(ink::env::test::recorded_events().into_iter().count(), 10);
+            assert!(contract.my_drafts(0, 10).is_empty());
+            assert_eq!(contract.total_supply(), 1);
+            assert_eq!(contract.balance_of(accounts.alice), 1);
+            assert!(contract.health().healthy);
         }
     }
 }
@@ -0,0 +1,86 @@
+//! PSP34 (Polkadot NFT standard) interface.
+//!
+//! Lets wallets, marketplaces, and indexers that speak PSP34 display and
+//! transfer DPP tokens. Token IDs are `Id::U128(token_id)`.
+
+use ink::prelude::{string::String, vec::Vec};
+use ink::Address;
+
+/// PSP34 token identifier.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum Id {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Bytes(Vec<u8>),
+}
+
+impl Id {
+    /// Numeric token ID, if the identifier is an unsigned integer.
+    pub fn to_token_id(&self) -> Option<u128> {
+        match self {
+            Id::U8(id) => Some(u128::from(*id)),
+            Id::U16(id) => Some(u128::from(*id)),
+            Id::U32(id) => Some(u128::from(*id)),
+            Id::U64(id) => Some(u128::from(*id)),
+            Id::U128(id) => Some(*id),
+            Id::Bytes(_) => None,
+        }
+    }
+}
+
+/// PSP34 error types.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum PSP34Error {
+    /// Contract-specific failure (the DPP error, debug-formatted)
+    Custom(String),
+    /// Caller tried to approve itself
+    SelfApprove,
+    /// Caller is neither the owner nor approved
+    NotApproved,
+    /// Token already exists
+    TokenExists,
+    /// Token does not exist
+    TokenNotExists,
+    /// Receiver rejected the transfer
+    SafeTransferCheckFailed(String),
+}
+
+#[ink::trait_definition]
+pub trait PSP34 {
+    /// Identifier of the collection (the contract address).
+    #[ink(message)]
+    fn collection_id(&self) -> Id;
+
+    /// Number of tokens owned by `owner` (saturates at `u32::MAX`).
+    #[ink(message)]
+    fn balance_of(&self, owner: Address) -> u32;
+
+    #[ink(message)]
+    fn owner_of(&self, id: Id) -> Option<Address>;
+
+    /// Whether `operator` may transfer `id` (any token of `owner` if `None`).
+    #[ink(message)]
+    fn allowance(&self, owner: Address, operator: Address, id: Option<Id>) -> bool;
+
+    /// Approve or disapprove `operator` for `id` (all tokens if `None`).
+    #[ink(message)]
+    fn approve(
+        &mut self,
+        operator: Address,
+        id: Option<Id>,
+        approved: bool,
+    ) -> Result<(), PSP34Error>;
+
+    /// Transfer `id` from its owner to `to`. `data` is ignored.
+    #[ink(message)]
+    fn transfer(&mut self, to: Address, id: Id, data: Vec<u8>) -> Result<(), PSP34Error>;
+
+    /// Number of existing tokens.
+    #[ink(message)]
+    fn total_supply(&self) -> u128;
+}
@@ -0,0 +1,165 @@
+//! FIDES Issuer Registry Contract
+//!
+//! Consortium-managed list of vetted economic operators. The DPP contract can
+//! be pointed at a deployment of this contract and will then only accept
+//! registrations from issuers for which `is_registered` returns `true`.
+//!
+//! @license Apache-2.0
+
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+#[ink::contract]
+mod issuer_registry {
+    use ink::storage::Mapping;
+
+    /// Error types
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        /// Caller is not the registry administrator
+        Unauthorized,
+        /// Account is not registered
+        NotRegistered,
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Emitted when an issuer is added to the registry
+    #[ink(event)]
+    pub struct IssuerRegistered {
+        #[ink(topic)]
+        pub issuer: Address,
+    }
+
+    /// Emitted when an issuer is removed from the registry
+    #[ink(event)]
+    pub struct IssuerDeregistered {
+        #[ink(topic)]
+        pub issuer: Address,
+    }
+
+    /// Emitted when registry administration is handed over
+    #[ink(event)]
+    pub struct AdminChanged {
+        #[ink(topic)]
+        pub admin: Address,
+    }
+
+    #[ink(storage)]
+    pub struct IssuerRegistry {
+        // Registry administrator (deployer). Adds and removes issuers.
+        admin: Address,
+
+        // issuer -> registered
+        issuers: Mapping<Address, ()>,
+    }
+
+    impl IssuerRegistry {
+        /// Constructor.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                admin: Self::env().caller(),
+                issuers: Mapping::new(),
+            }
+        }
+
+        /// Register an issuer (admin-only).
+        #[ink(message)]
+        pub fn register(&mut self, issuer: Address) -> Result<()> {
+            self.ensure_admin()?;
+            self.issuers.insert(issuer, &());
+            self.env().emit_event(IssuerRegistered { issuer });
+            Ok(())
+        }
+
+        /// Remove an issuer (admin-only).
+        #[ink(message)]
+        pub fn deregister(&mut self, issuer: Address) -> Result<()> {
+            self.ensure_admin()?;
+            if self.issuers.take(issuer).is_none() {
+                return Err(Error::NotRegistered);
+            }
+            self.env().emit_event(IssuerDeregistered { issuer });
+            Ok(())
+        }
+
+        /// Whether an issuer is registered.
+        ///
+        /// The selector is fixed because the DPP contract calls this message
+        /// by selector (`IS_REGISTERED_SELECTOR`).
+        #[ink(message, selector = 0x49535245)]
+        pub fn is_registered(&self, issuer: Address) -> bool {
+            self.issuers.contains(issuer)
+        }
+
+        /// Hand registry administration to another account (admin-only).
+        #[ink(message)]
+        pub fn set_admin(&mut self, admin: Address) -> Result<()> {
+            self.ensure_admin()?;
+            self.admin = admin;
+            self.env().emit_event(AdminChanged { admin });
+            Ok(())
+        }
+
+        /// Get the registry administrator.
+        #[ink(message)]
+        pub fn admin(&self) -> Address {
+            self.admin
+        }
+
+        fn ensure_admin(&self) -> Result<()> {
+            if self.env().caller() != self.admin {
+                return Err(Error::Unauthorized);
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[ink::test]
+        fn new_works() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let registry = IssuerRegistry::new();
+            assert_eq!(registry.admin(), accounts.alice);
+            assert!(!registry.is_registered(accounts.bob));
+        }
+
+        #[ink::test]
+        fn admin_registers_and_deregisters() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut registry = IssuerRegistry::new();
+
+            registry.register(accounts.bob).unwrap();
+            assert!(registry.is_registered(accounts.bob));
+
+            registry.deregister(accounts.bob).unwrap();
+            assert!(!registry.is_registered(accounts.bob));
+            assert_eq!(registry.deregister(accounts.bob), Err(Error::NotRegistered));
+        }
+
+        #[ink::test]
+        fn only_admin_manages_registry() {
+            let accounts = ink::env::test::default_accounts();
+            ink::env::test::set_caller(accounts.alice);
+            let mut registry = IssuerRegistry::new();
+
+            ink::env::test::set_caller(accounts.bob);
+            assert_eq!(registry.register(accounts.bob), Err(Error::Unauthorized));
+            assert_eq!(registry.set_admin(accounts.bob), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller(accounts.alice);
+            registry.set_admin(accounts.bob).unwrap();
+            assert_eq!(registry.register(accounts.charlie), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller(accounts.bob);
+            registry.register(accounts.charlie).unwrap();
+            assert!(registry.is_registered(accounts.charlie));
+        }
+    }
+}